@@ -1,16 +1,109 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use arbitrage_system::metrics::{PriceStore, Sample};
+use chrono::{DateTime, Duration, Utc};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-fn process_prices_benchmark(c: &mut Criterion) {
-    c.bench_function("process 1000 prices", |b| {
+const EXCHANGES: [&str; 3] = ["binance", "bybit", "kucoin"];
+const SYMBOLS: usize = 20;
+const CAPACITY: usize = 1000;
+
+fn symbols() -> Vec<String> {
+    (0..SYMBOLS).map(|i| format!("TOKEN{}USDT", i)).collect()
+}
+
+fn sample(start: DateTime<Utc>, i: i64) -> Sample {
+    Sample {
+        timestamp: start + Duration::milliseconds(i * 250),
+        price: dec!(100) + Decimal::new(i % 200, 2),
+        volume: Some(dec!(10)),
+    }
+}
+
+/// Хранилище с заполненными рядами по всем биржам и парам.
+fn filled_store() -> (PriceStore, DateTime<Utc>) {
+    let store = PriceStore::new(CAPACITY);
+    let start = Utc::now() - Duration::hours(1);
+    for symbol in symbols() {
+        for exchange in EXCHANGES {
+            for i in 0..CAPACITY as i64 {
+                store.insert(exchange, &symbol, sample(start, i));
+            }
+        }
+    }
+    (store, start)
+}
+
+fn spread_benchmark(c: &mut Criterion) {
+    c.bench_function("spread", |b| {
+        b.iter(|| {
+            let price1 = black_box(dec!(100.0));
+            let price2 = black_box(dec!(101.0));
+            (price2 - price1) * dec!(100) / price1
+        })
+    });
+}
+
+fn insert_benchmark(c: &mut Criterion) {
+    let symbols = symbols();
+    let mut group = c.benchmark_group("store_insert");
+
+    // Заполненные буферы: каждая вставка вытесняет самую старую точку
+    let (store, start) = filled_store();
+    let mut i = CAPACITY as i64;
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("steady_state", |b| {
         b.iter(|| {
-            let price1 = dec!(100.0);
-            let price2 = dec!(101.0);
-            let _spread = (price2 - price1) * dec!(100) / price1;
+            let symbol = &symbols[i as usize % SYMBOLS];
+            store.insert(EXCHANGES[i as usize % EXCHANGES.len()], symbol, sample(start, i));
+            i += 1;
         })
     });
+
+    // Один проход сканера: все пары на всех биржах из четырёх потоков
+    let points = (EXCHANGES.len() * SYMBOLS) as u64;
+    group.throughput(Throughput::Elements(points * 4));
+    group.bench_function("concurrent_4_threads", |b| {
+        b.iter_batched(
+            || PriceStore::new(CAPACITY),
+            |store| {
+                std::thread::scope(|scope| {
+                    for thread in 0..4 {
+                        let store = &store;
+                        let symbols = &symbols;
+                        scope.spawn(move || {
+                            for (n, symbol) in symbols.iter().enumerate() {
+                                for exchange in EXCHANGES {
+                                    store.insert(exchange, symbol, sample(start, (thread * SYMBOLS + n) as i64));
+                                }
+                            }
+                        });
+                    }
+                });
+                store
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn query_benchmark(c: &mut Criterion) {
+    let (store, start) = filled_store();
+    let mut group = c.benchmark_group("store_query");
+
+    group.bench_function("latest", |b| b.iter(|| store.latest("bybit", black_box("TOKEN7USDT"))));
+    group.bench_function("last_100_points", |b| b.iter(|| store.last_n("bybit", black_box("TOKEN7USDT"), 100)));
+    group.bench_function("last_60_seconds", |b| {
+        let since = start + Duration::milliseconds(CAPACITY as i64 * 250) - Duration::seconds(60);
+        b.iter(|| store.since("bybit", black_box("TOKEN7USDT"), since))
+    });
+    group.bench_function("ohlc_1m", |b| {
+        b.iter(|| store.ohlc("bybit", black_box("TOKEN7USDT"), start, Duration::minutes(1)))
+    });
+    group.bench_function("latest_by_exchange", |b| b.iter(|| store.latest_by_exchange(black_box("TOKEN7USDT"))));
+    group.finish();
 }
 
-criterion_group!(benches, process_prices_benchmark);
+criterion_group!(benches, spread_benchmark, insert_benchmark, query_benchmark);
 criterion_main!(benches);
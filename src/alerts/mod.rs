@@ -1,32 +1,572 @@
+pub mod rules;
+pub mod silences;
+
+pub use rules::{AlertRule, Comparison, Metric, Observation, RuleEngine};
+pub use silences::{NewSilence, Silence, SilenceStore};
+
+use crate::config::AlertsConfig;
+use crate::metrics::RingBuffer;
+use crate::notifications::NotificationSystem;
 use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use rust_decimal::Decimal;
 use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AlertError {
+    #[error("alert {0} not found")]
+    NotFound(u64),
+    #[error("silence {0} not found")]
+    SilenceNotFound(u64),
+    #[error("invalid silence: {0}")]
+    InvalidSilence(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Что произошло. Метка типа используется в метриках и маршрутизации уведомлений.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertType {
+    /// Исполнимый спред между биржами выше порога
+    PriceArbitrage,
+    /// Реализованная волатильность выше порога
+    HighVolatility,
+    /// Пара перешла в турбулентный режим
+    MarketRegime,
+    /// Биржа отвечает медленно или с ошибками
+    ExchangeLatency,
+    /// Предохранитель исключил биржу из анализа или вернул её
+    CircuitBreaker,
+    /// Стейблкоин потерял привязку к опорной валюте
+    Depeg,
+}
+
+impl AlertType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertType::PriceArbitrage => "price_arbitrage",
+            AlertType::HighVolatility => "high_volatility",
+            AlertType::MarketRegime => "market_regime",
+            AlertType::ExchangeLatency => "exchange_latency",
+            AlertType::CircuitBreaker => "circuit_breaker",
+            AlertType::Depeg => "depeg",
+        }
+    }
+}
+
+impl fmt::Display for AlertType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Числа, из-за которых поднято оповещение.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertData {
+    /// Спред, волатильность или отклонение от привязки, в процентах
+    pub price_difference: Option<Decimal>,
+    pub volume: Option<Decimal>,
+    pub exchanges: Option<Vec<String>>,
+    /// Задержка запросов, мс
+    pub latency: Option<u64>,
+}
+
+/// Состояние оповещения. Оповещение разрешается само, когда условие не повторялось
+/// дольше срока подавления его типа.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertState {
+    Firing,
+    /// Оператор в курсе: повторы не отправляются, пока оповещение не разрешится
+    /// или не станет важнее
+    Acknowledged,
+    Resolved,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alert {
-    pub timestamp: DateTime<Utc>,
+    /// Присваивается AlertManager при отправке
+    #[serde(default)]
+    pub id: u64,
+    pub alert_type: AlertType,
     pub message: String,
+    pub timestamp: DateTime<Utc>,
+    /// Пара или валюта; пусто для оповещений о бирже
+    pub symbol: String,
+    /// От 1 до 10, 10 — требует немедленного вмешательства
+    pub severity: u8,
+    pub data: Option<AlertData>,
+    /// Повторов, подавленных с прошлого отправленного оповещения с тем же ключом
+    #[serde(default)]
+    pub suppressed: u64,
+    /// Правило, по которому поднято оповещение
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    /// Канал уведомлений из правила; пусто — во все каналы
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route: Option<String>,
+    pub state: AlertState,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
 }
 
+impl Alert {
+    pub fn new(alert_type: AlertType, severity: u8, symbol: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            id: 0,
+            alert_type,
+            message: message.into(),
+            timestamp: Utc::now(),
+            symbol: symbol.into(),
+            severity: severity.clamp(1, 10),
+            data: None,
+            suppressed: 0,
+            rule: None,
+            route: None,
+            state: AlertState::Firing,
+            acknowledged_at: None,
+            resolved_at: None,
+        }
+    }
+
+    pub fn with_data(mut self, data: AlertData) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Биржи из данных оповещения, по алфавиту.
+    pub fn exchanges(&self) -> Vec<String> {
+        let mut exchanges = self
+            .data
+            .as_ref()
+            .and_then(|data| data.exchanges.clone())
+            .unwrap_or_default();
+        exchanges.sort();
+        exchanges.dedup();
+        exchanges
+    }
+
+    /// Ключ, по которому повторы считаются одним оповещением.
+    pub fn key(&self) -> AlertKey {
+        AlertKey {
+            alert_type: self.alert_type,
+            symbol: self.symbol.clone(),
+            exchanges: self.exchanges(),
+        }
+    }
+}
+
+/// Одно и то же условие: тип, пара и биржи. Числа в сообщении не учитываются.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlertKey {
+    pub alert_type: AlertType,
+    pub symbol: String,
+    pub exchanges: Vec<String>,
+}
+
+/// Что стало с оповещением.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    Sent,
+    /// Повтор раньше срока, но важнее отправленного
+    Escalated,
+    /// Повтор раньше срока или после подтверждения; записан только в счётчики
+    Suppressed,
+    /// Попало под заглушку
+    Silenced,
+}
+
+/// Отбор оповещений из истории. Пустые поля не ограничивают.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AlertQuery {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    #[serde(rename = "type")]
+    pub alert_type: Option<AlertType>,
+    pub min_severity: Option<u8>,
+    pub symbol: Option<String>,
+    pub state: Option<AlertState>,
+    pub limit: Option<usize>,
+}
+
+impl AlertQuery {
+    fn matches(&self, alert: &Alert) -> bool {
+        self.since.is_none_or(|since| alert.timestamp >= since)
+            && self.until.is_none_or(|until| alert.timestamp <= until)
+            && self.alert_type.is_none_or(|alert_type| alert.alert_type == alert_type)
+            && self.min_severity.is_none_or(|severity| alert.severity >= severity)
+            && self.symbol.as_ref().is_none_or(|symbol| alert.symbol.eq_ignore_ascii_case(symbol))
+            && self.state.is_none_or(|state| alert.state == state)
+    }
+}
+
+/// Счётчики с запуска.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AlertStats {
+    pub sent: u64,
+    pub escalated: u64,
+    pub suppressed: u64,
+    pub suppressed_by_type: BTreeMap<AlertType, u64>,
+    pub silenced: u64,
+    pub resolved: u64,
+}
+
+/// Неразрешённое оповещение по ключу.
+#[derive(Debug)]
+struct Incident {
+    sent_at: DateTime<Utc>,
+    /// Последний повтор, в том числе подавленный
+    last_seen: DateTime<Utc>,
+    severity: u8,
+    suppressed: u64,
+    acknowledged: bool,
+}
+
+/// Принимает оповещения, подавляет повторы и хранит ограниченную историю.
+///
+/// Повтор по тому же ключу в пределах срока подавления не отправляется и не попадает
+/// в историю, только в счётчики. Исключение — повтор с большей важностью: он
+/// отправляется сразу, и срок отсчитывается заново. Первое оповещение после срока
+/// несёт число подавленных повторов. Подтверждённое оповещение не повторяется, пока
+/// не разрешится; разрешается оно, когда повторов нет дольше срока подавления.
+/// Оповещения под действующей заглушкой не отправляются. Отправленные уходят
+/// в каналы уведомлений.
 #[derive(Debug)]
 pub struct AlertManager {
-    alerts: Vec<Alert>,
+    config: AlertsConfig,
+    history: RingBuffer<Alert>,
+    incidents: HashMap<AlertKey, Incident>,
+    silences: SilenceStore,
+    notifier: Arc<NotificationSystem>,
+    next_id: u64,
+    stats: AlertStats,
+}
+
+impl Default for AlertManager {
+    fn default() -> Self {
+        Self::new(&AlertsConfig::default())
+    }
 }
 
 impl AlertManager {
-    pub fn new() -> Self {
+    /// Загружает сохранённые заглушки из `alerts.silences_file`.
+    pub fn new(config: &AlertsConfig) -> Self {
         Self {
-            alerts: Vec::new(),
+            config: config.clone(),
+            history: RingBuffer::new(config.history_size),
+            incidents: HashMap::new(),
+            silences: SilenceStore::load(config.silences_file.as_deref()),
+            notifier: Arc::new(NotificationSystem::new()),
+            next_id: 1,
+            stats: AlertStats::default(),
         }
     }
 
-    pub fn send_alert(&mut self, message: String) {
-        self.alerts.push(Alert {
-            timestamp: Utc::now(),
-            message,
-        });
+    pub fn with_notifier(mut self, notifier: NotificationSystem) -> Self {
+        self.notifier = Arc::new(notifier);
+        self
     }
 
-    pub fn get_alerts(&self) -> Vec<Alert> {
-        self.alerts.clone()
+    /// Применяет сроки подавления из перезагруженной конфигурации; размер истории
+    /// и файл заглушек не меняются.
+    pub fn configure(&mut self, config: &AlertsConfig) {
+        self.config = config.clone();
+    }
+
+    pub async fn send_alert(&mut self, mut alert: Alert) -> Delivery {
+        let now = alert.timestamp;
+        self.resolve_stale(now);
+        if let Some(silence) = self.silences.matching(&alert, now) {
+            self.stats.silenced += 1;
+            debug!("[{} {}] silenced by #{}: {}", alert.alert_type, alert.severity, silence.id, alert.message);
+            return Delivery::Silenced;
+        }
+
+        let key = alert.key();
+        let cooldown = self.cooldown(alert.alert_type);
+        let delivery = match self.incidents.get_mut(&key) {
+            Some(incident)
+                if alert.severity <= incident.severity && (incident.acknowledged || now - incident.sent_at < cooldown) =>
+            {
+                incident.suppressed += 1;
+                incident.last_seen = now;
+                self.stats.suppressed += 1;
+                *self.stats.suppressed_by_type.entry(alert.alert_type).or_default() += 1;
+                debug!("[{} {}] suppressed repeat: {}", alert.alert_type, alert.severity, alert.message);
+                return Delivery::Suppressed;
+            }
+            Some(incident) if alert.severity > incident.severity => Delivery::Escalated,
+            _ => Delivery::Sent,
+        };
+
+        let previous = self.incidents.insert(
+            key,
+            Incident {
+                sent_at: now,
+                last_seen: now,
+                severity: alert.severity,
+                suppressed: 0,
+                acknowledged: false,
+            },
+        );
+        alert.id = self.next_id;
+        self.next_id += 1;
+        alert.suppressed = previous.map(|incident| incident.suppressed).unwrap_or_default();
+        match delivery {
+            Delivery::Escalated => {
+                self.stats.escalated += 1;
+                warn!("[{} {}] escalated: {}", alert.alert_type, alert.severity, alert.message);
+            }
+            _ => {
+                self.stats.sent += 1;
+                warn!("[{} {}] {}", alert.alert_type, alert.severity, alert.message);
+            }
+        }
+        if !self.notifier.is_empty() {
+            // Рассылка не держит блокировку менеджера и не задерживает проход
+            let notifier = self.notifier.clone();
+            let alert = alert.clone();
+            tokio::spawn(async move { notifier.notify(&alert).await });
+        }
+        self.history.push(alert);
+        delivery
+    }
+
+    fn cooldown(&self, alert_type: AlertType) -> chrono::Duration {
+        chrono::Duration::from_std(self.config.cooldown(alert_type)).unwrap_or(chrono::Duration::MAX)
+    }
+
+    /// Разрешает оповещения, которые не повторялись дольше срока подавления.
+    /// Возвращает число разрешённых ключей.
+    pub fn resolve_stale(&mut self, now: DateTime<Utc>) -> usize {
+        let stale: Vec<AlertKey> = self
+            .incidents
+            .iter()
+            .filter(|(key, incident)| now - incident.last_seen >= self.cooldown(key.alert_type))
+            .map(|(key, _)| key.clone())
+            .collect();
+        if stale.is_empty() {
+            return 0;
+        }
+
+        for key in &stale {
+            self.incidents.remove(key);
+        }
+        for alert in self.history.iter_mut() {
+            if alert.state != AlertState::Resolved && stale.contains(&alert.key()) {
+                alert.state = AlertState::Resolved;
+                alert.resolved_at = Some(now);
+                debug!("Alert #{} resolved: {}", alert.id, alert.message);
+            }
+        }
+        self.stats.resolved += stale.len() as u64;
+        stale.len()
+    }
+
+    /// Подтверждает оповещение и все неразрешённые с тем же ключом.
+    pub fn acknowledge(&mut self, id: u64, now: DateTime<Utc>) -> Result<Alert, AlertError> {
+        let key = self
+            .history
+            .iter()
+            .find(|alert| alert.id == id)
+            .map(Alert::key)
+            .ok_or(AlertError::NotFound(id))?;
+
+        if let Some(incident) = self.incidents.get_mut(&key) {
+            incident.acknowledged = true;
+        }
+        let mut acknowledged = None;
+        for alert in self.history.iter_mut() {
+            if alert.state == AlertState::Firing && alert.key() == key {
+                alert.state = AlertState::Acknowledged;
+                alert.acknowledged_at = Some(now);
+            }
+            if alert.id == id {
+                acknowledged = Some(alert.clone());
+            }
+        }
+        acknowledged.ok_or(AlertError::NotFound(id))
+    }
+
+    /// Подтверждает все действующие оповещения. Возвращает их число.
+    pub fn acknowledge_all(&mut self, now: DateTime<Utc>) -> usize {
+        let firing: Vec<u64> = self
+            .history
+            .iter()
+            .filter(|alert| alert.state == AlertState::Firing)
+            .map(|alert| alert.id)
+            .collect();
+        for &id in &firing {
+            let _ = self.acknowledge(id, now);
+        }
+        firing.len()
+    }
+
+    pub fn add_silence(&mut self, silence: NewSilence, now: DateTime<Utc>) -> Result<Silence, AlertError> {
+        let silence = self.silences.add(silence, now)?;
+        info!("Silence #{} added until {}", silence.id, silence.ends_at.format("%Y-%m-%d %H:%M:%S UTC"));
+        Ok(silence)
+    }
+
+    /// Заглушка по последнему действующему оповещению: его тип, пара и биржа,
+    /// если биржа одна.
+    pub fn silence_latest(&mut self, duration_secs: u64, now: DateTime<Utc>) -> Result<Option<Silence>, AlertError> {
+        let Some(alert) = self.history.iter().rev().find(|alert| alert.state == AlertState::Firing) else {
+            return Ok(None);
+        };
+        let exchanges = alert.exchanges();
+        let silence = NewSilence {
+            alert_type: Some(alert.alert_type),
+            symbol: Some(alert.symbol.clone()).filter(|symbol| !symbol.is_empty()),
+            exchange: match exchanges.as_slice() {
+                [exchange] => Some(exchange.clone()),
+                _ => None,
+            },
+            duration_secs,
+            comment: format!("silenced from alert #{}", alert.id),
+        };
+        self.add_silence(silence, now).map(Some)
+    }
+
+    pub fn expire_silence(&mut self, id: u64, now: DateTime<Utc>) -> Result<Silence, AlertError> {
+        let silence = self.silences.expire(id, now)?;
+        info!("Silence #{} removed", id);
+        Ok(silence)
+    }
+
+    pub fn silences(&self, now: DateTime<Utc>) -> Vec<Silence> {
+        self.silences.active(now)
+    }
+
+    /// Оповещения из истории, от новых к старым.
+    pub fn query(&self, query: &AlertQuery) -> Vec<Alert> {
+        self.history
+            .iter()
+            .rev()
+            .filter(|alert| query.matches(alert))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    pub fn stats(&self) -> &AlertStats {
+        &self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn manager(history_size: usize) -> AlertManager {
+        AlertManager::new(&AlertsConfig {
+            cooldown_secs: 60,
+            history_size,
+            silences_file: None,
+            ..AlertsConfig::default()
+        })
+    }
+
+    fn alert(severity: u8, at: DateTime<Utc>) -> Alert {
+        let mut alert = Alert::new(AlertType::HighVolatility, severity, "BTCUSDT", "BTCUSDT volatility 6.00%").with_data(
+            AlertData {
+                price_difference: None,
+                volume: None,
+                exchanges: Some(vec!["Bybit".to_string()]),
+                latency: None,
+            },
+        );
+        alert.timestamp = at;
+        alert
+    }
+
+    #[tokio::test]
+    async fn repeats_within_cooldown_are_suppressed() {
+        let mut manager = manager(100);
+        let start = Utc::now();
+
+        assert_eq!(manager.send_alert(alert(7, start)).await, Delivery::Sent);
+        assert_eq!(manager.send_alert(alert(7, start + Duration::seconds(10))).await, Delivery::Suppressed);
+        assert_eq!(manager.send_alert(alert(6, start + Duration::seconds(59))).await, Delivery::Suppressed);
+
+        assert_eq!(manager.stats().sent, 1);
+        assert_eq!(manager.stats().suppressed, 2);
+        assert_eq!(manager.stats().suppressed_by_type[&AlertType::HighVolatility], 2);
+        assert_eq!(manager.query(&AlertQuery::default()).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn fires_again_after_cooldown() {
+        let mut manager = manager(100);
+        let start = Utc::now();
+
+        manager.send_alert(alert(7, start)).await;
+        manager.send_alert(alert(7, start + Duration::seconds(30))).await;
+        // Повтор 30 с назад держит инцидент открытым, но срок считается от отправки
+        assert_eq!(manager.send_alert(alert(7, start + Duration::seconds(60))).await, Delivery::Sent);
+
+        let history = manager.query(&AlertQuery::default());
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].suppressed, 1);
+        assert_eq!(manager.stats().resolved, 0);
+    }
+
+    #[tokio::test]
+    async fn quiet_incident_resolves_and_fires_fresh() {
+        let mut manager = manager(100);
+        let start = Utc::now();
+
+        manager.send_alert(alert(7, start)).await;
+        assert_eq!(manager.resolve_stale(start + Duration::seconds(60)), 1);
+        assert_eq!(manager.query(&AlertQuery::default())[0].state, AlertState::Resolved);
+
+        assert_eq!(manager.send_alert(alert(7, start + Duration::seconds(61))).await, Delivery::Sent);
+        assert_eq!(manager.query(&AlertQuery::default())[0].suppressed, 0);
+    }
+
+    #[tokio::test]
+    async fn acknowledged_incident_escalates_only_on_higher_severity() {
+        let mut manager = manager(100);
+        let start = Utc::now();
+
+        manager.send_alert(alert(7, start)).await;
+        let first = manager.query(&AlertQuery::default())[0].id;
+        let acknowledged = manager.acknowledge(first, start + Duration::seconds(5)).unwrap();
+        assert_eq!(acknowledged.state, AlertState::Acknowledged);
+
+        // Подтверждённый инцидент молчит и после срока подавления, пока повторы не утихнут
+        assert_eq!(manager.send_alert(alert(7, start + Duration::seconds(50))).await, Delivery::Suppressed);
+        assert_eq!(manager.send_alert(alert(7, start + Duration::seconds(100))).await, Delivery::Suppressed);
+        assert_eq!(manager.send_alert(alert(9, start + Duration::seconds(110))).await, Delivery::Escalated);
+
+        let history = manager.query(&AlertQuery::default());
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[0].severity, history[0].state, history[0].suppressed), (9, AlertState::Firing, 2));
+        assert_eq!(manager.stats().escalated, 1);
+    }
+
+    #[tokio::test]
+    async fn history_keeps_the_newest_alerts() {
+        let mut manager = manager(3);
+        let start = Utc::now();
+
+        for (i, symbol) in ["BTCUSDT", "ETHUSDT", "SOLUSDT", "XRPUSDT", "ADAUSDT"].into_iter().enumerate() {
+            let mut alert = alert(7, start + Duration::seconds(i as i64));
+            alert.symbol = symbol.to_string();
+            assert_eq!(manager.send_alert(alert).await, Delivery::Sent);
+        }
+
+        let ids: Vec<u64> = manager.query(&AlertQuery::default()).iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec![5, 4, 3]);
+        assert!(matches!(manager.acknowledge(1, start), Err(AlertError::NotFound(1))));
     }
 }
@@ -0,0 +1,378 @@
+use super::{Alert, AlertData, AlertType};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Величина, за которой следит правило.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// Исполнимый спред по вершинам стаканов, %; по паре и связке бирж покупки и продажи
+    Spread,
+    /// Реализованная волатильность за короткое окно, % за √час; по паре и бирже
+    Volatility,
+    /// Доля успешных запросов в окне; по бирже
+    SuccessRate,
+    /// p99 задержки запросов, мс; по бирже
+    Latency,
+}
+
+impl Metric {
+    pub fn alert_type(self) -> AlertType {
+        match self {
+            Metric::Spread => AlertType::PriceArbitrage,
+            Metric::Volatility => AlertType::HighVolatility,
+            Metric::SuccessRate | Metric::Latency => AlertType::ExchangeLatency,
+        }
+    }
+
+    /// Метрика биржи, а не пары: область `symbols` к ней неприменима.
+    pub fn per_exchange(self) -> bool {
+        matches!(self, Metric::SuccessRate | Metric::Latency)
+    }
+
+    fn format(self, value: f64) -> String {
+        match self {
+            Metric::Spread | Metric::Volatility => format!("{:.2}%", value),
+            Metric::SuccessRate => format!("{:.2}%", value * 100.0),
+            Metric::Latency => format!("{:.0}ms", value),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Metric::Spread => "spread",
+            Metric::Volatility => "volatility",
+            Metric::SuccessRate => "success rate",
+            Metric::Latency => "p99 latency",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparison {
+    #[serde(rename = ">")]
+    Above,
+    #[serde(rename = ">=")]
+    AtLeast,
+    #[serde(rename = "<")]
+    Below,
+    #[serde(rename = "<=")]
+    AtMost,
+}
+
+impl Comparison {
+    pub fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Above => value > threshold,
+            Comparison::AtLeast => value >= threshold,
+            Comparison::Below => value < threshold,
+            Comparison::AtMost => value <= threshold,
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Comparison::Above => ">",
+            Comparison::AtLeast => ">=",
+            Comparison::Below => "<",
+            Comparison::AtMost => "<=",
+        })
+    }
+}
+
+/// Правило оповещения из секции `alerts.rules`:
+/// «spread BTCUSDT Binance/Bybit > 0.4 в течение 10 секунд».
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: Metric,
+    pub op: Comparison,
+    /// В единицах метрики: проценты, доля или миллисекунды
+    pub threshold: f64,
+    /// Сколько условие должно держаться непрерывно; 0 — сразу
+    #[serde(default)]
+    pub for_secs: u64,
+    /// Пары; пусто — все
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    /// Биржи; пусто — все. Для спреда в области должны быть обе биржи связки
+    #[serde(default)]
+    pub exchanges: Vec<String>,
+    #[serde(default = "default_severity")]
+    pub severity: u8,
+    /// Куда отправлять оповещение; пусто — во все каналы
+    #[serde(default)]
+    pub route: Option<String>,
+}
+
+fn default_severity() -> u8 {
+    5
+}
+
+impl AlertRule {
+    pub fn new(name: &str, metric: Metric, op: Comparison, threshold: f64, severity: u8) -> Self {
+        Self {
+            name: name.to_string(),
+            metric,
+            op,
+            threshold,
+            for_secs: 0,
+            symbols: Vec::new(),
+            exchanges: Vec::new(),
+            severity,
+            route: None,
+        }
+    }
+
+    fn applies_to(&self, observation: &Observation) -> bool {
+        observation.metric == self.metric
+            && (self.symbols.is_empty() || self.symbols.iter().any(|s| s.eq_ignore_ascii_case(&observation.symbol)))
+            && (self.exchanges.is_empty()
+                || observation
+                    .exchanges
+                    .iter()
+                    .all(|exchange| self.exchanges.iter().any(|e| e.eq_ignore_ascii_case(exchange))))
+    }
+}
+
+/// Правила по умолчанию: пороги, которые раньше были зашиты в MarketMonitor.
+pub fn default_rules() -> Vec<AlertRule> {
+    vec![
+        AlertRule::new("high_volatility", Metric::Volatility, Comparison::Above, 5.0, 7),
+        AlertRule::new("wide_spread", Metric::Spread, Comparison::Above, 2.0, 8),
+        AlertRule::new("degraded_exchange", Metric::SuccessRate, Comparison::Below, 0.95, 9),
+    ]
+}
+
+/// Одно значение метрики за проход.
+#[derive(Debug, Clone)]
+pub struct Observation {
+    pub metric: Metric,
+    /// Пусто для метрик биржи
+    pub symbol: String,
+    /// Для спреда — биржа покупки и биржа продажи
+    pub exchanges: Vec<String>,
+    pub value: f64,
+    /// Объём или задержка, попадают в данные оповещения
+    pub volume: Option<Decimal>,
+    pub latency: Option<u64>,
+}
+
+impl Observation {
+    pub fn new(metric: Metric, symbol: &str, exchanges: Vec<String>, value: f64) -> Self {
+        Self {
+            metric,
+            symbol: symbol.to_string(),
+            exchanges,
+            value,
+            volume: None,
+            latency: None,
+        }
+    }
+
+    pub fn with_volume(mut self, volume: Option<Decimal>) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn with_latency(mut self, latency: u64) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    fn scope(&self) -> String {
+        match self.metric {
+            Metric::Spread => self.exchanges.join("→"),
+            _ => self.exchanges.join(", "),
+        }
+    }
+}
+
+/// Ряд, по которому правило держит условие: правило, пара, биржи.
+type Series = (String, String, Vec<String>);
+
+/// Проверяет правила по значениям метрик прохода. Условие с `for_secs` должно
+/// выполняться на каждом проходе подряд: ряд, которого нет в проходе или где
+/// условие не выполнено, начинает отсчёт заново.
+#[derive(Debug, Default)]
+pub struct RuleEngine {
+    rules: Vec<AlertRule>,
+    /// С какого момента условие выполняется
+    pending: HashMap<Series, DateTime<Utc>>,
+}
+
+impl RuleEngine {
+    pub fn new(rules: &[AlertRule]) -> Self {
+        Self {
+            rules: rules.to_vec(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Применяет правила из перезагруженной конфигурации. Отсчёт сохраняется
+    /// для правил, которые не изменились.
+    pub fn configure(&mut self, rules: &[AlertRule]) {
+        if self.rules == rules {
+            return;
+        }
+        let unchanged: Vec<&str> = rules
+            .iter()
+            .filter(|rule| self.rules.contains(rule))
+            .map(|rule| rule.name.as_str())
+            .collect();
+        self.pending.retain(|(name, _, _), _| unchanged.contains(&name.as_str()));
+        self.rules = rules.to_vec();
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Возвращает оповещения по правилам, условие которых держится не меньше `for_secs`.
+    pub fn evaluate(&mut self, observations: &[Observation], now: DateTime<Utc>) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        let mut holding = HashMap::new();
+        for rule in &self.rules {
+            for observation in observations.iter().filter(|o| rule.applies_to(o)) {
+                if !rule.op.holds(observation.value, rule.threshold) {
+                    continue;
+                }
+                let series = (rule.name.clone(), observation.symbol.clone(), observation.exchanges.clone());
+                let since = self.pending.get(&series).copied().unwrap_or(now);
+                holding.insert(series, since);
+                if (now - since).num_seconds() >= rule.for_secs as i64 {
+                    alerts.push(alert(rule, observation, since, now));
+                }
+            }
+        }
+        self.pending = holding;
+        alerts
+    }
+}
+
+fn alert(rule: &AlertRule, observation: &Observation, since: DateTime<Utc>, now: DateTime<Utc>) -> Alert {
+    let metric = rule.metric;
+    let subject = if observation.symbol.is_empty() {
+        metric.as_str().to_string()
+    } else {
+        format!("{} {}", observation.symbol, metric.as_str())
+    };
+    let mut message = format!(
+        "{}: {} {} on {} {} {}",
+        rule.name,
+        subject,
+        metric.format(observation.value),
+        observation.scope(),
+        rule.op,
+        metric.format(rule.threshold),
+    );
+    if rule.for_secs > 0 {
+        message.push_str(&format!(" for {}s", (now - since).num_seconds()));
+    }
+
+    let mut alert = Alert::new(metric.alert_type(), rule.severity, &observation.symbol, message).with_data(AlertData {
+        price_difference: match metric {
+            Metric::Spread | Metric::Volatility => Decimal::from_f64_retain(observation.value).map(|v| v.round_dp(4)),
+            Metric::SuccessRate | Metric::Latency => None,
+        },
+        volume: observation.volume,
+        exchanges: Some(observation.exchanges.clone()),
+        latency: observation.latency,
+    });
+    alert.rule = Some(rule.name.clone());
+    alert.route = rule.route.clone();
+    alert
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn spread_rule(name: &str, threshold: f64, for_secs: u64) -> AlertRule {
+        AlertRule {
+            for_secs,
+            ..AlertRule::new(name, Metric::Spread, Comparison::Above, threshold, 8)
+        }
+    }
+
+    fn spread(symbol: &str, value: f64) -> Observation {
+        Observation::new(Metric::Spread, symbol, vec!["Binance".to_string(), "Bybit".to_string()], value)
+    }
+
+    #[test]
+    fn fires_only_after_condition_holds_for_duration() {
+        let mut engine = RuleEngine::new(&[spread_rule("wide", 0.4, 10)]);
+        let start = Utc::now();
+
+        assert!(engine.evaluate(&[spread("BTCUSDT", 0.5)], start).is_empty());
+        assert!(engine.evaluate(&[spread("BTCUSDT", 0.6)], start + Duration::seconds(5)).is_empty());
+        let alerts = engine.evaluate(&[spread("BTCUSDT", 0.7)], start + Duration::seconds(10));
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].rule.as_deref(), Some("wide"));
+        assert_eq!(alerts[0].alert_type, AlertType::PriceArbitrage);
+        assert!(alerts[0].message.ends_with("for 10s"), "{}", alerts[0].message);
+    }
+
+    #[test]
+    fn broken_condition_restarts_the_hold() {
+        let mut engine = RuleEngine::new(&[spread_rule("wide", 0.4, 10)]);
+        let start = Utc::now();
+
+        engine.evaluate(&[spread("BTCUSDT", 0.5)], start);
+        engine.evaluate(&[spread("BTCUSDT", 0.3)], start + Duration::seconds(5));
+        assert!(engine.evaluate(&[spread("BTCUSDT", 0.5)], start + Duration::seconds(10)).is_empty());
+        assert_eq!(engine.evaluate(&[spread("BTCUSDT", 0.5)], start + Duration::seconds(20)).len(), 1);
+    }
+
+    #[test]
+    fn series_missing_from_a_scan_restarts_the_hold() {
+        let mut engine = RuleEngine::new(&[spread_rule("wide", 0.4, 10)]);
+        let start = Utc::now();
+
+        engine.evaluate(&[spread("BTCUSDT", 0.5), spread("ETHUSDT", 0.5)], start);
+        // BTCUSDT не пришёл: например, биржа не ответила
+        engine.evaluate(&[spread("ETHUSDT", 0.5)], start + Duration::seconds(5));
+        let alerts = engine.evaluate(&[spread("BTCUSDT", 0.5), spread("ETHUSDT", 0.5)], start + Duration::seconds(10));
+
+        let symbols: Vec<&str> = alerts.iter().map(|a| a.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["ETHUSDT"]);
+    }
+
+    #[test]
+    fn configure_keeps_hold_of_unchanged_rules() {
+        let mut engine = RuleEngine::new(&[spread_rule("wide", 0.4, 10), spread_rule("wider", 0.45, 10)]);
+        let start = Utc::now();
+        engine.evaluate(&[spread("BTCUSDT", 0.5)], start);
+
+        // «wide» не изменилось, у «wider» новый порог, «widest» добавлено
+        engine.configure(&[spread_rule("wide", 0.4, 10), spread_rule("wider", 0.3, 10), spread_rule("widest", 0.2, 0)]);
+        let alerts = engine.evaluate(&[spread("BTCUSDT", 0.5)], start + Duration::seconds(10));
+
+        let mut fired: Vec<&str> = alerts.iter().filter_map(|a| a.rule.as_deref()).collect();
+        fired.sort();
+        assert_eq!(fired, vec!["wide", "widest"]);
+    }
+
+    #[test]
+    fn scope_filters_symbols_and_exchanges() {
+        let rule = AlertRule {
+            symbols: vec!["btcusdt".to_string()],
+            exchanges: vec!["binance".to_string(), "bybit".to_string()],
+            ..spread_rule("scoped", 0.4, 0)
+        };
+        let mut engine = RuleEngine::new(&[rule]);
+        let kucoin = Observation::new(Metric::Spread, "BTCUSDT", vec!["Binance".to_string(), "KuCoin".to_string()], 0.9);
+
+        let alerts = engine.evaluate(&[spread("BTCUSDT", 0.5), spread("ETHUSDT", 0.9), kucoin], Utc::now());
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].exchanges(), vec!["Binance".to_string(), "Bybit".to_string()]);
+    }
+}
@@ -0,0 +1,286 @@
+use super::{Alert, AlertError, AlertType};
+use chrono::{DateTime, Duration, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Заглушка: оповещения, подходящие под все заданные условия, не отправляются
+/// до `ends_at`. Например, биржа на плановом обслуживании.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Silence {
+    pub id: u64,
+    pub alert_type: Option<AlertType>,
+    pub symbol: Option<String>,
+    /// Совпадает, если биржа есть среди бирж оповещения
+    pub exchange: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    #[serde(default)]
+    pub comment: String,
+}
+
+impl Silence {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+
+    pub fn matches(&self, alert: &Alert) -> bool {
+        self.alert_type.is_none_or(|alert_type| alert.alert_type == alert_type)
+            && self.symbol.as_ref().is_none_or(|symbol| alert.symbol.eq_ignore_ascii_case(symbol))
+            && self
+                .exchange
+                .as_ref()
+                .is_none_or(|exchange| alert.exchanges().iter().any(|e| e.eq_ignore_ascii_case(exchange)))
+    }
+}
+
+/// Запрос на заглушку от API или терминала.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewSilence {
+    #[serde(default, rename = "type")]
+    pub alert_type: Option<AlertType>,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub exchange: Option<String>,
+    pub duration_secs: u64,
+    #[serde(default)]
+    pub comment: String,
+}
+
+/// Заглушки с сохранением в JSON: переживают перезапуск. Истёкшие при загрузке отбрасываются.
+#[derive(Debug, Default)]
+pub struct SilenceStore {
+    path: Option<PathBuf>,
+    silences: Vec<Silence>,
+    next_id: u64,
+}
+
+impl SilenceStore {
+    /// Без файла заглушки живут только в памяти. Нечитаемый файл не мешает запуску;
+    /// повреждённый переименовывается в `*.corrupt`, чтобы сохранение его не затёрло.
+    pub fn load(path: Option<&Path>) -> Self {
+        let mut store = Self {
+            path: path.map(Path::to_path_buf),
+            silences: Vec::new(),
+            next_id: 1,
+        };
+        let Some(path) = path.filter(|path| path.exists()) else {
+            return store;
+        };
+        match std::fs::read(path).map_err(AlertError::from).and_then(|bytes| {
+            serde_json::from_slice::<Vec<Silence>>(&bytes).map_err(AlertError::from)
+        }) {
+            Ok(silences) => {
+                let now = Utc::now();
+                store.next_id = silences.iter().map(|s| s.id + 1).max().unwrap_or(1);
+                store.silences = silences.into_iter().filter(|s| s.ends_at > now).collect();
+            }
+            Err(AlertError::Json(e)) => {
+                warn!("Failed to parse silences from {}: {}", path.display(), e);
+                let mut aside = path.as_os_str().to_owned();
+                aside.push(".corrupt");
+                let aside = PathBuf::from(aside);
+                match std::fs::rename(path, &aside) {
+                    Ok(()) => warn!("Moved {} to {}", path.display(), aside.display()),
+                    Err(e) => {
+                        warn!("Failed to move {} aside, silences will not be saved: {}", path.display(), e);
+                        store.path = None;
+                    }
+                }
+            }
+            Err(e) => {
+                // Файл мог стать читаемым позже: не сохраняем поверх него
+                warn!("Failed to load silences from {}, silences will not be saved: {}", path.display(), e);
+                store.path = None;
+            }
+        }
+        store
+    }
+
+    pub fn add(&mut self, silence: NewSilence, now: DateTime<Utc>) -> Result<Silence, AlertError> {
+        if silence.alert_type.is_none() && silence.symbol.is_none() && silence.exchange.is_none() {
+            return Err(AlertError::InvalidSilence("must match on type, symbol or exchange".to_string()));
+        }
+        if silence.duration_secs == 0 {
+            return Err(AlertError::InvalidSilence("duration_secs must be greater than 0".to_string()));
+        }
+        let duration = Duration::try_seconds(silence.duration_secs as i64)
+            .ok_or_else(|| AlertError::InvalidSilence("duration_secs is too large".to_string()))?;
+
+        let silence = Silence {
+            id: self.next_id,
+            alert_type: silence.alert_type,
+            symbol: silence.symbol,
+            exchange: silence.exchange,
+            starts_at: now,
+            ends_at: now + duration,
+            comment: silence.comment,
+        };
+        self.next_id += 1;
+        self.silences.push(silence.clone());
+        self.save(now);
+        Ok(silence)
+    }
+
+    /// Снимает заглушку досрочно.
+    pub fn expire(&mut self, id: u64, now: DateTime<Utc>) -> Result<Silence, AlertError> {
+        let silence = self
+            .silences
+            .iter_mut()
+            .find(|s| s.id == id && s.ends_at > now)
+            .ok_or(AlertError::SilenceNotFound(id))?;
+        silence.ends_at = now;
+        let silence = silence.clone();
+        self.save(now);
+        Ok(silence)
+    }
+
+    /// Действующие заглушки, по времени окончания.
+    pub fn active(&self, now: DateTime<Utc>) -> Vec<Silence> {
+        let mut active: Vec<_> = self.silences.iter().filter(|s| s.is_active(now)).cloned().collect();
+        active.sort_by_key(|s| s.ends_at);
+        active
+    }
+
+    /// Заглушка, под которую попадает оповещение.
+    pub fn matching(&self, alert: &Alert, now: DateTime<Utc>) -> Option<&Silence> {
+        self.silences.iter().find(|s| s.is_active(now) && s.matches(alert))
+    }
+
+    /// Истёкшие заглушки не сохраняются. Ошибка записи только логируется: заглушка
+    /// действует до перезапуска.
+    fn save(&mut self, now: DateTime<Utc>) {
+        self.silences.retain(|s| s.ends_at > now);
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .map_err(AlertError::from)
+            .and_then(|_| Ok(serde_json::to_vec_pretty(&self.silences)?))
+            .and_then(|json| Ok(std::fs::write(path, json)?));
+        if let Err(e) = result {
+            warn!("Failed to save silences to {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::AlertData;
+
+    /// Отдельный каталог на тест в системном временном каталоге.
+    fn temp_file(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("arbitrage-silences-{}-{}", std::process::id(), test));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("silences.json")
+    }
+
+    fn new_silence(exchange: &str, duration_secs: u64) -> NewSilence {
+        NewSilence {
+            alert_type: None,
+            symbol: None,
+            exchange: Some(exchange.to_string()),
+            duration_secs,
+            comment: "maintenance".to_string(),
+        }
+    }
+
+    fn alert(alert_type: AlertType, symbol: &str, exchanges: &[&str]) -> Alert {
+        let mut alert = Alert::new(alert_type, 7, symbol, "test");
+        alert.data = Some(AlertData {
+            price_difference: None,
+            volume: None,
+            exchanges: Some(exchanges.iter().map(|e| e.to_string()).collect()),
+            latency: None,
+        });
+        alert
+    }
+
+    #[test]
+    fn silences_survive_reload() {
+        let path = temp_file("reload");
+        let now = Utc::now();
+        let mut store = SilenceStore::load(Some(&path));
+        let added = store.add(new_silence("bybit", 3600), now).unwrap();
+        store.add(new_silence("kucoin", 600), now).unwrap();
+
+        let reloaded = SilenceStore::load(Some(&path));
+        let active = reloaded.active(now);
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[1].id, added.id);
+        assert_eq!(active[1].comment, "maintenance");
+
+        // Идентификаторы продолжаются после перезагрузки
+        let mut reloaded = reloaded;
+        assert_eq!(reloaded.add(new_silence("binance", 60), now).unwrap().id, 3);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn matches_on_every_given_field() {
+        let now = Utc::now();
+        let mut store = SilenceStore::load(None);
+        store
+            .add(
+                NewSilence {
+                    alert_type: Some(AlertType::HighVolatility),
+                    symbol: Some("btcusdt".to_string()),
+                    ..new_silence("Bybit", 60)
+                },
+                now,
+            )
+            .unwrap();
+
+        assert!(store.matching(&alert(AlertType::HighVolatility, "BTCUSDT", &["bybit"]), now).is_some());
+        assert!(store.matching(&alert(AlertType::HighVolatility, "BTCUSDT", &["Binance", "Bybit"]), now).is_some());
+        assert!(store.matching(&alert(AlertType::HighVolatility, "ETHUSDT", &["Bybit"]), now).is_none());
+        assert!(store.matching(&alert(AlertType::PriceArbitrage, "BTCUSDT", &["Bybit"]), now).is_none());
+        assert!(store.matching(&alert(AlertType::HighVolatility, "BTCUSDT", &["KuCoin"]), now).is_none());
+        assert!(store
+            .matching(&alert(AlertType::HighVolatility, "BTCUSDT", &["Bybit"]), now + Duration::seconds(60))
+            .is_none());
+    }
+
+    #[test]
+    fn expired_silences_are_pruned_on_save() {
+        let path = temp_file("prune");
+        let now = Utc::now();
+        let mut store = SilenceStore::load(Some(&path));
+        store.add(new_silence("bybit", 10), now).unwrap();
+        let kept = store.add(new_silence("kucoin", 3600), now).unwrap();
+        let expired = store.add(new_silence("binance", 3600), now).unwrap();
+
+        // Сохранение через минуту отбрасывает истёкшую по сроку и снятую досрочно
+        store.expire(expired.id, now + Duration::seconds(60)).unwrap();
+
+        let saved: Vec<Silence> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.iter().map(|s| s.id).collect::<Vec<_>>(), vec![kept.id]);
+        assert!(matches!(
+            store.expire(expired.id, now + Duration::seconds(61)),
+            Err(AlertError::SilenceNotFound(_))
+        ));
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn corrupt_file_is_moved_aside() {
+        let path = temp_file("corrupt");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "[{ not json").unwrap();
+
+        let mut store = SilenceStore::load(Some(&path));
+        assert!(store.active(Utc::now()).is_empty());
+        store.add(new_silence("bybit", 60), Utc::now()).unwrap();
+
+        let aside = path.with_file_name("silences.json.corrupt");
+        assert_eq!(std::fs::read_to_string(&aside).unwrap(), "[{ not json");
+        let saved: Vec<Silence> = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.len(), 1);
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
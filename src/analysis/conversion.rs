@@ -0,0 +1,175 @@
+use crate::fees::FeeModel;
+use crate::transfers::TransferModel;
+use crate::types::{ArbitrageOpportunity, MarketPrice};
+use chrono::Utc;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+
+/// Курсы котируемых валют к опорной по биржам: `rate("binance", "USDC")` —
+/// цена 1 USDC в USDT на Binance по паре `USDCUSDT`.
+#[derive(Debug, Clone)]
+pub struct QuoteConverter {
+    reference: String,
+    rates: HashMap<String, HashMap<String, Decimal>>,
+    /// Проскальзывание на ноге конвертации, доля
+    slippage: Decimal,
+}
+
+impl QuoteConverter {
+    /// `slippage` — в процентах, как остальные пороги арбитража.
+    pub fn new(reference: &str, slippage: Decimal) -> Self {
+        Self {
+            reference: reference.to_string(),
+            rates: HashMap::new(),
+            slippage: slippage / dec!(100),
+        }
+    }
+
+    /// Пары, по которым считаются курсы: `USDCUSDT`, `BTCUSDT`, ...
+    pub fn conversion_symbols(reference: &str, quotes: &[String]) -> Vec<String> {
+        quotes
+            .iter()
+            .filter(|quote| quote.as_str() != reference)
+            .map(|quote| format!("{}{}", quote, reference))
+            .collect()
+    }
+
+    /// Собирает курсы из цен пар конвертации (`symbol → exchange → price`).
+    pub fn from_prices(
+        reference: &str,
+        slippage: Decimal,
+        quotes: &[String],
+        prices: &HashMap<String, HashMap<String, MarketPrice>>,
+    ) -> Self {
+        let mut converter = Self::new(reference, slippage);
+        for quote in quotes.iter().filter(|quote| quote.as_str() != reference) {
+            let Some(quotes_by_exchange) = prices.get(&format!("{}{}", quote, reference)) else {
+                continue;
+            };
+            for (exchange, price) in quotes_by_exchange {
+                converter.set_rate(exchange, quote, price.price);
+            }
+        }
+        converter
+    }
+
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    pub fn set_rate(&mut self, exchange: &str, quote: &str, rate: Decimal) {
+        if rate > dec!(0) {
+            self.rates
+                .entry(exchange.to_lowercase())
+                .or_default()
+                .insert(quote.to_string(), rate);
+        }
+    }
+
+    /// Курс котируемой валюты к опорной на бирже; для самой опорной — 1.
+    pub fn rate(&self, exchange: &str, quote: &str) -> Option<Decimal> {
+        if quote == self.reference {
+            return Some(dec!(1));
+        }
+        self.rates.get(&exchange.to_lowercase())?.get(quote).copied()
+    }
+
+    /// Стоимость конвертации в долях: тейкер-комиссия пары конвертации плюс проскальзывание.
+    pub fn conversion_cost(&self, exchange: &str, quote: &str, fees: &FeeModel) -> Decimal {
+        if quote == self.reference {
+            return dec!(0);
+        }
+        fees.taker(exchange, &format!("{}{}", quote, self.reference)) + self.slippage
+    }
+}
+
+/// Котировка базового актива на бирже, приведённая к опорной валюте.
+/// Покупка считается по лучшей продаже стакана, продажа — по лучшей покупке.
+struct NormalizedQuote<'a> {
+    exchange: &'a str,
+    symbol: &'a str,
+    price: &'a MarketPrice,
+    /// Стоимость покупки единицы с комиссиями сделки и конвертации
+    buy_cost: Decimal,
+    /// Выручка от продажи единицы за вычетом комиссий сделки и конвертации
+    sell_proceeds: Decimal,
+}
+
+/// Ищет арбитраж базового актива между разными котировками (`BTCUSDC` на одной бирже,
+/// `BTCUSDT` на другой). `quotes` — `(символ, котируемая валюта) → цены по биржам`.
+/// Связки с одинаковым символом не рассматриваются — их находит `find_opportunity`.
+pub fn find_cross_opportunity(
+    quotes: &[(&str, &str, &HashMap<String, MarketPrice>)],
+    converter: &QuoteConverter,
+    fees: &FeeModel,
+    transfers: &TransferModel,
+    min_profit_threshold: Decimal,
+) -> Option<ArbitrageOpportunity> {
+    let mut normalized = Vec::new();
+    for (symbol, quote, prices) in quotes {
+        for (exchange, price) in prices.iter().filter(|(_, p)| p.price > dec!(0)) {
+            let Some(rate) = converter.rate(exchange, quote) else {
+                continue;
+            };
+            let conversion = converter.conversion_cost(exchange, quote, fees);
+            let trade_fee = fees.taker(exchange, symbol);
+            normalized.push(NormalizedQuote {
+                exchange,
+                symbol,
+                price,
+                buy_cost: price.ask() * rate * (dec!(1) + trade_fee + conversion),
+                sell_proceeds: price.bid() * rate * (dec!(1) - trade_fee - conversion),
+            });
+        }
+    }
+
+    let mut best: Option<(&NormalizedQuote, &NormalizedQuote, Decimal, bool)> = None;
+    for buy in &normalized {
+        for sell in &normalized {
+            if buy.symbol == sell.symbol || buy.exchange == sell.exchange {
+                continue;
+            }
+
+            let transfer = transfers.assess(buy.symbol, buy.exchange, sell.exchange, buy.price.ask());
+            let spread = (sell.sell_proceeds - buy.buy_cost) / buy.buy_cost * dec!(100) - transfer.cost_percent;
+            let blocked = transfer.warning.is_some();
+            if spread <= min_profit_threshold {
+                continue;
+            }
+
+            // Маршруты, где инвентарь не переместить, уступают любым доступным
+            let better = match &best {
+                None => true,
+                Some((_, _, best_spread, best_blocked)) => (!blocked, spread) > (!*best_blocked, *best_spread),
+            };
+            if better {
+                best = Some((buy, sell, spread, blocked));
+            }
+        }
+    }
+
+    let (buy, sell, spread, _) = best?;
+    let transfer = transfers.assess(buy.symbol, buy.exchange, sell.exchange, buy.price.ask());
+    let volume = match (buy.price.volume_24h, sell.price.volume_24h) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+
+    Some(ArbitrageOpportunity {
+        id: None,
+        pair: buy.symbol.to_string(),
+        sell_pair: Some(sell.symbol.to_string()),
+        buy_price: buy.price.ask(),
+        buy_exchange: buy.exchange.to_string(),
+        sell_price: sell.price.bid(),
+        sell_exchange: sell.exchange.to_string(),
+        spread,
+        volume,
+        // Прибыль на единицу базового актива в опорной валюте
+        potential_profit: buy.buy_cost * spread / dec!(100),
+        transfer_cost: transfer.cost_percent,
+        transfer_warning: transfer.warning,
+        timestamp: Utc::now(),
+    })
+}
@@ -0,0 +1,167 @@
+use super::median;
+use crate::alerts::{Alert, AlertData, AlertType};
+use crate::types::MarketPrice;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+const DEPEG_SEVERITY: u8 = 9;
+const RESTORED_SEVERITY: u8 = 4;
+
+/// Курс стейблкоина к опорной валюте.
+#[derive(Debug, Clone, Serialize)]
+pub struct PegStatus {
+    pub token: String,
+    /// Курс по биржам
+    pub rates: BTreeMap<String, Decimal>,
+    /// Отклонение медианного курса от 1, в процентах со знаком
+    pub deviation: Decimal,
+}
+
+/// Следит за курсами стейблкоинов к опорной валюте на всех биржах и определяет,
+/// какие котируемые валюты потеряли привязку.
+///
+/// По курсам `USDC/USDT`, `DAI/USDT` нельзя напрямую сказать, какая из валют отвязалась.
+/// Если отклонились большинство стейблкоинов и в одну сторону, виновной считается опорная.
+#[derive(Debug, Default)]
+pub struct DepegMonitor {
+    statuses: BTreeMap<String, PegStatus>,
+    depegged: BTreeSet<String>,
+}
+
+impl DepegMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Пары, по которым считаются курсы: `USDCUSDT`, `DAIUSDT`, ...
+    pub fn symbols(reference: &str, stablecoins: &[String]) -> Vec<String> {
+        stablecoins
+            .iter()
+            .filter(|token| token.as_str() != reference)
+            .map(|token| format!("{}{}", token, reference))
+            .collect()
+    }
+
+    /// Обновляет курсы по ценам (`symbol → exchange → price`) и возвращает оповещения
+    /// о потере и восстановлении привязки. `threshold` — в процентах; привязка считается
+    /// восстановленной, когда отклонение опускается ниже половины порога.
+    pub fn update(
+        &mut self,
+        reference: &str,
+        stablecoins: &[String],
+        threshold: Decimal,
+        prices: &HashMap<String, HashMap<String, MarketPrice>>,
+    ) -> Vec<Alert> {
+        for token in stablecoins.iter().filter(|token| token.as_str() != reference) {
+            let rates: BTreeMap<String, Decimal> = prices
+                .get(&format!("{}{}", token, reference))
+                .into_iter()
+                .flatten()
+                .filter(|(_, p)| p.price > dec!(0))
+                .map(|(exchange, p)| (exchange.clone(), p.price))
+                .collect();
+            // Без свежих котировок сохраняется прошлое состояние
+            let Some(rate) = median(rates.values().copied().collect()) else {
+                continue;
+            };
+            self.statuses.insert(
+                token.clone(),
+                PegStatus {
+                    token: token.clone(),
+                    rates,
+                    deviation: (rate - dec!(1)) * dec!(100),
+                },
+            );
+        }
+        self.statuses.retain(|token, _| stablecoins.contains(token) && token != reference);
+
+        let outside = |status: &PegStatus, was_depegged: bool| {
+            let limit = if was_depegged { threshold / dec!(2) } else { threshold };
+            status.deviation.abs() > limit
+        };
+
+        let mut deviating: Vec<&PegStatus> = self
+            .statuses
+            .values()
+            .filter(|status| {
+                let was_depegged = self.depegged.contains(&status.token) || self.depegged.contains(reference);
+                outside(status, was_depegged)
+            })
+            .collect();
+
+        let same_direction = deviating.iter().all(|s| s.deviation > dec!(0))
+            || deviating.iter().all(|s| s.deviation < dec!(0));
+        let reference_blamed = self.statuses.len() >= 2
+            && deviating.len() * 2 > self.statuses.len()
+            && same_direction;
+
+        let mut depegged = BTreeSet::new();
+        let mut alerts = Vec::new();
+        if reference_blamed {
+            depegged.insert(reference.to_string());
+            if !self.depegged.contains(reference) {
+                deviating.sort_by_key(|s| s.token.clone());
+                let others: Vec<String> = deviating
+                    .iter()
+                    .map(|s| format!("{} {:+.2}%", s.token, s.deviation))
+                    .collect();
+                let message = format!("{} depegged: stablecoins trade at {} against it", reference, others.join(", "));
+                alerts.push(Alert::new(AlertType::Depeg, DEPEG_SEVERITY, reference, message));
+            }
+        } else {
+            for status in &deviating {
+                depegged.insert(status.token.clone());
+                if !self.depegged.contains(&status.token) {
+                    let message = format!("{} depegged: {}", status.token, describe(status, reference));
+                    alerts.push(peg_alert(status, DEPEG_SEVERITY, message));
+                }
+            }
+        }
+
+        for token in self.depegged.difference(&depegged) {
+            match self.statuses.get(token) {
+                Some(status) => {
+                    let message = format!("{} peg restored: {}", token, describe(status, reference));
+                    alerts.push(peg_alert(status, RESTORED_SEVERITY, message));
+                }
+                None => alerts.push(Alert::new(AlertType::Depeg, RESTORED_SEVERITY, token, format!("{} peg restored", token))),
+            }
+        }
+
+        self.depegged = depegged;
+        alerts
+    }
+
+    pub fn statuses(&self) -> impl Iterator<Item = &PegStatus> {
+        self.statuses.values()
+    }
+
+    /// Валюты, потерявшие привязку.
+    pub fn depegged(&self) -> &BTreeSet<String> {
+        &self.depegged
+    }
+
+    pub fn is_depegged(&self, token: &str) -> bool {
+        self.depegged.contains(token)
+    }
+}
+
+fn describe(status: &PegStatus, reference: &str) -> String {
+    let venues: Vec<String> = status
+        .rates
+        .iter()
+        .map(|(exchange, rate)| format!("{} {:.4}", exchange, rate))
+        .collect();
+    format!("{:+.2}% against {} ({})", status.deviation, reference, venues.join(", "))
+}
+
+/// Оповещение по одной валюте: отклонение и биржи, по которым оно посчитано.
+fn peg_alert(status: &PegStatus, severity: u8, message: String) -> Alert {
+    Alert::new(AlertType::Depeg, severity, &status.token, message).with_data(AlertData {
+        price_difference: Some(status.deviation.round_dp(4)),
+        exchanges: Some(status.rates.keys().cloned().collect()),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,308 @@
+use crate::metrics::RingBuffer;
+use crate::types::ArbitrageOpportunity;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Закрытых возможностей в истории трекера.
+const CLOSED_HISTORY: usize = 10_000;
+
+/// Связка возможности: `(pair, buy_exchange, sell_exchange)`.
+type Route = (String, String, String);
+
+/// Жизненный цикл одной возможности: от первого прохода, где она найдена,
+/// до первого прохода, где её уже нет.
+#[derive(Debug, Clone, Serialize)]
+pub struct OpportunityLifecycle {
+    /// Постоянен, пока возможность не закрылась; новое появление связки получает новый
+    pub id: u64,
+    /// Подпись связки: `BTCUSDT` или `BTCUSDC→BTCUSDT`
+    pub pair: String,
+    pub buy_exchange: String,
+    pub sell_exchange: String,
+    pub opened_at: DateTime<Utc>,
+    pub peak_at: DateTime<Utc>,
+    pub peak_spread: Decimal,
+    /// Последний проход, где возможность была
+    pub last_seen: DateTime<Utc>,
+    pub last_spread: Decimal,
+    /// Проходов, где возможность была
+    pub observations: u32,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+impl OpportunityLifecycle {
+    /// Наблюдаемая длительность: от первого до последнего прохода с возможностью.
+    /// Ноль — возможность исчезла раньше следующего прохода.
+    pub fn duration_secs(&self) -> f64 {
+        (self.last_seen - self.opened_at).num_milliseconds() as f64 / 1000.0
+    }
+
+    /// Сколько спред потерял от пика к последнему наблюдению, процентные пункты.
+    pub fn decay(&self) -> Decimal {
+        self.peak_spread - self.last_spread
+    }
+
+    pub fn time_to_peak_secs(&self) -> f64 {
+        (self.peak_at - self.opened_at).num_milliseconds() as f64 / 1000.0
+    }
+}
+
+/// Распределение длительности и затухания закрытых возможностей.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PersistenceStats {
+    pub count: usize,
+    pub duration_p50_secs: f64,
+    pub duration_p90_secs: f64,
+    pub duration_max_secs: f64,
+    pub time_to_peak_p50_secs: f64,
+    pub mean_peak_spread: Decimal,
+    /// Средняя потеря спреда от пика до закрытия, процентные пункты
+    pub mean_decay: Decimal,
+}
+
+impl PersistenceStats {
+    fn from_lifecycles<'a>(lifecycles: impl Iterator<Item = &'a OpportunityLifecycle>) -> Self {
+        let lifecycles: Vec<_> = lifecycles.collect();
+        if lifecycles.is_empty() {
+            return Self::default();
+        }
+
+        let mut durations: Vec<f64> = lifecycles.iter().map(|l| l.duration_secs()).collect();
+        durations.sort_by(f64::total_cmp);
+        let mut to_peak: Vec<f64> = lifecycles.iter().map(|l| l.time_to_peak_secs()).collect();
+        to_peak.sort_by(f64::total_cmp);
+        let count = Decimal::from(lifecycles.len());
+        Self {
+            count: lifecycles.len(),
+            duration_p50_secs: quantile(&durations, 0.5),
+            duration_p90_secs: quantile(&durations, 0.9),
+            duration_max_secs: durations.last().copied().unwrap_or_default(),
+            time_to_peak_p50_secs: quantile(&to_peak, 0.5),
+            mean_peak_spread: (lifecycles.iter().map(|l| l.peak_spread).sum::<Decimal>() / count).round_dp(4),
+            mean_decay: (lifecycles.iter().map(|l| l.decay()).sum::<Decimal>() / count).round_dp(4),
+        }
+    }
+}
+
+/// Сводка по закрытым возможностям: по парам и по связкам бирж `buy→sell`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LifecycleReport {
+    pub total: PersistenceStats,
+    pub by_pair: BTreeMap<String, PersistenceStats>,
+    pub by_route: BTreeMap<String, PersistenceStats>,
+}
+
+/// Сопоставляет возможности между проходами: одна и та же связка
+/// `(пара, биржа покупки, биржа продажи)` сохраняет идентификатор, пока находится
+/// на каждом проходе. Точность длительности — период проходов.
+#[derive(Debug)]
+pub struct OpportunityTracker {
+    next_id: u64,
+    active: HashMap<Route, OpportunityLifecycle>,
+    closed: RingBuffer<OpportunityLifecycle>,
+}
+
+impl Default for OpportunityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpportunityTracker {
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            active: HashMap::new(),
+            closed: RingBuffer::new(CLOSED_HISTORY),
+        }
+    }
+
+    /// Учитывает полный проход: проставляет идентификаторы возможностям и закрывает
+    /// связки, которых в проходе нет. Возвращает закрытые.
+    pub fn update(&mut self, opportunities: &mut [ArbitrageOpportunity], now: DateTime<Utc>) -> Vec<OpportunityLifecycle> {
+        let seen: HashSet<u64> = opportunities
+            .iter_mut()
+            .map(|opportunity| self.observe(opportunity, now))
+            .collect();
+        self.close_where(|lifecycle| !seen.contains(&lifecycle.id), now)
+    }
+
+    /// Учитывает проверку одной пары (тик бэктеста): закрываются только связки этой пары.
+    pub fn update_pair(
+        &mut self,
+        pair: &str,
+        opportunity: Option<&mut ArbitrageOpportunity>,
+        now: DateTime<Utc>,
+    ) -> Vec<OpportunityLifecycle> {
+        let seen = opportunity.map(|opportunity| self.observe(opportunity, now));
+        self.close_where(|lifecycle| lifecycle.pair == pair && Some(lifecycle.id) != seen, now)
+    }
+
+    /// Закрывает все открытые возможности, например в конце бэктеста.
+    pub fn finish(&mut self, now: DateTime<Utc>) -> Vec<OpportunityLifecycle> {
+        self.close_where(|_| true, now)
+    }
+
+    /// Возможность впервые найдена на последнем проходе, а не держится с прошлых.
+    pub fn is_opened(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        self.active
+            .get(&route(opportunity))
+            .is_some_and(|lifecycle| lifecycle.observations == 1)
+    }
+
+    fn observe(&mut self, opportunity: &mut ArbitrageOpportunity, now: DateTime<Utc>) -> u64 {
+        let route = route(opportunity);
+        let lifecycle = self.active.entry(route).or_insert_with(|| {
+            let id = self.next_id;
+            self.next_id += 1;
+            OpportunityLifecycle {
+                id,
+                pair: opportunity.symbols(),
+                buy_exchange: opportunity.buy_exchange.clone(),
+                sell_exchange: opportunity.sell_exchange.clone(),
+                opened_at: now,
+                peak_at: now,
+                peak_spread: opportunity.spread,
+                last_seen: now,
+                last_spread: opportunity.spread,
+                observations: 0,
+                closed_at: None,
+            }
+        });
+        if opportunity.spread > lifecycle.peak_spread {
+            lifecycle.peak_spread = opportunity.spread;
+            lifecycle.peak_at = now;
+        }
+        lifecycle.last_seen = now;
+        lifecycle.last_spread = opportunity.spread;
+        lifecycle.observations += 1;
+        opportunity.id = Some(lifecycle.id);
+        lifecycle.id
+    }
+
+    fn close_where(&mut self, closes: impl Fn(&OpportunityLifecycle) -> bool, now: DateTime<Utc>) -> Vec<OpportunityLifecycle> {
+        let routes: Vec<Route> = self
+            .active
+            .iter()
+            .filter(|(_, lifecycle)| closes(lifecycle))
+            .map(|(route, _)| route.clone())
+            .collect();
+
+        let mut closed = Vec::new();
+        for route in routes {
+            if let Some(mut lifecycle) = self.active.remove(&route) {
+                lifecycle.closed_at = Some(now);
+                self.closed.push(lifecycle.clone());
+                closed.push(lifecycle);
+            }
+        }
+        closed.sort_by_key(|lifecycle| lifecycle.id);
+        closed
+    }
+
+    /// Открытые возможности, от старых к новым.
+    pub fn active(&self) -> Vec<OpportunityLifecycle> {
+        let mut active: Vec<_> = self.active.values().cloned().collect();
+        active.sort_by_key(|lifecycle| lifecycle.id);
+        active
+    }
+
+    /// Закрытые возможности из истории, от старых к новым.
+    pub fn closed(&self) -> impl Iterator<Item = &OpportunityLifecycle> {
+        self.closed.iter()
+    }
+
+    pub fn report(&self) -> LifecycleReport {
+        let mut by_pair: BTreeMap<String, Vec<&OpportunityLifecycle>> = BTreeMap::new();
+        let mut by_route: BTreeMap<String, Vec<&OpportunityLifecycle>> = BTreeMap::new();
+        for lifecycle in self.closed.iter() {
+            by_pair.entry(lifecycle.pair.clone()).or_default().push(lifecycle);
+            by_route
+                .entry(format!("{}→{}", lifecycle.buy_exchange, lifecycle.sell_exchange))
+                .or_default()
+                .push(lifecycle);
+        }
+
+        let summarize = |groups: BTreeMap<String, Vec<&OpportunityLifecycle>>| {
+            groups
+                .into_iter()
+                .map(|(key, lifecycles)| (key, PersistenceStats::from_lifecycles(lifecycles.into_iter())))
+                .collect()
+        };
+        LifecycleReport {
+            total: PersistenceStats::from_lifecycles(self.closed.iter()),
+            by_pair: summarize(by_pair),
+            by_route: summarize(by_route),
+        }
+    }
+}
+
+fn route(opportunity: &ArbitrageOpportunity) -> Route {
+    (
+        opportunity.symbols(),
+        opportunity.buy_exchange.clone(),
+        opportunity.sell_exchange.clone(),
+    )
+}
+
+/// Квантиль по отсортированной выборке, ближайший ранг.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal_macros::dec;
+
+    fn opportunity(spread: Decimal) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            id: None,
+            pair: "BTCUSDT".to_string(),
+            sell_pair: None,
+            buy_price: dec!(65000),
+            buy_exchange: "Binance".to_string(),
+            sell_price: dec!(65500),
+            sell_exchange: "Bybit".to_string(),
+            spread,
+            volume: None,
+            potential_profit: dec!(5),
+            transfer_cost: Decimal::ZERO,
+            transfer_warning: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn opened_only_on_first_scan_of_a_route() {
+        let mut tracker = OpportunityTracker::new();
+        let start = Utc::now();
+
+        let mut scan = vec![opportunity(dec!(0.6))];
+        tracker.update(&mut scan, start);
+        assert!(tracker.is_opened(&scan[0]));
+        let first_id = scan[0].id;
+
+        let mut scan = vec![opportunity(dec!(0.8))];
+        tracker.update(&mut scan, start + Duration::seconds(5));
+        assert!(!tracker.is_opened(&scan[0]));
+        assert_eq!(scan[0].id, first_id);
+
+        let closed = tracker.update(&mut [], start + Duration::seconds(10));
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].peak_spread, dec!(0.8));
+        assert_eq!(closed[0].duration_secs(), 5.0);
+
+        let mut scan = vec![opportunity(dec!(0.7))];
+        tracker.update(&mut scan, start + Duration::seconds(15));
+        assert!(tracker.is_opened(&scan[0]));
+        assert_ne!(scan[0].id, first_id);
+    }
+}
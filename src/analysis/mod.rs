@@ -1,14 +1,32 @@
+mod conversion;
+mod depeg;
+mod lifecycle;
+mod quality;
+
+pub use conversion::{find_cross_opportunity, QuoteConverter};
+pub use depeg::{DepegMonitor, PegStatus};
+pub use lifecycle::{LifecycleReport, OpportunityLifecycle, OpportunityTracker, PersistenceStats};
+pub use quality::{QualityGate, RejectReason, RejectedQuote};
+
 use rust_decimal::Decimal;
+use std::cmp::Ordering;
 use std::collections::HashMap;
-use crate::types::MarketPrice;
+use std::ops::{Add, Div};
+use crate::fees::FeeModel;
+use crate::transfers::TransferModel;
+use crate::types::{ArbitrageOpportunity, MarketPrice};
+use chrono::Utc;
 use rust_decimal_macros::dec;
 use log::info;
 use colored::Colorize;
 
+/// Лучшая связка бирж для пары. Обе ноги считаются по исполнимым ценам: покупка по лучшей
+/// продаже стакана, продажа по лучшей покупке. Без стакана — по цене последней сделки.
 pub fn analyze_prices(
     pair: &str,
     prices: &HashMap<String, MarketPrice>,
-    exchange_fees: &HashMap<String, Decimal>,
+    fees: &FeeModel,
+    transfers: &TransferModel,
     min_profit_threshold: Decimal,
 ) -> Option<(String, String, Decimal)> {
     let valid_prices: HashMap<String, &MarketPrice> = prices.iter()
@@ -21,7 +39,8 @@ pub fn analyze_prices(
     }
 
     let mut best_opportunity = None;
-    let default_fee = dec!(0.001);
+    // Маршруты, где инвентарь не переместить, уступают любым доступным
+    let mut best_blocked = true;
 
     // Проверяем все возможные пары бирж
     for (buy_exchange, buy_price) in valid_prices.iter() {
@@ -30,22 +49,27 @@ pub fn analyze_prices(
                 continue;
             }
 
-            let buy_fee = exchange_fees.get(buy_exchange).unwrap_or(&default_fee);
-            let sell_fee = exchange_fees.get(sell_exchange).unwrap_or(&default_fee);
+            // Обе ноги исполняются рыночными ордерами
+            let buy_fee = fees.taker(buy_exchange, pair);
+            let sell_fee = fees.taker(sell_exchange, pair);
 
-            let total_price_with_fees = buy_price.price * (dec!(1) + *buy_fee);
-            let sell_price_after_fees = sell_price.price * (dec!(1) - *sell_fee);
+            let total_price_with_fees = buy_price.ask() * (dec!(1) + buy_fee);
+            let sell_price_after_fees = sell_price.bid() * (dec!(1) - sell_fee);
 
-            let spread = ((sell_price_after_fees - total_price_with_fees) / total_price_with_fees) 
-                * dec!(100);
+            let transfer = transfers.assess(pair, buy_exchange, sell_exchange, buy_price.ask());
+            let spread = ((sell_price_after_fees - total_price_with_fees) / total_price_with_fees)
+                * dec!(100)
+                - transfer.cost_percent;
+            let blocked = transfer.warning.is_some();
 
             if spread > min_profit_threshold {
                 let should_update = match &best_opportunity {
                     None => true,
-                    Some((_, _, prev_spread)) => spread > *prev_spread,
+                    Some((_, _, prev_spread)) => (!blocked, spread) > (!best_blocked, *prev_spread),
                 };
 
                 if should_update {
+                    best_blocked = blocked;
                     best_opportunity = Some((
                         buy_exchange.clone(),
                         sell_exchange.clone(),
@@ -60,9 +84,9 @@ pub fn analyze_prices(
                         if best_opportunity.is_none() { "New" } else { "Better" }.yellow(),
                         pair.yellow(),
                         buy_exchange.green(),
-                        buy_price.price,
+                        buy_price.ask(),
                         sell_exchange.red(),
-                        sell_price.price,
+                        sell_price.bid(),
                         spread
                     );
                 }
@@ -72,3 +96,110 @@ pub fn analyze_prices(
 
     best_opportunity
 }
+
+/// Обёртка над `analyze_prices`, возвращающая готовую возможность для вывода и сериализации.
+pub fn find_opportunity(
+    pair: &str,
+    prices: &HashMap<String, MarketPrice>,
+    fees: &FeeModel,
+    transfers: &TransferModel,
+    min_profit_threshold: Decimal,
+) -> Option<ArbitrageOpportunity> {
+    let (buy_exchange, sell_exchange, spread) =
+        analyze_prices(pair, prices, fees, transfers, min_profit_threshold)?;
+    let buy = prices.get(&buy_exchange)?;
+    let sell = prices.get(&sell_exchange)?;
+    let transfer = transfers.assess(pair, &buy_exchange, &sell_exchange, buy.ask());
+
+    let volume = match (buy.volume_24h, sell.volume_24h) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+
+    Some(ArbitrageOpportunity {
+        id: None,
+        pair: pair.to_string(),
+        sell_pair: None,
+        buy_price: buy.ask(),
+        buy_exchange,
+        sell_price: sell.bid(),
+        sell_exchange,
+        spread,
+        volume,
+        // Прибыль на единицу базового актива с учётом комиссий
+        potential_profit: buy.ask() * spread / dec!(100),
+        transfer_cost: transfer.cost_percent,
+        transfer_warning: transfer.warning,
+        timestamp: Utc::now(),
+    })
+}
+
+/// Медиана цен или волатильностей; для чётного числа значений — среднее двух центральных.
+pub(crate) fn median<T>(mut values: Vec<T>) -> Option<T>
+where
+    T: Copy + PartialOrd + Add<Output = T> + Div<Output = T> + From<u8>,
+{
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[mid - 1] + values[mid]) / T::from(2))
+    } else {
+        Some(values[mid])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::types::TopOfBook;
+
+    fn book(bid: Decimal, ask: Decimal) -> Option<TopOfBook> {
+        Some(TopOfBook {
+            bid,
+            bid_size: dec!(1),
+            ask,
+            ask_size: dec!(1),
+        })
+    }
+
+    fn find(prices: &HashMap<String, MarketPrice>) -> Option<ArbitrageOpportunity> {
+        let config = Config::default();
+        find_opportunity("BTCUSDT", prices, &config.fee_model(), &config.transfer_model(), dec!(0.1))
+    }
+
+    #[test]
+    fn last_prices_are_used_without_books() {
+        let prices = HashMap::from([
+            ("binance".to_string(), MarketPrice::new(dec!(100), None, 0)),
+            ("bybit".to_string(), MarketPrice::new(dec!(101), None, 0)),
+        ]);
+        let opportunity = find(&prices).unwrap();
+        assert_eq!((opportunity.buy_exchange.as_str(), opportunity.buy_price), ("binance", dec!(100)));
+        assert_eq!((opportunity.sell_exchange.as_str(), opportunity.sell_price), ("bybit", dec!(101)));
+    }
+
+    #[test]
+    fn books_replace_last_prices() {
+        // Последние сделки расходятся на 1%, но купить можно только по 100.5, а продать — по 100.6
+        let prices = HashMap::from([
+            ("binance".to_string(), MarketPrice::new(dec!(100), None, 0).with_book(book(dec!(99.9), dec!(100.5)))),
+            ("bybit".to_string(), MarketPrice::new(dec!(101), None, 0).with_book(book(dec!(100.6), dec!(101.2)))),
+        ]);
+        assert!(find(&prices).is_none());
+
+        // Последние сделки почти совпадают, а стаканы пересеклись
+        let prices = HashMap::from([
+            ("binance".to_string(), MarketPrice::new(dec!(100.3), None, 0).with_book(book(dec!(99.9), dec!(100)))),
+            ("bybit".to_string(), MarketPrice::new(dec!(100.4), None, 0).with_book(book(dec!(101), dec!(101.1)))),
+        ]);
+        let opportunity = find(&prices).unwrap();
+        assert_eq!((opportunity.buy_exchange.as_str(), opportunity.buy_price), ("binance", dec!(100)));
+        assert_eq!((opportunity.sell_exchange.as_str(), opportunity.sell_price), ("bybit", dec!(101)));
+        // (101 · 0.999 − 100 · 1.001) / (100 · 1.001) · 100
+        assert_eq!(opportunity.spread.round_dp(4), dec!(0.7982));
+    }
+}
@@ -0,0 +1,247 @@
+use super::median;
+use crate::types::MarketPrice;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Почему котировка не допущена к анализу.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum RejectReason {
+    /// Нулевая или отрицательная цена
+    NonPositive,
+    /// Котировка старше `monitoring.price_timeout_ms`
+    Stale { age_ms: i64 },
+    /// Отклонение от медианы по биржам больше `arbitrage.max_price_deviation`, в процентах
+    Outlier { deviation: Decimal, median: Decimal },
+    /// Лучшая покупка выше лучшей продажи на той же бирже — стакан собран из разных моментов
+    CrossedBook { bid: Decimal, ask: Decimal },
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RejectReason::NonPositive => write!(f, "non-positive price"),
+            RejectReason::Stale { age_ms } => write!(f, "stale ({}ms old)", age_ms),
+            RejectReason::Outlier { deviation, median } => {
+                write!(f, "outlier ({:+.2}% from median {})", deviation, median)
+            }
+            RejectReason::CrossedBook { bid, ask } => write!(f, "crossed book (bid {} > ask {})", bid, ask),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedQuote {
+    pub exchange: String,
+    pub symbol: String,
+    pub price: Decimal,
+    #[serde(flatten)]
+    pub reason: RejectReason,
+}
+
+/// Фильтр качества данных перед анализом: отбрасывает устаревшие котировки
+/// и цены, слишком далёкие от медианы по биржам.
+#[derive(Debug, Clone)]
+pub struct QualityGate {
+    max_age_ms: i64,
+    max_deviation: Decimal,
+}
+
+impl QualityGate {
+    /// `max_deviation` — в процентах.
+    pub fn new(max_age_ms: u64, max_deviation: Decimal) -> Self {
+        Self {
+            max_age_ms: max_age_ms as i64,
+            max_deviation,
+        }
+    }
+
+    /// Делит котировки пары на допущенные и отклонённые. `now_ms` — текущее время
+    /// в миллисекундах (в бэктесте — время тика). При двух биржах медиана —
+    /// середина между ними, и сильное расхождение отбрасывает обе котировки.
+    pub fn filter(
+        &self,
+        symbol: &str,
+        prices: HashMap<String, MarketPrice>,
+        now_ms: i64,
+    ) -> (HashMap<String, MarketPrice>, Vec<RejectedQuote>) {
+        let mut rejected = Vec::new();
+        let mut reject = |exchange: String, price: &MarketPrice, reason| {
+            rejected.push(RejectedQuote {
+                exchange,
+                symbol: symbol.to_string(),
+                price: price.price,
+                reason,
+            });
+        };
+
+        let mut fresh = HashMap::new();
+        for (exchange, price) in prices {
+            let age_ms = (now_ms - price.timestamp).max(0);
+            if price.price <= dec!(0) {
+                reject(exchange, &price, RejectReason::NonPositive);
+            } else if age_ms > self.max_age_ms {
+                reject(exchange, &price, RejectReason::Stale { age_ms });
+            } else if let Some(book) = price.book.filter(|book| book.bid > book.ask) {
+                reject(exchange, &price, RejectReason::CrossedBook { bid: book.bid, ask: book.ask });
+            } else {
+                fresh.insert(exchange, price);
+            }
+        }
+
+        if fresh.len() < 2 {
+            return (fresh, rejected);
+        }
+        let Some(median) = median(fresh.values().map(|p| p.price).collect()) else {
+            return (fresh, rejected);
+        };
+
+        let mut accepted = HashMap::new();
+        for (exchange, price) in fresh {
+            let deviation = (price.price - median) / median * dec!(100);
+            if deviation.abs() > self.max_deviation {
+                reject(exchange, &price, RejectReason::Outlier { deviation, median });
+            } else {
+                accepted.insert(exchange, price);
+            }
+        }
+        (accepted, rejected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::TopOfBook;
+
+    const NOW_MS: i64 = 1_700_000_000_000;
+
+    /// Котировки `(биржа, цена, возраст в мс)`.
+    fn prices(quotes: &[(&str, Decimal, i64)]) -> HashMap<String, MarketPrice> {
+        quotes
+            .iter()
+            .map(|&(exchange, price, age_ms)| (exchange.to_string(), MarketPrice::new(price, None, NOW_MS - age_ms)))
+            .collect()
+    }
+
+    /// Допущенные биржи и отклонённые `(биржа, причина)`, отсортированные по бирже.
+    fn filter(prices: HashMap<String, MarketPrice>) -> (Vec<String>, Vec<(String, RejectReason)>) {
+        let gate = QualityGate::new(5_000, dec!(1));
+        let (accepted, rejected) = gate.filter("BTCUSDT", prices, NOW_MS);
+        let mut accepted: Vec<_> = accepted.into_keys().collect();
+        accepted.sort();
+        let mut rejected: Vec<_> = rejected
+            .into_iter()
+            .inspect(|quote| assert_eq!(quote.symbol, "BTCUSDT"))
+            .map(|quote| (quote.exchange, quote.reason))
+            .collect();
+        rejected.sort_by(|a, b| a.0.cmp(&b.0));
+        (accepted, rejected)
+    }
+
+    #[test]
+    fn filter_rejects_with_reasons() {
+        let cases = [
+            (
+                "close quotes pass",
+                prices(&[("binance", dec!(100), 0), ("bybit", dec!(100.5), 1_000), ("kucoin", dec!(99.5), 5_000)]),
+                vec!["binance", "bybit", "kucoin"],
+                vec![],
+            ),
+            (
+                "stale quote is dropped",
+                prices(&[("binance", dec!(100), 0), ("bybit", dec!(100), 0), ("kucoin", dec!(100), 5_001)]),
+                vec!["binance", "bybit"],
+                vec![("kucoin", RejectReason::Stale { age_ms: 5_001 })],
+            ),
+            (
+                "zero price is rejected before age",
+                prices(&[("binance", dec!(100), 0), ("bybit", dec!(100), 0), ("kucoin", dec!(0), 60_000)]),
+                vec!["binance", "bybit"],
+                vec![("kucoin", RejectReason::NonPositive)],
+            ),
+            (
+                "outlier above the median",
+                prices(&[("binance", dec!(100), 0), ("bybit", dec!(100), 0), ("kucoin", dec!(110), 0)]),
+                vec!["binance", "bybit"],
+                vec![("kucoin", RejectReason::Outlier { deviation: dec!(10), median: dec!(100) })],
+            ),
+            (
+                "outlier below the median",
+                prices(&[("binance", dec!(100), 0), ("bybit", dec!(95), 0), ("kucoin", dec!(100), 0)]),
+                vec!["binance", "kucoin"],
+                vec![("bybit", RejectReason::Outlier { deviation: dec!(-5), median: dec!(100) })],
+            ),
+            (
+                "deviation at the limit passes",
+                prices(&[("binance", dec!(100), 0), ("bybit", dec!(101), 0), ("kucoin", dec!(100), 0)]),
+                vec!["binance", "bybit", "kucoin"],
+                vec![],
+            ),
+            (
+                "stale quote does not move the median",
+                prices(&[("binance", dec!(100), 0), ("bybit", dec!(100.5), 0), ("kucoin", dec!(130), 10_000)]),
+                vec!["binance", "bybit"],
+                vec![("kucoin", RejectReason::Stale { age_ms: 10_000 })],
+            ),
+            (
+                "two diverging venues reject each other",
+                prices(&[("binance", dec!(100), 0), ("bybit", dec!(104), 0)]),
+                vec![],
+                vec![
+                    ("binance", RejectReason::Outlier { deviation: dec!(-2) / dec!(102) * dec!(100), median: dec!(102) }),
+                    ("bybit", RejectReason::Outlier { deviation: dec!(2) / dec!(102) * dec!(100), median: dec!(102) }),
+                ],
+            ),
+            (
+                "single venue is not compared",
+                prices(&[("binance", dec!(100), 0), ("bybit", dec!(100), 9_000)]),
+                vec!["binance"],
+                vec![("bybit", RejectReason::Stale { age_ms: 9_000 })],
+            ),
+        ];
+
+        for (name, prices, accepted, rejected) in cases {
+            let rejected: Vec<_> = rejected
+                .into_iter()
+                .map(|(exchange, reason)| (exchange.to_string(), reason))
+                .collect();
+            assert_eq!(filter(prices), (accepted.into_iter().map(String::from).collect(), rejected), "{}", name);
+        }
+    }
+
+    #[test]
+    fn crossed_book_is_rejected() {
+        let mut prices = prices(&[("binance", dec!(100), 0), ("bybit", dec!(100), 0)]);
+        let book = TopOfBook {
+            bid: dec!(100.2),
+            bid_size: dec!(1),
+            ask: dec!(99.8),
+            ask_size: dec!(1),
+        };
+        prices.insert("kucoin".to_string(), MarketPrice::new(dec!(100), None, NOW_MS).with_book(Some(book)));
+
+        let (accepted, rejected) = filter(prices);
+        assert_eq!(accepted, vec!["binance", "bybit"]);
+        assert_eq!(
+            rejected,
+            vec![("kucoin".to_string(), RejectReason::CrossedBook { bid: dec!(100.2), ask: dec!(99.8) })]
+        );
+    }
+
+    #[test]
+    fn reasons_are_readable() {
+        let cases = [
+            (RejectReason::NonPositive, "non-positive price"),
+            (RejectReason::Stale { age_ms: 6_000 }, "stale (6000ms old)"),
+            (RejectReason::Outlier { deviation: dec!(-5), median: dec!(100) }, "outlier (-5.00% from median 100)"),
+            (RejectReason::CrossedBook { bid: dec!(101), ask: dec!(100) }, "crossed book (bid 101 > ask 100)"),
+        ];
+        for (reason, text) in cases {
+            assert_eq!(reason.to_string(), text);
+        }
+    }
+}
@@ -1,54 +1,149 @@
 use std::sync::Arc;
 use std::net::SocketAddr;
 use axum::{
-    extract::State,
-    routing::get,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
     Router,
     Json,
 };
 use tokio::sync::Mutex;
 use crate::metrics::MetricsCollector;
-use crate::alerts::AlertManager;
+use crate::alerts::{Alert, AlertError, AlertManager, AlertQuery, AlertStats, NewSilence, Silence};
+use crate::analysis::OpportunityTracker;
+use chrono::Utc;
 use log::info;
 
 #[derive(Debug)]
 pub struct ApiState {
     pub metrics: Arc<MetricsCollector>,
     pub alert_manager: Arc<Mutex<AlertManager>>,
+    pub tracker: Arc<Mutex<OpportunityTracker>>,
 }
 
+/// HTTP API: `/metrics` для Prometheus, `/api/metrics`, `/api/alerts`,
+/// `/api/alerts/stats`, `/api/silences` и `/api/opportunities` в JSON.
+/// Оповещение подтверждается `POST /api/alerts/{id}/ack`, заглушка снимается
+/// `DELETE /api/silences/{id}`.
 pub async fn start_api_server(
+    addr: SocketAddr,
     metrics: Arc<MetricsCollector>,
     alert_manager: Arc<Mutex<AlertManager>>,
-) {
+    tracker: Arc<Mutex<OpportunityTracker>>,
+) -> std::io::Result<()> {
     let state = Arc::new(ApiState {
         metrics,
         alert_manager,
+        tracker,
     });
 
     let app = Router::new()
+        .route("/metrics", get(get_prometheus_metrics))
         .route("/api/metrics", get(get_metrics))
         .route("/api/alerts", get(get_alerts))
+        .route("/api/alerts/stats", get(get_alert_stats))
+        .route("/api/alerts/{id}/ack", post(acknowledge_alert))
+        .route("/api/silences", get(get_silences).post(add_silence))
+        .route("/api/silences/{id}", delete(expire_silence))
+        .route("/api/opportunities", get(get_opportunities))
         .with_state(state);
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 8080));
-    info!("Starting API server on {}", addr);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("API server listening on {}", addr);
 
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    info!("Listening on {}", addr);
-    
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app).await
+}
+
+async fn get_prometheus_metrics(
+    State(state): State<Arc<ApiState>>,
+) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        state.metrics.render_prometheus(),
+    )
 }
 
 async fn get_metrics(
     State(state): State<Arc<ApiState>>,
 ) -> Json<serde_json::Value> {
-    Json(serde_json::to_value(state.metrics.get_statistics()).unwrap())
+    Json(serde_json::json!({
+        "statistics": state.metrics.get_statistics(),
+        "uptime_secs": state.metrics.uptime().as_secs(),
+        "exchanges": state.metrics.exchange_health().into_iter().collect::<std::collections::BTreeMap<_, _>>(),
+        "spreads": state.metrics.crossable_spreads().into_iter().collect::<std::collections::BTreeMap<_, _>>(),
+        "regimes": state.metrics.regimes().into_iter().collect::<std::collections::BTreeMap<_, _>>(),
+    }))
 }
 
+/// История оповещений, от новых к старым. Фильтры: `since`, `until` (RFC 3339),
+/// `type`, `min_severity`, `symbol`, `limit`.
 async fn get_alerts(
     State(state): State<Arc<ApiState>>,
-) -> Json<Vec<crate::alerts::Alert>> {
+    Query(query): Query<AlertQuery>,
+) -> Json<Vec<Alert>> {
+    let alert_manager = state.alert_manager.lock().await;
+    Json(alert_manager.query(&query))
+}
+
+async fn get_alert_stats(
+    State(state): State<Arc<ApiState>>,
+) -> Json<AlertStats> {
+    let alert_manager = state.alert_manager.lock().await;
+    Json(alert_manager.stats().clone())
+}
+
+impl IntoResponse for AlertError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            AlertError::NotFound(_) | AlertError::SilenceNotFound(_) => StatusCode::NOT_FOUND,
+            AlertError::InvalidSilence(_) => StatusCode::BAD_REQUEST,
+            AlertError::Io(_) | AlertError::Json(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(serde_json::json!({ "error": self.to_string() }))).into_response()
+    }
+}
+
+async fn acknowledge_alert(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<Alert>, AlertError> {
+    let mut alert_manager = state.alert_manager.lock().await;
+    alert_manager.acknowledge(id, Utc::now()).map(Json)
+}
+
+async fn get_silences(
+    State(state): State<Arc<ApiState>>,
+) -> Json<Vec<Silence>> {
     let alert_manager = state.alert_manager.lock().await;
-    Json(alert_manager.get_alerts())
+    Json(alert_manager.silences(Utc::now()))
+}
+
+/// Тело: `{"type", "symbol", "exchange", "duration_secs", "comment"}`, нужно хотя бы одно условие.
+async fn add_silence(
+    State(state): State<Arc<ApiState>>,
+    Json(silence): Json<NewSilence>,
+) -> Result<(StatusCode, Json<Silence>), AlertError> {
+    let mut alert_manager = state.alert_manager.lock().await;
+    let silence = alert_manager.add_silence(silence, Utc::now())?;
+    Ok((StatusCode::CREATED, Json(silence)))
+}
+
+async fn expire_silence(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<Silence>, AlertError> {
+    let mut alert_manager = state.alert_manager.lock().await;
+    alert_manager.expire_silence(id, Utc::now()).map(Json)
+}
+
+/// Открытые возможности и распределение длительности закрытых.
+async fn get_opportunities(
+    State(state): State<Arc<ApiState>>,
+) -> Json<serde_json::Value> {
+    let tracker = state.tracker.lock().await;
+    Json(serde_json::json!({
+        "active": tracker.active(),
+        "persistence": tracker.report(),
+    }))
 }
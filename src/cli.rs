@@ -0,0 +1,134 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use log::LevelFilter;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(name = "arbitrage-system", version, about = "Cross-exchange arbitrage monitor")]
+pub struct Cli {
+    /// Path to the configuration file
+    #[arg(short, long, global = true, default_value = "config/default.yaml")]
+    pub config: PathBuf,
+
+    /// Environment name; `<config dir>/<ENV>.yaml` is layered over the main file
+    #[arg(long, global = true, env = "ARB_ENV")]
+    pub env: Option<String>,
+
+    /// Override a configuration key, e.g. `--set arbitrage.min_profit_percentage=0.8`
+    #[arg(long = "set", global = true, value_name = "KEY=VALUE")]
+    pub overrides: Vec<String>,
+
+    /// Log level
+    #[arg(long, global = true, value_enum, default_value_t = LogLevel::Info)]
+    pub log_level: LogLevel,
+
+    /// User name shown in the interface
+    #[arg(short, long, global = true, env = "USER", default_value = "anonymous")]
+    pub user: String,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Interactive terminal monitor (default)
+    Monitor,
+
+    /// Headless mode: scan, log opportunities and optionally record prices
+    Daemon {
+        /// Append fetched prices to this file (JSON Lines)
+        #[arg(long)]
+        record: Option<PathBuf>,
+    },
+
+    /// Scan all pairs once, print opportunities and exit
+    ScanOnce {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Run the analyzer over recorded price history
+    Backtest {
+        /// Price history file (JSON Lines)
+        #[arg(short, long, default_value = "data/metrics/price_history.json")]
+        input: PathBuf,
+
+        /// Size of a single trade in quote currency
+        #[arg(long, default_value = "100")]
+        trade_amount: rust_decimal::Decimal,
+
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Replay recorded price history in (accelerated) real time
+    Replay {
+        /// Price history file (JSON Lines)
+        #[arg(short, long, default_value = "data/metrics/price_history.json")]
+        input: PathBuf,
+
+        /// Playback speed multiplier (0 replays without delays)
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
+
+    /// Validate the configuration and exit
+    CheckConfig,
+
+    /// Export recorded price history
+    Export {
+        /// Price history file (JSON Lines)
+        #[arg(short, long, default_value = "data/metrics/price_history.json")]
+        input: PathBuf,
+
+        /// Output file (stdout if omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Export format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+
+        /// Only export this pair
+        #[arg(long)]
+        symbol: Option<String>,
+    },
+
+    /// Send a test notification to the configured channels
+    NotifyTest {
+        /// Only this channel
+        #[arg(long)]
+        channel: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
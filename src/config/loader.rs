@@ -0,0 +1,238 @@
+use super::{validate, Config, ConfigError, ValidationReport};
+use log::debug;
+use serde_yaml::{Mapping, Value};
+use std::path::{Path, PathBuf};
+
+/// Префикс переменных окружения, переопределяющих ключи конфигурации.
+/// `ARB_ARBITRAGE__MIN_PROFIT_PERCENTAGE=0.8` → `arbitrage.min_profit_percentage`.
+const ENV_PREFIX: &str = "ARB_";
+const ENV_SEPARATOR: &str = "__";
+
+/// Собирает конфигурацию из слоёв, каждый следующий перекрывает предыдущий:
+/// основной файл → файл окружения (`<dir>/<env>.yaml`) → переменные `ARB_*` → `--set`.
+#[derive(Debug, Clone)]
+pub struct ConfigLoader {
+    path: PathBuf,
+    environment: Option<String>,
+    overrides: Vec<String>,
+}
+
+impl ConfigLoader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            environment: None,
+            overrides: Vec::new(),
+        }
+    }
+
+    pub fn environment(mut self, environment: Option<String>) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    pub fn overrides(mut self, overrides: Vec<String>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Путь к файлу окружения, если окружение задано.
+    pub fn environment_path(&self) -> Option<PathBuf> {
+        let environment = self.environment.as_deref()?;
+        let dir = self.path.parent().unwrap_or_else(|| Path::new("."));
+        Some(dir.join(format!("{}.yaml", environment)))
+    }
+
+    pub fn load(&self) -> Result<Config, ConfigError> {
+        deserialize(self.load_value()?).map(|(config, _)| config)
+    }
+
+    /// Загружает конфигурацию и проверяет её. Наличие ошибок в отчёте
+    /// решает вызывающий код.
+    pub fn load_validated(&self) -> Result<(Config, ValidationReport), ConfigError> {
+        let (config, unknown_keys) = deserialize(self.load_value()?)?;
+        let report = validate(&config, &unknown_keys);
+        Ok((config, report))
+    }
+
+    /// Итоговое YAML-дерево после слияния всех слоёв, до десериализации.
+    pub fn load_value(&self) -> Result<Value, ConfigError> {
+        let mut value = read_yaml(&self.path)?;
+
+        if let Some(env_path) = self.environment_path() {
+            if env_path.exists() {
+                merge(&mut value, read_yaml(&env_path)?);
+            } else {
+                debug!("No environment config at {}", env_path.display());
+            }
+        }
+
+        for (key, raw) in std::env::vars() {
+            let Some(name) = key.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            if !name.contains(ENV_SEPARATOR) {
+                continue;
+            }
+            let path: Vec<String> = name
+                .split(ENV_SEPARATOR)
+                .map(|part| part.to_lowercase())
+                .collect();
+            set_path(&mut value, &path, parse_scalar(&raw));
+        }
+
+        for item in &self.overrides {
+            let (key, raw) = item
+                .split_once('=')
+                .ok_or_else(|| ConfigError::Override(item.clone()))?;
+            let path: Vec<String> = key.trim().split('.').map(str::to_string).collect();
+            if path.iter().any(String::is_empty) {
+                return Err(ConfigError::Override(item.clone()));
+            }
+            set_path(&mut value, &path, parse_scalar(raw.trim()));
+        }
+
+        Ok(value)
+    }
+}
+
+/// Десериализует дерево, возвращая также пути ключей, которые не распознаны.
+pub(crate) fn deserialize(value: Value) -> Result<(Config, Vec<String>), ConfigError> {
+    let mut unknown_keys = Vec::new();
+    let mut track = |path: serde_ignored::Path| unknown_keys.push(path.to_string());
+    let deserializer = serde_ignored::Deserializer::new(value, &mut track);
+    let config = serde_path_to_error::deserialize(deserializer).map_err(|e| ConfigError::Invalid {
+        key: e.path().to_string(),
+        message: e.into_inner().to_string(),
+    })?;
+    Ok((config, unknown_keys))
+}
+
+fn read_yaml(path: &Path) -> Result<Value, ConfigError> {
+    let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut value: Value = serde_yaml::from_str(&text).map_err(|source| ConfigError::Yaml {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if value.is_null() {
+        value = Value::Mapping(Mapping::new());
+    }
+    interpolate_value(&mut value, &path.display().to_string(), "")?;
+    Ok(value)
+}
+
+/// Рекурсивно вливает `overlay` в `base`: словари сливаются по ключам, остальное заменяется.
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn set_path(root: &mut Value, path: &[String], value: Value) {
+    let mut current = root;
+    for key in path {
+        if !current.is_mapping() {
+            *current = Value::Mapping(Mapping::new());
+        }
+        let Value::Mapping(map) = current else {
+            unreachable!()
+        };
+        current = map
+            .entry(Value::String(key.clone()))
+            .or_insert(Value::Null);
+    }
+    *current = value;
+}
+
+/// Значения из окружения и командной строки разбираются как YAML-скаляры,
+/// чтобы `0.8`, `true` и `[USDT, USDC]` получили правильный тип.
+fn parse_scalar(raw: &str) -> Value {
+    serde_yaml::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+fn interpolate_value(value: &mut Value, origin: &str, key: &str) -> Result<(), ConfigError> {
+    match value {
+        Value::String(s) if s.contains('$') => {
+            *s = interpolate(s).map_err(|message| ConfigError::Interpolation {
+                origin: format!("{}: `{}`", origin, key),
+                message,
+            })?;
+        }
+        Value::Mapping(map) => {
+            for (k, v) in map.iter_mut() {
+                let name = match k {
+                    Value::String(name) => name.clone(),
+                    other => serde_yaml::to_string(other).unwrap_or_default().trim().to_string(),
+                };
+                let child = if key.is_empty() { name } else { format!("{}.{}", key, name) };
+                interpolate_value(v, origin, &child)?;
+            }
+        }
+        Value::Sequence(seq) => {
+            for (i, v) in seq.iter_mut().enumerate() {
+                interpolate_value(v, origin, &format!("{}[{}]", key, i))?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Подставляет `${VAR}` и `${VAR:-default}`; `$${` даёт буквальное `${`.
+/// Незаданная переменная без значения по умолчанию заменяется пустой строкой.
+pub fn interpolate(input: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if let Some(after) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = after;
+        } else if let Some(body) = tail.strip_prefix("${") {
+            let end = body
+                .find('}')
+                .ok_or_else(|| format!("unterminated `${{` in \"{}\"", input))?;
+            let expr = &body[..end];
+            let (name, default) = match expr.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (expr, None),
+            };
+            if name.is_empty() {
+                return Err(format!("empty variable name in \"{}\"", input));
+            }
+            match (std::env::var(name), default) {
+                (Ok(value), None) => out.push_str(&value),
+                (Ok(value), Some(_)) if !value.is_empty() => out.push_str(&value),
+                (_, Some(default)) => out.push_str(default),
+                (Err(_), None) => debug!("Environment variable {} is not set", name),
+            }
+            rest = &body[end + 1..];
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
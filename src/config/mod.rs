@@ -0,0 +1,559 @@
+mod loader;
+mod redact;
+mod reload;
+mod validation;
+
+pub use loader::{interpolate, ConfigLoader};
+pub use reload::{diff, fixed, ConfigHandle, ConfigWatcher};
+pub use validation::{validate, Issue, Severity, ValidationReport};
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use crate::alerts::{rules, AlertRule, AlertType};
+use crate::analysis::QualityGate;
+use crate::exchanges::HttpClient;
+use crate::fees::{FeeModel, FeeSchedule};
+use crate::transfers::TransferModel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("{path}: invalid YAML: {source}")]
+    Yaml {
+        path: PathBuf,
+        source: serde_yaml::Error,
+    },
+
+    #[error("{origin}: {message}")]
+    Interpolation { origin: String, message: String },
+
+    #[error("invalid override `{0}`, expected KEY=VALUE")]
+    Override(String),
+
+    #[error("invalid value for `{key}`: {message}")]
+    Invalid { key: String, message: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub exchanges: BTreeMap<String, ExchangeConfig>,
+    pub monitoring: MonitoringConfig,
+    pub http: HttpConfig,
+    pub arbitrage: ArbitrageConfig,
+    pub transfers: TransfersConfig,
+    pub depeg: DepegConfig,
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub volatility: VolatilityConfig,
+    pub alerts: AlertsConfig,
+    pub telegram: TelegramConfig,
+    pub notifications: NotificationsConfig,
+    pub api: ApiConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let exchanges = ["binance", "bybit", "kucoin"]
+            .into_iter()
+            .map(|name| (name.to_string(), ExchangeConfig::default()))
+            .collect();
+
+        Self {
+            exchanges,
+            monitoring: MonitoringConfig::default(),
+            http: HttpConfig::default(),
+            arbitrage: ArbitrageConfig::default(),
+            transfers: TransfersConfig::default(),
+            depeg: DepegConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            volatility: VolatilityConfig::default(),
+            alerts: AlertsConfig::default(),
+            telegram: TelegramConfig::default(),
+            notifications: NotificationsConfig::default(),
+            api: ApiConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExchangeConfig {
+    pub enabled: bool,
+    #[serde(deserialize_with = "string_like")]
+    pub api_key: String,
+    #[serde(deserialize_with = "string_like")]
+    pub api_secret: String,
+    #[serde(alias = "api_passphrase", deserialize_with = "string_like")]
+    pub passphrase: String,
+    pub fees: FeeSchedule,
+    pub rate_limit: RateLimitConfig,
+    /// Адрес API вместо стандартного, например локальный мок-сервер
+    pub base_url: Option<String>,
+    /// Прокси для запросов к бирже: `http://`, `https://` или `socks5://`
+    pub proxy: Option<String>,
+}
+
+impl Default for ExchangeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            api_key: String::new(),
+            api_secret: String::new(),
+            passphrase: String::new(),
+            fees: FeeSchedule::default(),
+            rate_limit: RateLimitConfig::default(),
+            base_url: None,
+            proxy: None,
+        }
+    }
+}
+
+/// Переопределение встроенных лимитов адаптера, в единицах веса запроса.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub burst: Option<f64>,
+    pub weight_per_second: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonitoringConfig {
+    pub update_interval_ms: u64,
+    pub price_timeout_ms: u64,
+    /// Как часто оценивать смещение часов бирж; 0 — не синхронизировать
+    pub clock_sync_interval_secs: u64,
+    pub supported_quote_tokens: Vec<String>,
+    pub base_tokens: Vec<String>,
+}
+
+impl Default for MonitoringConfig {
+    fn default() -> Self {
+        Self {
+            update_interval_ms: 1000,
+            price_timeout_ms: 5000,
+            clock_sync_interval_secs: 300,
+            supported_quote_tokens: ["USDT", "USDC", "BUSD", "BTC"].map(String::from).to_vec(),
+            base_tokens: [
+                "BTC", "ETH", "SOL", "BNB", "XRP", "ADA", "AVAX", "DOGE", "TRX", "TON",
+                "DOT", "MATIC", "SHIB", "UNI", "LINK", "BCH", "LTC", "ATOM", "XLM", "ICP",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+/// Настройки общего HTTP-клиента. Применяются при запуске.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Таймаут запроса; по умолчанию `monitoring.price_timeout_ms`
+    pub timeout_ms: Option<u64>,
+    pub connect_timeout_ms: u64,
+    /// Повторы GET-запросов при сетевых ошибках и 5xx
+    pub max_retries: u32,
+    pub retry_backoff_ms: u64,
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout_secs: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: None,
+            connect_timeout_ms: 3000,
+            max_retries: 2,
+            retry_backoff_ms: 200,
+            pool_max_idle_per_host: 8,
+            pool_idle_timeout_secs: 90,
+        }
+    }
+}
+
+/// Режим работы: только мониторинг, бумажная торговля или реальные сделки.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradingMode {
+    #[default]
+    Monitor,
+    Paper,
+    Live,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArbitrageConfig {
+    pub mode: TradingMode,
+    pub min_profit_percentage: Decimal,  // в процентах, 0.5 = 0.5%
+    pub min_profit_usd: Decimal,
+    pub min_volume_24h: Decimal,
+    pub min_exchanges_required: usize,
+    pub max_price_deviation: Decimal,    // в процентах
+    pub max_trade_amount: Decimal,
+    pub daily_limit: Decimal,
+    pub max_loss_per_trade: Decimal,
+    pub max_daily_loss: Decimal,
+    /// Сравнивать пары с разными котировками (BTCUSDC против BTCUSDT)
+    pub cross_quote: bool,
+    /// Опорная валюта, к которой приводятся цены при кросс-котировках
+    pub reference_quote: String,
+    pub conversion_slippage: Decimal,    // в процентах
+}
+
+impl Default for ArbitrageConfig {
+    fn default() -> Self {
+        Self {
+            mode: TradingMode::Monitor,
+            min_profit_percentage: dec!(0.5),
+            min_profit_usd: dec!(0.5),
+            min_volume_24h: dec!(10000),      // $10,000
+            min_exchanges_required: 2,
+            max_price_deviation: dec!(30),    // 30%
+            max_trade_amount: dec!(100),
+            daily_limit: dec!(1000),
+            max_loss_per_trade: dec!(5),
+            max_daily_loss: dec!(50),
+            cross_quote: true,
+            reference_quote: "USDT".to_string(),
+            conversion_slippage: dec!(0.01),
+        }
+    }
+}
+
+/// Учёт стоимости перемещения инвентаря между биржами.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransfersConfig {
+    pub enabled: bool,
+    /// Как часто перезапрашиваются сети и комиссии вывода, в секундах
+    pub refresh_interval_secs: u64,
+    /// На сколько сделок распределяется стоимость одной ребалансировки
+    pub amortize_over_trades: u32,
+}
+
+impl Default for TransfersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_interval_secs: 900,
+            amortize_over_trades: 10,
+        }
+    }
+}
+
+/// Контроль привязки стейблкоинов к `arbitrage.reference_quote`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DepegConfig {
+    pub enabled: bool,
+    pub stablecoins: Vec<String>,
+    pub threshold: Decimal,  // в процентах
+    /// Не искать арбитраж в парах с отвязавшейся валютой
+    pub auto_pause: bool,
+}
+
+impl Default for DepegConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stablecoins: ["USDT", "USDC", "BUSD", "DAI"].map(String::from).to_vec(),
+            threshold: dec!(0.5),
+            auto_pause: true,
+        }
+    }
+}
+
+/// Предохранитель бирж: когда исключать нездоровую биржу из анализа и как долго.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    /// Сбоев подряд до открытия
+    pub failure_threshold: u32,
+    pub min_success_rate: f64,          // доля успешных запросов в окне
+    /// Размер окна запросов для доли успешных
+    pub window: usize,
+    /// Минимум запросов в окне, прежде чем судить по доле успешных
+    pub min_requests: usize,
+    /// Сколько биржа может не отдавать годных котировок
+    pub max_data_age_secs: u64,
+    /// Первая пауза; после каждой неудачной пробы удваивается до `max_open_secs`
+    pub open_secs: u64,
+    pub max_open_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_threshold: 5,
+            min_success_rate: 0.5,
+            window: 50,
+            min_requests: 10,
+            max_data_age_secs: 60,
+            open_secs: 30,
+            max_open_secs: 600,
+        }
+    }
+}
+
+/// Окна реализованной волатильности и пороги режима рынка.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolatilityConfig {
+    /// Недавняя волатильность: по ней же проверяется `alerts.volatility_threshold`
+    pub short_window_secs: u64,
+    /// Фоновая волатильность, с которой сравнивается недавняя
+    pub long_window_secs: u64,
+    /// Длина свечи для среднего истинного диапазона
+    pub atr_candle_secs: u64,
+    /// `short / long` не выше — затишье
+    pub calm_ratio: f64,
+    /// `short / long` не ниже — турбулентность
+    pub turbulent_ratio: f64,
+}
+
+impl Default for VolatilityConfig {
+    fn default() -> Self {
+        Self {
+            short_window_secs: 120,
+            long_window_secs: 900,
+            atr_candle_secs: 60,
+            calm_ratio: 0.5,
+            turbulent_ratio: 2.0,
+        }
+    }
+}
+
+/// Правила оповещений и подавление повторов.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertsConfig {
+    /// Проверяются на каждом проходе по метрикам спредов, волатильности и бирж
+    pub rules: Vec<AlertRule>,
+    /// Повтор оповещения с тем же типом, парой и биржами раньше этого срока подавляется,
+    /// если его важность не выше уже отправленного
+    pub cooldown_secs: u64,
+    /// Срок подавления по типам оповещений, вместо `cooldown_secs`
+    pub cooldowns: BTreeMap<AlertType, u64>,
+    /// Оповещений в истории; применяется при запуске
+    pub history_size: usize,
+    /// Куда сохраняются заглушки, чтобы пережить перезапуск; без файла — только в памяти
+    pub silences_file: Option<PathBuf>,
+}
+
+impl AlertsConfig {
+    pub fn cooldown(&self, alert_type: AlertType) -> Duration {
+        Duration::from_secs(self.cooldowns.get(&alert_type).copied().unwrap_or(self.cooldown_secs))
+    }
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            rules: rules::default_rules(),
+            cooldown_secs: 300,
+            cooldowns: BTreeMap::new(),
+            history_size: 1000,
+            silences_file: Some(PathBuf::from("data/silences.json")),
+        }
+    }
+}
+
+/// HTTP API с метриками Prometheus (`/metrics`) и оповещениями. Применяется при запуске.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub enabled: bool,
+    /// Адрес и порт, например `127.0.0.1:8080`
+    pub listen: String,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            listen: "127.0.0.1:8080".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelegramConfig {
+    pub enabled: bool,
+    #[serde(deserialize_with = "string_like")]
+    pub token: String,
+    #[serde(deserialize_with = "string_like")]
+    pub chat_id: String,
+}
+
+/// Каналы уведомлений об оповещениях. Применяется при запуске.
+/// Включённая секция `telegram` добавляет канал с именем `telegram`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Оповещения менее важные не уходят в каналы
+    pub min_severity: u8,
+    pub channels: Vec<ChannelConfig>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            min_severity: 5,
+            channels: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelConfig {
+    /// На это имя ссылается `route` в правилах оповещений
+    pub name: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// Вместо `notifications.min_severity`
+    #[serde(default)]
+    pub min_severity: Option<u8>,
+    #[serde(flatten)]
+    pub kind: ChannelKind,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChannelKind {
+    Telegram {
+        #[serde(deserialize_with = "string_like")]
+        token: String,
+        #[serde(deserialize_with = "string_like")]
+        chat_id: String,
+        /// Адрес Bot API; меняется для проверки на локальной заглушке
+        #[serde(default = "telegram_api_url")]
+        api_url: String,
+    },
+    /// Incoming webhook Slack
+    Slack { webhook_url: String },
+    Discord { webhook_url: String },
+    /// POST уведомления в JSON на произвольный адрес
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+    /// SMTP без TLS: для внешних серверов — через локальный релей
+    Email {
+        host: String,
+        #[serde(default = "smtp_port")]
+        port: u16,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+    },
+    /// notify-send на этой машине
+    Desktop,
+}
+
+pub fn telegram_api_url() -> String {
+    "https://api.telegram.org".to_string()
+}
+
+fn smtp_port() -> u16 {
+    25
+}
+
+/// Строковые поля (ключи, chat_id) из окружения приходят как YAML-скаляры
+/// и могут распознаться как числа — принимаем и их.
+fn string_like<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    use serde::de::Error;
+    match serde_yaml::Value::deserialize(deserializer)? {
+        serde_yaml::Value::String(s) => Ok(s),
+        serde_yaml::Value::Number(n) => Ok(n.to_string()),
+        serde_yaml::Value::Bool(b) => Ok(b.to_string()),
+        serde_yaml::Value::Null => Ok(String::new()),
+        other => Err(D::Error::custom(format!("expected a string, found {:?}", other))),
+    }
+}
+
+impl Config {
+    /// Загружает конфигурацию из файла без окружения и переопределений.
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, ConfigError> {
+        ConfigLoader::new(path).load()
+    }
+
+    /// Конфигурация для вывода с замаскированными секретами.
+    pub fn redacted(&self) -> serde_yaml::Value {
+        redact::redacted(self)
+    }
+
+    /// Включённые биржи в алфавитном порядке.
+    pub fn enabled_exchanges(&self) -> impl Iterator<Item = (&String, &ExchangeConfig)> {
+        self.exchanges.iter().filter(|(_, exchange)| exchange.enabled)
+    }
+
+    /// Общий HTTP-клиент; таймаут по умолчанию — `monitoring.price_timeout_ms`.
+    pub fn http_client(&self) -> reqwest::Result<HttpClient> {
+        HttpClient::new(&self.http, Duration::from_millis(self.monitoring.price_timeout_ms))
+    }
+
+    /// Модель комиссий по расписаниям включённых бирж.
+    pub fn fee_model(&self) -> FeeModel {
+        FeeModel::new(
+            self.enabled_exchanges()
+                .map(|(name, exchange)| (name.clone(), exchange.fees.clone()))
+                .collect(),
+        )
+    }
+
+    /// Модель переводов без данных о сетях и курсов; их добавляют `with_networks` и `with_converter`.
+    /// Без курсов стоимость оценивается только для пар в опорной валюте.
+    /// При выключенном учёте переводов стоимость всегда нулевая.
+    pub fn transfer_model(&self) -> TransferModel {
+        let amortize_over_trades = if self.transfers.enabled {
+            self.transfers.amortize_over_trades
+        } else {
+            0
+        };
+        TransferModel::new(
+            self.monitoring.supported_quote_tokens.clone(),
+            &self.arbitrage.reference_quote,
+            amortize_over_trades,
+            self.arbitrage.max_trade_amount,
+        )
+    }
+
+    /// Фильтр котировок по возрасту и отклонению от медианы.
+    pub fn quality_gate(&self) -> QualityGate {
+        QualityGate::new(self.monitoring.price_timeout_ms, self.arbitrage.max_price_deviation)
+    }
+
+    pub fn pairs(&self) -> Vec<String> {
+        let mut pairs = Vec::new();
+        for base in &self.monitoring.base_tokens {
+            for quote in &self.monitoring.supported_quote_tokens {
+                if base != quote {
+                    pairs.push(format!("{}{}", base, quote));
+                }
+            }
+        }
+        pairs
+    }
+}
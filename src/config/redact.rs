@@ -0,0 +1,113 @@
+use super::Config;
+use serde_yaml::Value;
+
+/// Ключи с секретами: ключи бирж, токены, пароли. Адреса вебхуков несут токен в пути.
+const SECRET_KEYS: [&str; 8] = [
+    "api_key",
+    "api_secret",
+    "passphrase",
+    "token",
+    "password",
+    "url",
+    "webhook_url",
+    "headers",
+];
+
+/// Секретен ли ключ по пути вида `notifications.channels[0].password`.
+/// Всё внутри `headers` тоже секретно: там обычно `Authorization`.
+pub(crate) fn is_secret(path: &str) -> bool {
+    let mut segments = path.split('.').map(|segment| segment.split('[').next().unwrap_or(segment));
+    let last = segments.next_back().unwrap_or_default();
+    segments.any(|segment| segment == "headers") || SECRET_KEYS.contains(&last)
+}
+
+/// Конфигурация для вывода: непустые секреты заменены на `***`, порядок ключей сохранён.
+pub(crate) fn redacted(config: &Config) -> Value {
+    fn walk(path: String, value: Value) -> Value {
+        match value {
+            Value::Mapping(map) => Value::Mapping(
+                map.into_iter()
+                    .map(|(key, value)| {
+                        let name = key.as_str().map(str::to_string).unwrap_or_default();
+                        let path = if path.is_empty() { name } else { format!("{}.{}", path, name) };
+                        (key, walk(path, value))
+                    })
+                    .collect(),
+            ),
+            Value::Sequence(items) => Value::Sequence(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, value)| walk(format!("{}[{}]", path, i), value))
+                    .collect(),
+            ),
+            value if is_secret(&path) && !is_blank(&value) => Value::String("***".to_string()),
+            value => value,
+        }
+    }
+
+    serde_yaml::to_value(config).map_or(Value::Null, |value| walk(String::new(), value))
+}
+
+fn is_blank(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_paths() {
+        assert!(is_secret("exchanges.binance.api_key"));
+        assert!(is_secret("exchanges.okx.api_secret"));
+        assert!(is_secret("exchanges.okx.passphrase"));
+        assert!(is_secret("telegram.token"));
+        assert!(is_secret("notifications.channels[0].password"));
+        assert!(is_secret("notifications.channels[1].webhook_url"));
+        assert!(is_secret("notifications.channels[2].url"));
+        assert!(is_secret("notifications.channels[2].headers.Authorization"));
+        assert!(!is_secret("exchanges.binance.base_url"));
+        assert!(!is_secret("notifications.channels[0].username"));
+        assert!(!is_secret("telegram.chat_id"));
+        assert!(!is_secret("exchanges.binance.fees.discount_token"));
+    }
+
+    #[test]
+    fn redacts_nested_secrets() {
+        let config: Config = serde_yaml::from_str(
+            r#"
+telegram:
+  token: "123:abc"
+notifications:
+  channels:
+    - name: ops
+      type: webhook
+      url: https://hooks.example.com/T0K3N
+      headers:
+        Authorization: Bearer s3cr3t
+    - name: mail
+      type: email
+      host: localhost
+      username: bot
+      password: hunter2
+      from: bot@example.com
+      to: [ops@example.com]
+"#,
+        )
+        .unwrap();
+
+        let text = serde_yaml::to_string(&redacted(&config)).unwrap();
+        for secret in ["123:abc", "T0K3N", "s3cr3t", "hunter2"] {
+            assert!(!text.contains(secret), "{} leaked:\n{}", secret, text);
+        }
+        assert!(text.contains("Authorization: '***'"));
+        assert!(text.contains("username: bot"));
+        // Пустые секреты остаются пустыми: видно, что ключ не задан
+        assert!(text.contains("api_key: ''"));
+    }
+}
@@ -0,0 +1,262 @@
+use super::redact::is_secret;
+use super::{Config, ConfigLoader};
+use log::{error, info, warn};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::watch;
+
+/// Как часто проверяется время изменения файлов конфигурации.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Актуальная конфигурация. Потребители берут снимок (`borrow().clone()`)
+/// в начале каждого цикла, поэтому замена атомарна для них.
+pub type ConfigHandle = watch::Receiver<Arc<Config>>;
+
+/// Хэндл без перезагрузки — для разовых команд.
+pub fn fixed(config: Config) -> ConfigHandle {
+    watch::channel(Arc::new(config)).1
+}
+
+/// Следит за файлами конфигурации и SIGHUP, перечитывает и проверяет
+/// конфигурацию и публикует её, только если в ней нет ошибок.
+pub struct ConfigWatcher {
+    loader: ConfigLoader,
+    sender: watch::Sender<Arc<Config>>,
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl ConfigWatcher {
+    pub fn new(loader: ConfigLoader, initial: Config) -> (Self, ConfigHandle) {
+        let (sender, receiver) = watch::channel(Arc::new(initial));
+        let files = std::iter::once(loader.path().to_path_buf())
+            .chain(loader.environment_path())
+            .map(|path| {
+                let modified = modified(&path);
+                (path, modified)
+            })
+            .collect();
+
+        (Self { loader, sender, files }, receiver)
+    }
+
+    pub async fn run(mut self) {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        let mut hangup = hangup_signal();
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {
+                    if self.files_changed() {
+                        info!("Configuration file changed, reloading");
+                        self.reload();
+                    }
+                }
+                _ = recv_hangup(&mut hangup) => {
+                    info!("Received SIGHUP, reloading configuration");
+                    self.files_changed();
+                    self.reload();
+                }
+            }
+
+            if self.sender.is_closed() {
+                break;
+            }
+        }
+    }
+
+    fn files_changed(&mut self) -> bool {
+        let mut changed = false;
+        for (path, last) in self.files.iter_mut() {
+            let current = modified(path);
+            if current != *last {
+                *last = current;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Перечитывает конфигурацию. Возвращает `true`, если она была заменена.
+    pub fn reload(&self) -> bool {
+        let (config, report) = match self.loader.load_validated() {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                error!("Config reload rejected: {}", e);
+                return false;
+            }
+        };
+
+        if report.has_errors() {
+            for issue in report.errors() {
+                error!("Config reload rejected: {}", issue);
+            }
+            return false;
+        }
+        for issue in report.warnings() {
+            warn!("Config {}", issue);
+        }
+
+        let current = self.sender.borrow().clone();
+        let changes = diff(&current, &config);
+        if changes.is_empty() {
+            info!("Config reloaded, no changes");
+            return false;
+        }
+
+        for change in &changes {
+            info!("Config changed: {}", change);
+        }
+        if current.enabled_exchanges().map(|(name, _)| name).ne(config.enabled_exchanges().map(|(name, _)| name)) {
+            warn!("Enabling or disabling exchanges takes effect after restart");
+        }
+
+        self.sender.send_replace(Arc::new(config));
+        true
+    }
+}
+
+fn modified(path: &PathBuf) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Список изменённых ключей в виде `key: old → new`, секреты скрыты.
+pub fn diff(old: &Config, new: &Config) -> Vec<String> {
+    let old = flatten(old);
+    let new = flatten(new);
+    let mut changes = Vec::new();
+
+    for (key, old_value) in &old {
+        match new.get(key) {
+            Some(new_value) if new_value == old_value => {}
+            Some(new_value) => changes.push(format!("{}: {} → {}", key, redact(key, old_value), redact(key, new_value))),
+            None => changes.push(format!("{}: removed", key)),
+        }
+    }
+    for (key, new_value) in &new {
+        if !old.contains_key(key) {
+            changes.push(format!("{}: added {}", key, redact(key, new_value)));
+        }
+    }
+
+    changes
+}
+
+fn flatten(config: &Config) -> BTreeMap<String, String> {
+    fn walk(prefix: String, value: serde_json::Value, out: &mut BTreeMap<String, String>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    let path = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                    walk(path, value, out);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for (i, value) in items.into_iter().enumerate() {
+                    walk(format!("{}[{}]", prefix, i), value, out);
+                }
+            }
+            other => {
+                out.insert(prefix, other.to_string());
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    if let Ok(value) = serde_json::to_value(config) {
+        walk(String::new(), value, &mut out);
+    }
+    out
+}
+
+fn redact<'a>(key: &str, value: &'a str) -> &'a str {
+    if is_secret(key) && value != "\"\"" && value != "null" {
+        "***"
+    } else {
+        value
+    }
+}
+
+#[cfg(unix)]
+type Hangup = Option<tokio::signal::unix::Signal>;
+#[cfg(not(unix))]
+type Hangup = Option<()>;
+
+#[cfg(unix)]
+fn hangup_signal() -> Hangup {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::hangup()) {
+        Ok(signal) => Some(signal),
+        Err(e) => {
+            warn!("Cannot listen for SIGHUP: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn hangup_signal() -> Hangup {
+    None
+}
+
+async fn recv_hangup(hangup: &mut Hangup) {
+    #[cfg(unix)]
+    if let Some(signal) = hangup {
+        signal.recv().await;
+        return;
+    }
+    let _ = hangup;
+    std::future::pending::<()>().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(yaml: &str) -> Config {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn diff_redacts_secrets_inside_lists() {
+        let old = config(
+            r#"
+notifications:
+  channels:
+    - name: mail
+      type: email
+      host: localhost
+      password: old-pass
+      from: bot@example.com
+      to: [ops@example.com]
+"#,
+        );
+        let new = config(
+            r#"
+notifications:
+  channels:
+    - name: mail
+      type: email
+      host: localhost
+      password: new-pass
+      from: bot@example.com
+      to: [ops@example.com]
+    - name: ops
+      type: webhook
+      url: https://hooks.example.com/T0K3N
+      headers:
+        Authorization: Bearer s3cr3t
+"#,
+        );
+
+        let changes = diff(&old, &new);
+        assert!(changes.contains(&"notifications.channels[0].password: *** → ***".to_string()));
+        assert!(changes.contains(&"notifications.channels[1].url: added ***".to_string()));
+        assert!(changes.contains(&"notifications.channels[1].headers.Authorization: added ***".to_string()));
+        assert!(changes.contains(&"notifications.channels[1].name: added \"ops\"".to_string()));
+        for secret in ["old-pass", "new-pass", "T0K3N", "s3cr3t"] {
+            assert!(changes.iter().all(|change| !change.contains(secret)), "{} leaked: {:?}", secret, changes);
+        }
+    }
+}
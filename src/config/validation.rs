@@ -0,0 +1,663 @@
+use super::{ChannelKind, Config, ExchangeConfig, TradingMode};
+use crate::alerts::Metric;
+use crate::exchanges::cex;
+use crate::metrics::HISTORY_CAPACITY;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub severity: Severity,
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{:<7} {}: {}", level, self.key, self.message)
+    }
+}
+
+/// Результат проверки конфигурации. Ошибки не дают запустить систему,
+/// предупреждения только логируются.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    fn warn(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Warning, key.into(), message.into());
+    }
+
+    fn error(&mut self, key: impl Into<String>, message: impl Into<String>) {
+        self.push(Severity::Error, key.into(), message.into());
+    }
+
+    fn push(&mut self, severity: Severity, key: String, message: String) {
+        self.issues.push(Issue { severity, key, message });
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Семантическая проверка: единицы, диапазоны, согласованность полей и неизвестные ключи.
+pub fn validate(config: &Config, unknown_keys: &[String]) -> ValidationReport {
+    let mut report = ValidationReport::default();
+
+    for key in unknown_keys {
+        report.warn(key.clone(), "unknown key, ignored");
+    }
+
+    validate_exchanges(config, &mut report);
+    validate_monitoring(config, &mut report);
+    validate_http(config, &mut report);
+    validate_arbitrage(config, &mut report);
+    validate_circuit_breaker(config, &mut report);
+    validate_volatility(config, &mut report);
+
+    if config.transfers.enabled {
+        if config.transfers.amortize_over_trades == 0 {
+            report.error("transfers.amortize_over_trades", "must be at least 1");
+        }
+        if config.transfers.refresh_interval_secs == 0 {
+            report.error("transfers.refresh_interval_secs", "must be greater than 0");
+        } else if config.transfers.refresh_interval_secs < 60 {
+            report.warn(
+                "transfers.refresh_interval_secs",
+                format!("{}s is likely to hit exchange rate limits", config.transfers.refresh_interval_secs),
+            );
+        }
+    }
+
+    if config.depeg.enabled {
+        validate_tokens("depeg.stablecoins", &config.depeg.stablecoins, &mut report);
+        if config.depeg.threshold <= dec!(0) {
+            report.error("depeg.threshold", "must be greater than 0");
+        } else if config.depeg.threshold > dec!(5) {
+            report.warn("depeg.threshold", format!("{}% lets large depegs through unnoticed", config.depeg.threshold));
+        }
+        if !config.depeg.stablecoins.contains(&config.arbitrage.reference_quote) {
+            report.error(
+                "depeg.stablecoins",
+                format!("must include arbitrage.reference_quote {}", config.arbitrage.reference_quote),
+            );
+        }
+    }
+
+    validate_alert_rules(config, &mut report);
+    validate_notifications(config, &mut report);
+    if config.alerts.history_size == 0 {
+        report.error("alerts.history_size", "must be at least 1");
+    }
+    if config.alerts.cooldown_secs == 0 {
+        report.warn("alerts.cooldown_secs", "0 sends a persistent condition as an alert on every scan");
+    }
+
+    if config.api.enabled && config.api.listen.parse::<std::net::SocketAddr>().is_err() {
+        report.error("api.listen", format!("{:?} is not an address like 127.0.0.1:8080", config.api.listen));
+    }
+
+    if config.telegram.enabled {
+        if config.telegram.token.is_empty() {
+            report.error("telegram.token", "telegram is enabled but no bot token is set");
+        }
+        if config.telegram.chat_id.is_empty() {
+            report.error("telegram.chat_id", "telegram is enabled but no chat id is set");
+        }
+    }
+
+    report.issues.sort_by_key(|i| std::cmp::Reverse(i.severity));
+    report
+}
+
+fn validate_exchanges(config: &Config, report: &mut ValidationReport) {
+    let mut usable = 0;
+
+    for (name, exchange) in &config.exchanges {
+        let key = format!("exchanges.{}", name);
+
+        validate_fees(&key, exchange, report);
+        for (field, value) in [("burst", exchange.rate_limit.burst), ("weight_per_second", exchange.rate_limit.weight_per_second)] {
+            if value.is_some_and(|v| v <= 0.0 || !v.is_finite()) {
+                report.error(format!("{}.rate_limit.{}", key, field), "must be greater than 0");
+            }
+        }
+        if let Some(url) = &exchange.base_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                report.error(format!("{}.base_url", key), "must start with http:// or https://");
+            }
+        }
+        if let Some(proxy) = &exchange.proxy {
+            if let Err(e) = reqwest::Proxy::all(proxy) {
+                report.error(format!("{}.proxy", key), format!("invalid proxy URL: {}", e));
+            }
+        }
+
+        if !exchange.enabled {
+            continue;
+        }
+        if !cex::is_supported(name) {
+            report.warn(key, "no adapter for this exchange, it will be skipped");
+            continue;
+        }
+        usable += 1;
+
+        if config.arbitrage.mode == TradingMode::Live {
+            if exchange.api_key.is_empty() || exchange.api_secret.is_empty() {
+                report.error(key.clone(), "live trading requires api_key and api_secret");
+            }
+            if name.eq_ignore_ascii_case("kucoin") && exchange.passphrase.is_empty() {
+                report.error(format!("{}.passphrase", key), "live trading on KuCoin requires a passphrase");
+            }
+        }
+    }
+
+    if usable == 0 {
+        report.error("exchanges", "no supported exchange is enabled");
+    }
+
+    let required = config.arbitrage.min_exchanges_required;
+    if required < 2 {
+        report.error("arbitrage.min_exchanges_required", "arbitrage needs at least 2 exchanges");
+    } else if required > usable {
+        report.error(
+            "arbitrage.min_exchanges_required",
+            format!("requires {} exchanges but only {} supported exchanges are enabled", required, usable),
+        );
+    }
+}
+
+fn validate_fees(key: &str, exchange: &ExchangeConfig, report: &mut ValidationReport) {
+    let fees = &exchange.fees;
+    let key = format!("{}.fees", key);
+
+    fee_rate(&format!("{}.maker", key), fees.maker, report);
+    fee_rate(&format!("{}.taker", key), fees.taker, report);
+    for (i, tier) in fees.tiers.iter().enumerate() {
+        fee_rate(&format!("{}.tiers[{}].maker", key, i), tier.maker, report);
+        fee_rate(&format!("{}.tiers[{}].taker", key, i), tier.taker, report);
+        if tier.min_volume_30d < dec!(0) {
+            report.error(format!("{}.tiers[{}].min_volume_30d", key, i), "must not be negative");
+        }
+    }
+    for (symbol, rates) in &fees.symbols {
+        fee_rate(&format!("{}.symbols.{}.maker", key, symbol), rates.maker, report);
+        fee_rate(&format!("{}.symbols.{}.taker", key, symbol), rates.taker, report);
+    }
+
+    if fees.token_discount < dec!(0) || fees.token_discount >= dec!(1) {
+        report.error(
+            format!("{}.token_discount", key),
+            "must be a fraction in [0, 1), e.g. 0.25 for 25%",
+        );
+    }
+    if fees.pay_with_token && fees.discount_token.is_none() {
+        report.warn(format!("{}.pay_with_token", key), "no discount_token set, discount is ignored");
+    }
+    if fees.fetch_account_rates && (exchange.api_key.is_empty() || exchange.api_secret.is_empty()) {
+        report.warn(
+            format!("{}.fetch_account_rates", key),
+            "requires api_key and api_secret, falling back to the configured schedule",
+        );
+    }
+}
+
+/// Комиссии — доли; отрицательная ставка мейкера допустима (ребейт).
+fn fee_rate(key: &str, rate: Decimal, report: &mut ValidationReport) {
+    if rate <= dec!(-0.01) || rate >= dec!(0.1) {
+        report.error(key, format!("{} is out of range; fees are fractions (0.001 = 0.1%)", rate));
+    } else if rate > dec!(0.01) {
+        report.warn(
+            key,
+            format!("{} means {}%; fees are fractions (0.001 = 0.1%)", rate, (rate * dec!(100)).normalize()),
+        );
+    }
+}
+
+fn validate_monitoring(config: &Config, report: &mut ValidationReport) {
+    let monitoring = &config.monitoring;
+
+    if monitoring.update_interval_ms == 0 {
+        report.error("monitoring.update_interval_ms", "must be greater than 0");
+    } else if monitoring.update_interval_ms < 200 {
+        report.warn(
+            "monitoring.update_interval_ms",
+            format!("{}ms is likely to hit exchange rate limits", monitoring.update_interval_ms),
+        );
+    }
+
+    if monitoring.price_timeout_ms == 0 {
+        report.error("monitoring.price_timeout_ms", "must be greater than 0");
+    } else if monitoring.price_timeout_ms > 60_000 {
+        report.warn(
+            "monitoring.price_timeout_ms",
+            format!("{}ms lets very stale prices into analysis", monitoring.price_timeout_ms),
+        );
+    }
+
+    if monitoring.clock_sync_interval_secs > 0 && monitoring.clock_sync_interval_secs < 10 {
+        report.warn(
+            "monitoring.clock_sync_interval_secs",
+            format!("{}s wastes request weight; clock drift is slow", monitoring.clock_sync_interval_secs),
+        );
+    }
+
+    validate_tokens("monitoring.supported_quote_tokens", &monitoring.supported_quote_tokens, report);
+    validate_tokens("monitoring.base_tokens", &monitoring.base_tokens, report);
+}
+
+fn validate_http(config: &Config, report: &mut ValidationReport) {
+    let http = &config.http;
+
+    if http.timeout_ms == Some(0) {
+        report.error("http.timeout_ms", "must be greater than 0");
+    }
+    if http.connect_timeout_ms == 0 {
+        report.error("http.connect_timeout_ms", "must be greater than 0");
+    }
+    if http.max_retries > 5 {
+        report.warn(
+            "http.max_retries",
+            format!("{} retries can stall a scan far beyond monitoring.update_interval_ms", http.max_retries),
+        );
+    }
+    if http.max_retries > 0 && http.retry_backoff_ms == 0 {
+        report.warn("http.retry_backoff_ms", "retries without a pause hammer a failing exchange");
+    }
+}
+
+fn validate_circuit_breaker(config: &Config, report: &mut ValidationReport) {
+    let breaker = &config.circuit_breaker;
+    if !breaker.enabled {
+        return;
+    }
+
+    if breaker.failure_threshold == 0 {
+        report.error("circuit_breaker.failure_threshold", "must be at least 1");
+    }
+    if !(0.0..=1.0).contains(&breaker.min_success_rate) {
+        report.error("circuit_breaker.min_success_rate", "must be a fraction between 0 and 1");
+    }
+    if breaker.window == 0 {
+        report.error("circuit_breaker.window", "must be at least 1");
+    }
+    if breaker.min_requests > breaker.window {
+        report.warn(
+            "circuit_breaker.min_requests",
+            format!("is capped by circuit_breaker.window ({})", breaker.window),
+        );
+    }
+    if breaker.open_secs == 0 {
+        report.error("circuit_breaker.open_secs", "must be greater than 0");
+    }
+    if breaker.max_open_secs < breaker.open_secs {
+        report.error("circuit_breaker.max_open_secs", "must not be less than circuit_breaker.open_secs");
+    }
+    let update_secs = config.monitoring.update_interval_ms / 1000;
+    if breaker.max_data_age_secs <= update_secs {
+        report.warn(
+            "circuit_breaker.max_data_age_secs",
+            format!("{}s does not exceed monitoring.update_interval_ms, every exchange will trip", breaker.max_data_age_secs),
+        );
+    }
+}
+
+fn validate_volatility(config: &Config, report: &mut ValidationReport) {
+    let volatility = &config.volatility;
+
+    if volatility.short_window_secs == 0 {
+        report.error("volatility.short_window_secs", "must be greater than 0");
+    }
+    if volatility.long_window_secs <= volatility.short_window_secs {
+        report.error("volatility.long_window_secs", "must be greater than volatility.short_window_secs");
+    }
+    if volatility.atr_candle_secs == 0 {
+        report.error("volatility.atr_candle_secs", "must be greater than 0");
+    } else if volatility.atr_candle_secs > volatility.long_window_secs {
+        report.warn("volatility.atr_candle_secs", "exceeds volatility.long_window_secs, ATR covers a single candle");
+    }
+    if !(volatility.calm_ratio > 0.0 && volatility.calm_ratio < 1.0) {
+        report.error("volatility.calm_ratio", "must be between 0 and 1");
+    }
+    if volatility.turbulent_ratio <= 1.0 {
+        report.error("volatility.turbulent_ratio", "must be greater than 1");
+    }
+    // История цен ограничена числом точек, а не временем
+    let history_secs = HISTORY_CAPACITY as u64 * config.monitoring.update_interval_ms / 1000;
+    if volatility.long_window_secs > history_secs {
+        report.warn(
+            "volatility.long_window_secs",
+            format!("price history holds about {}s at monitoring.update_interval_ms, the window is truncated", history_secs),
+        );
+    }
+}
+
+fn validate_alert_rules(config: &Config, report: &mut ValidationReport) {
+    let mut names = HashSet::new();
+    for (i, rule) in config.alerts.rules.iter().enumerate() {
+        let key = format!("alerts.rules[{}]", i);
+        if rule.name.is_empty() {
+            report.error(format!("{}.name", key), "must not be empty");
+        } else if !names.insert(rule.name.as_str()) {
+            report.error(format!("{}.name", key), format!("rule {} is defined twice", rule.name));
+        }
+        if !rule.threshold.is_finite() {
+            report.error(format!("{}.threshold", key), "must be a number");
+        } else if rule.metric == Metric::SuccessRate && !(0.0..=1.0).contains(&rule.threshold) {
+            report.error(format!("{}.threshold", key), "success_rate is a fraction between 0 and 1");
+        }
+        if !(1..=10).contains(&rule.severity) {
+            report.error(format!("{}.severity", key), "must be between 1 and 10");
+        }
+        if rule.metric.per_exchange() && !rule.symbols.is_empty() {
+            report.warn(format!("{}.symbols", key), "exchange metrics have no symbol, the rule never fires");
+        }
+        for exchange in &rule.exchanges {
+            if !config.exchanges.keys().any(|name| name.eq_ignore_ascii_case(exchange)) {
+                report.error(format!("{}.exchanges", key), format!("unknown exchange {}", exchange));
+            }
+        }
+        if let Some(route) = &rule.route {
+            let known = config.notifications.channels.iter().any(|c| &c.name == route)
+                || (config.telegram.enabled && route == "telegram");
+            if !known {
+                report.error(format!("{}.route", key), format!("no notification channel named {}", route));
+            }
+        }
+        if rule.metric == Metric::Spread && rule.exchanges.len() == 1 {
+            report.warn(
+                format!("{}.exchanges", key),
+                "spread is between two exchanges, both must be listed for the rule to fire",
+            );
+        }
+    }
+}
+
+fn validate_notifications(config: &Config, report: &mut ValidationReport) {
+    let notifications = &config.notifications;
+    if !(1..=10).contains(&notifications.min_severity) {
+        report.error("notifications.min_severity", "must be between 1 and 10");
+    }
+
+    let mut names = HashSet::new();
+    if config.telegram.enabled {
+        names.insert("telegram");
+    }
+    for (i, channel) in notifications.channels.iter().enumerate() {
+        let key = format!("notifications.channels[{}]", i);
+        if channel.name.is_empty() {
+            report.error(format!("{}.name", key), "must not be empty");
+        } else if !names.insert(channel.name.as_str()) {
+            report.error(format!("{}.name", key), format!("channel {} is defined twice", channel.name));
+        }
+        if channel.min_severity.is_some_and(|severity| !(1..=10).contains(&severity)) {
+            report.error(format!("{}.min_severity", key), "must be between 1 and 10");
+        }
+        if !channel.enabled {
+            continue;
+        }
+
+        let url = |field: &str, url: &str, report: &mut ValidationReport| {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                // Сам адрес не выводится: в нём токен вебхука
+                report.error(format!("{}.{}", key, field), "is not an http(s) URL");
+            }
+        };
+        match &channel.kind {
+            ChannelKind::Telegram { token, chat_id, api_url } => {
+                if token.is_empty() {
+                    report.error(format!("{}.token", key), "no bot token is set");
+                }
+                if chat_id.is_empty() {
+                    report.error(format!("{}.chat_id", key), "no chat id is set");
+                }
+                url("api_url", api_url, report);
+            }
+            ChannelKind::Slack { webhook_url } | ChannelKind::Discord { webhook_url } => {
+                url("webhook_url", webhook_url, report);
+            }
+            ChannelKind::Webhook { url: target, .. } => url("url", target, report),
+            ChannelKind::Email { host, username, password, from, to, .. } => {
+                if host.is_empty() {
+                    report.error(format!("{}.host", key), "must not be empty");
+                }
+                if !from.contains('@') {
+                    report.error(format!("{}.from", key), format!("{:?} is not an email address", from));
+                }
+                if to.is_empty() {
+                    report.error(format!("{}.to", key), "must list at least one recipient");
+                }
+                for address in to.iter().filter(|address| !address.contains('@')) {
+                    report.error(format!("{}.to", key), format!("{:?} is not an email address", address));
+                }
+                if username.is_some() != password.is_some() {
+                    report.error(format!("{}.password", key), "username and password must be set together");
+                }
+                let local = matches!(host.as_str(), "localhost" | "127.0.0.1" | "::1");
+                if username.is_some() && !local {
+                    report.warn(
+                        format!("{}.host", key),
+                        "SMTP is sent without TLS, credentials travel in clear text; use a local relay",
+                    );
+                }
+            }
+            ChannelKind::Desktop => {}
+        }
+    }
+}
+
+fn validate_tokens(key: &str, tokens: &[String], report: &mut ValidationReport) {
+    if tokens.is_empty() {
+        report.error(key, "must not be empty");
+        return;
+    }
+
+    let mut seen = HashSet::new();
+    for token in tokens {
+        if token.is_empty() || token.chars().any(|c| !c.is_ascii_uppercase() && !c.is_ascii_digit()) {
+            report.warn(key, format!("token {:?} should be upper-case alphanumeric", token));
+        }
+        if !seen.insert(token) {
+            report.warn(key, format!("token {} is listed twice", token));
+        }
+    }
+}
+
+fn validate_arbitrage(config: &Config, report: &mut ValidationReport) {
+    let arbitrage = &config.arbitrage;
+
+    let min_profit = arbitrage.min_profit_percentage;
+    if min_profit < dec!(0) {
+        report.error("arbitrage.min_profit_percentage", "must not be negative");
+    } else if min_profit > dec!(0) && min_profit < dec!(0.05) {
+        report.warn(
+            "arbitrage.min_profit_percentage",
+            format!("{} is in percent ({}% profit); did you mean {}?", min_profit, min_profit, (min_profit * dec!(100)).normalize()),
+        );
+    } else if min_profit > dec!(10) {
+        report.warn("arbitrage.min_profit_percentage", format!("{}% is unrealistically high", min_profit));
+    }
+
+    let deviation = arbitrage.max_price_deviation;
+    if deviation <= dec!(0) || deviation > dec!(100) {
+        report.error("arbitrage.max_price_deviation", "must be a percentage in (0, 100]");
+    } else if deviation < dec!(1) {
+        report.warn(
+            "arbitrage.max_price_deviation",
+            format!("{} is in percent; quotes deviating more than {}% will be dropped", deviation, deviation),
+        );
+    }
+
+    non_negative("arbitrage.min_volume_24h", arbitrage.min_volume_24h, report);
+    non_negative("arbitrage.min_profit_usd", arbitrage.min_profit_usd, report);
+    non_negative("arbitrage.max_loss_per_trade", arbitrage.max_loss_per_trade, report);
+    non_negative("arbitrage.max_daily_loss", arbitrage.max_daily_loss, report);
+    non_negative("arbitrage.conversion_slippage", arbitrage.conversion_slippage, report);
+
+    if arbitrage.cross_quote {
+        if !config.monitoring.supported_quote_tokens.contains(&arbitrage.reference_quote) {
+            report.error(
+                "arbitrage.reference_quote",
+                format!("{} is not in monitoring.supported_quote_tokens", arbitrage.reference_quote),
+            );
+        }
+        if arbitrage.conversion_slippage > dec!(1) {
+            report.warn(
+                "arbitrage.conversion_slippage",
+                format!("{} is in percent ({}% per conversion)", arbitrage.conversion_slippage, arbitrage.conversion_slippage),
+            );
+        }
+    }
+
+    if arbitrage.max_trade_amount <= dec!(0) {
+        report.error("arbitrage.max_trade_amount", "must be greater than 0");
+    }
+    if arbitrage.daily_limit < arbitrage.max_trade_amount {
+        report.warn(
+            "arbitrage.daily_limit",
+            format!("{} is below max_trade_amount {}", arbitrage.daily_limit, arbitrage.max_trade_amount),
+        );
+    }
+    if arbitrage.max_loss_per_trade > arbitrage.max_trade_amount {
+        report.warn("arbitrage.max_loss_per_trade", "exceeds max_trade_amount");
+    }
+    if arbitrage.max_daily_loss < arbitrage.max_loss_per_trade {
+        report.warn("arbitrage.max_daily_loss", "is below max_loss_per_trade");
+    }
+    if arbitrage.min_profit_usd > arbitrage.max_trade_amount * min_profit / dec!(100)
+        && min_profit > dec!(0)
+    {
+        report.warn(
+            "arbitrage.min_profit_usd",
+            format!(
+                "{} cannot be reached at min_profit_percentage {}% with max_trade_amount {}",
+                arbitrage.min_profit_usd, min_profit, arbitrage.max_trade_amount
+            ),
+        );
+    }
+}
+
+fn non_negative(key: &str, value: Decimal, report: &mut ValidationReport) {
+    if value < dec!(0) {
+        report.error(key, "must not be negative");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(yaml: &str) -> Vec<(String, String)> {
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        validate(&config, &[])
+            .errors()
+            .map(|issue| (issue.key.clone(), issue.message.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn duplicate_rule_names_are_rejected() {
+        let errors = errors(
+            r#"
+alerts:
+  rules:
+    - { name: wide, metric: spread, op: ">", threshold: 0.5 }
+    - { name: wide, metric: spread, op: ">", threshold: 1.0 }
+"#,
+        );
+        assert_eq!(errors, vec![("alerts.rules[1].name".to_string(), "rule wide is defined twice".to_string())]);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_key() {
+        let cases = [
+            (
+                r#"
+alerts:
+  rules:
+    - { name: wide, metric: spread, op: ">", threshold: 0.5, route: pager }
+"#,
+                "alerts.rules[0].route",
+                "no notification channel named pager",
+            ),
+            (
+                "exchanges: { binance: { fees: { maker: -0.05 } } }",
+                "exchanges.binance.fees.maker",
+                "-0.05 is out of range",
+            ),
+            (
+                "exchanges: { bybit: { fees: { taker: -0.2 } } }",
+                "exchanges.bybit.fees.taker",
+                "-0.2 is out of range",
+            ),
+            (
+                "api: { enabled: true, listen: localhost }",
+                "api.listen",
+                "\"localhost\" is not an address",
+            ),
+            (
+                "notifications: { channels: [{ name: ops, type: slack, webhook_url: hooks.slack.com/T0 }] }",
+                "notifications.channels[0].webhook_url",
+                "is not an http(s) URL",
+            ),
+            (
+                "telegram: { enabled: true, chat_id: '-100' }",
+                "telegram.token",
+                "no bot token is set",
+            ),
+        ];
+
+        for (yaml, key, message) in cases {
+            let errors = errors(yaml);
+            assert!(errors.iter().all(|(_, m)| !m.contains("hooks.slack.com")), "secret leaked: {:?}", errors);
+            assert!(
+                errors.iter().any(|(k, m)| k == key && m.contains(message)),
+                "expected {}: {} in {:?}",
+                key,
+                message,
+                errors
+            );
+        }
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert!(errors("{}").is_empty());
+    }
+}
@@ -1,129 +1,248 @@
 use crossterm::{
+    event::{self, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
     cursor::{Hide, Show, MoveTo},
     style::Stylize,
 };
+use std::fmt::Write as _;
 use std::io::{stdout, Write};
-use chrono::Utc;
-use rust_decimal::Decimal;
-use crate::core::{logger::log, pairs::PairsManager};
+use chrono::{DateTime, Utc};
+use crate::alerts::{AlertQuery, AlertState};
+use crate::config::ConfigHandle;
+use crate::core::{logger::log, pairs::PairsManager, scanner::{ScanResult, Scanner}};
+use crate::metrics::Regime;
 use std::time::Duration;
 use tokio::time::sleep;
 
-#[derive(Clone)]
-pub struct Exchange {
-    pub name: String,
+/// На сколько клавиша `s` глушит последнее оповещение.
+const SILENCE_SECS: u64 = 3600;
+
+/// Команды с клавиатуры.
+enum Input {
+    Quit,
+    /// `a` — подтвердить все действующие оповещения
+    Acknowledge,
+    /// `s` — заглушить последнее оповещение на час
+    Silence,
 }
 
 pub struct App {
     user: String,
     pairs_manager: PairsManager,
-    exchanges: Vec<Exchange>,
+    scanner: Scanner,
+    config: ConfigHandle,
 }
 
 impl App {
-    pub fn new(user: &str) -> Self {
-        let exchanges = vec![
-            Exchange { name: "Binance".to_string() },
-            Exchange { name: "Bybit".to_string() },
-            Exchange { name: "Kucoin".to_string() }, // Исправлено с OKX на Kucoin
-        ];
-    
+    pub fn new(user: &str, config: ConfigHandle) -> Self {
         Self {
             user: user.to_string(),
             pairs_manager: PairsManager::new(),
-            exchanges,
+            scanner: Scanner::new(config.clone()),
+            config,
         }
     }
 
+    pub fn scanner(&self) -> &Scanner {
+        &self.scanner
+    }
+
     pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
         log("Starting Arbitrage Monitor");
-        
+
         let mut stdout = stdout();
         enable_raw_mode()?;
         execute!(stdout, Hide)?;
 
         let (tx, mut rx) = tokio::sync::mpsc::channel(1);
         let tx_clone = tx.clone();
-        
+
         ctrlc::set_handler(move || {
-            let _ = tx_clone.blocking_send(());
+            let _ = tx_clone.blocking_send(Input::Quit);
         })?;
 
-        let start_time = Utc::now();
-        let mut counter = 0;
-
-        loop {
-            tokio::select! {
-                _ = rx.recv() => {
-                    self.cleanup_and_exit(&mut stdout, "Received Ctrl+C").await?;
+        // В raw-режиме Ctrl+C не порождает SIGINT, поэтому читаем клавиши сами
+        std::thread::spawn(move || loop {
+            if let Ok(Event::Key(key)) = event::read() {
+                let ctrl_c = key.code == KeyCode::Char('c')
+                    && key.modifiers.contains(KeyModifiers::CONTROL);
+                if ctrl_c || key.code == KeyCode::Char('q') || key.code == KeyCode::Esc {
+                    let _ = tx.blocking_send(Input::Quit);
                     break;
                 }
-                _ = async {
-                    counter += 1;
-                    let now = Utc::now();
-                    
-                    execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
-
-                    println!("╔════════════════════════════════════════════════════════════════╗");
-                    println!("║ 🤖 Arbitrage Monitor v1.0                                      ║");
-                    println!("║ 👤 User: {:<52} ║", self.user.clone().blue());
-                    println!("║ 🕒 Started: {:<48} ║", 
-                        start_time.format("%Y-%m-%d %H:%M:%S UTC").to_string());
-                    println!("║ ⌛ Uptime: {:<50} ║",
-                        format!("{}h {}m {}s",
-                            (now - start_time).num_hours(),
-                            (now - start_time).num_minutes() % 60,
-                            (now - start_time).num_seconds() % 60
-                        ).yellow()
-                    );
-                    println!("║ 📊 Pairs: {:<3} | Exchanges: {:<3} | Updates: {:<5}            ║", 
-                        self.pairs_manager.get_pairs_count(),
-                        self.exchanges.len(),
-                        counter.to_string().yellow()
-                    );
-                    println!("╚════════════════════════════════════════════════════════════════╝\n");
-
-                    println!("Update #{} - {}", 
-                        counter.to_string().yellow(),
-                        now.format("%Y-%m-%d %H:%M:%S UTC").to_string()
-                    );
-                    println!("{}\n", "─".repeat(70));
-
-                    PairsManager::print_table_header();
-
-                    let test_opportunity = crate::core::pairs::ArbitrageOpportunity {
-                        pair: "BTCUSDT".to_string(),
-                        buy_exchange: "Binance".to_string(),
-                        sell_exchange: "Bybit".to_string(),
-                        buy_price: Decimal::new(42000, 0),
-                        sell_price: Decimal::new(42100, 0),
-                        spread: Decimal::new(238, 3),
-                        timestamp: Utc::now(),
-                    };
-
-                    println!("{}", self.pairs_manager.format_opportunity(&test_opportunity));
-                    println!("└──────────┴────────────────────┴────────────────────┴──────────┴──────────┴──────────┘");
-
-                    println!("\n📈 Performance Stats:");
-                    println!("  📊 Pairs monitored: {}", self.pairs_manager.get_pairs_count());
-                    println!("  🏢 Active exchanges: {}", self.exchanges.len());
-                    println!("  ⚡ Updates: {}", counter);
-                    
-                    print!("\n⏳ Next update in 1s... (Press Ctrl+C to exit)");
-                    stdout.flush()?;
-
-                    sleep(Duration::from_secs(1)).await;
-                    Ok::<(), Box<dyn std::error::Error>>(())
-                } => {
-                    if let Err(e) = std::io::stdout().flush() {
-                        log(&format!("Error flushing stdout: {}", e));
+                let input = match key.code {
+                    KeyCode::Char('a') => Input::Acknowledge,
+                    KeyCode::Char('s') => Input::Silence,
+                    _ => continue,
+                };
+                let _ = tx.blocking_send(input);
+            }
+        });
+
+        let start_time = Utc::now();
+
+        // Клавиши обрабатываются параллельно с проходом и не прерывают его:
+        // проход обрывается только при выходе
+        let inputs = async {
+            while let Some(input) = rx.recv().await {
+                let alerts = self.scanner.alerts();
+                match input {
+                    Input::Acknowledge => {
+                        let count = alerts.lock().await.acknowledge_all(Utc::now());
+                        log(&format!("Acknowledged {} alerts", count));
                     }
+                    Input::Silence => match alerts.lock().await.silence_latest(SILENCE_SECS, Utc::now()) {
+                        Ok(Some(silence)) => log(&format!("Silence #{} added: {}", silence.id, silence.comment)),
+                        Ok(None) => log("No firing alert to silence"),
+                        Err(e) => log(&format!("Failed to add silence: {}", e)),
+                    },
+                    Input::Quit => break,
+                }
+            }
+        };
+        let updates = async {
+            let mut counter = 0;
+            loop {
+                counter += 1;
+                let scan = self.scanner.scan().await;
+                let update_interval = Duration::from_millis(self.config.borrow().monitoring.update_interval_ms);
+                if let Err(e) = self.render(&mut stdout, &scan, counter, start_time, update_interval).await {
+                    log(&format!("Error rendering update: {}", e));
                 }
+                sleep(update_interval).await;
             }
+        };
+        tokio::select! {
+            _ = inputs => {}
+            _ = updates => {}
         }
 
+        self.cleanup_and_exit(&mut stdout, "Received Ctrl+C").await
+    }
+
+    /// Кадр с результатами прохода, статистикой и оповещениями.
+    async fn render(
+        &self,
+        stdout: &mut std::io::Stdout,
+        scan: &ScanResult,
+        counter: u64,
+        start_time: DateTime<Utc>,
+        update_interval: Duration,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let pairs_count = self.scanner.pairs().len();
+        let now = Utc::now();
+
+        let mut frame = String::new();
+        writeln!(frame, "╔════════════════════════════════════════════════════════════════╗")?;
+        writeln!(frame, "║ 🤖 Arbitrage Monitor v1.0                                      ║")?;
+        writeln!(frame, "║ 👤 User: {:<52} ║", self.user.clone().blue())?;
+        writeln!(frame, "║ 🕒 Started: {:<48} ║",
+            start_time.format("%Y-%m-%d %H:%M:%S UTC").to_string())?;
+        writeln!(frame, "║ ⌛ Uptime: {:<50} ║",
+            format!("{}h {}m {}s",
+                (now - start_time).num_hours(),
+                (now - start_time).num_minutes() % 60,
+                (now - start_time).num_seconds() % 60
+            ).yellow()
+        )?;
+        writeln!(frame, "║ 📊 Pairs: {:<3} | Exchanges: {:<3} | Updates: {:<5}            ║",
+            pairs_count,
+            self.scanner.exchange_count(),
+            counter.to_string().yellow()
+        )?;
+        writeln!(frame, "╚════════════════════════════════════════════════════════════════╝\n")?;
+
+        writeln!(frame, "Update #{} - {}",
+            counter.to_string().yellow(),
+            now.format("%Y-%m-%d %H:%M:%S UTC")
+        )?;
+        writeln!(frame, "{}\n", "─".repeat(70))?;
+
+        frame.push_str(&PairsManager::table_header());
+        for opportunity in &scan.opportunities {
+            writeln!(frame, "{}", self.pairs_manager.format_opportunity(opportunity))?;
+        }
+        writeln!(frame, "└──────────┴────────────────────┴────────────────────┴──────────┴──────────┴──────────┘")?;
+
+        writeln!(frame, "\n📈 Performance Stats:")?;
+        writeln!(frame, "  📊 Pairs monitored: {}", pairs_count)?;
+        writeln!(frame, "  🏢 Active exchanges: {}/{}",
+            self.scanner.exchange_count() - scan.open_circuits.len(), self.scanner.exchange_count())?;
+        writeln!(frame, "  💹 Quotes received: {} (errors: {})", scan.prices.len(), scan.errors)?;
+        writeln!(frame, "  ⚡ Updates: {}", counter)?;
+        let persistence = self.scanner.tracker().lock().await.report().total;
+        if persistence.count > 0 {
+            writeln!(frame, "  ⌛ Opportunity lifetime: {:.1}s median, {:.1}s p90 ({} closed)",
+                persistence.duration_p50_secs, persistence.duration_p90_secs, persistence.count)?;
+        }
+        for (name, stats) in self.scanner.rate_limit_stats() {
+            writeln!(frame, "  🚦 {}: {} requests, throttled {} ({}ms), rejected {}",
+                name, stats.requests, stats.throttled, stats.throttled_ms, stats.rejected)?;
+        }
+        let metrics = self.scanner.metrics();
+        for (name, clock) in self.scanner.clock_stats() {
+            let latency = clock.avg_latency_ms.map_or("-".to_string(), |ms| format!("{:.0}ms", ms));
+            let offset = clock.offset_ms.map_or("-".to_string(), |ms| format!("{:+}ms", ms));
+            let success = metrics.get_exchange_health(&name).map_or("-".to_string(), |h| {
+                format!("{:.1}% (p99 {}ms)", h.success_rate * 100.0, h.p99_latency.as_millis())
+            });
+            writeln!(frame, "  ⏱ {}: latency {}, clock offset {}, success {}", name, latency, offset, success)?;
+        }
+        if !scan.rejected.is_empty() {
+            writeln!(frame, "  🚫 Rejected quotes: {}", scan.rejected.len().to_string().yellow())?;
+            for quote in scan.rejected.iter().take(5) {
+                writeln!(frame, "     {} {}: {}", quote.exchange, quote.symbol, quote.reason)?;
+            }
+        }
+        let turbulent: Vec<String> = metrics
+            .regimes()
+            .into_iter()
+            .filter(|(_, reading)| reading.regime == Regime::Turbulent)
+            .map(|(pair, reading)| format!("{} ({:.2}%/√h)", pair, reading.short_volatility))
+            .collect();
+        if !turbulent.is_empty() {
+            writeln!(frame, "  🌪 Turbulent: {}", turbulent.join(", ").yellow())?;
+        }
+        for (name, reason) in &scan.open_circuits {
+            writeln!(frame, "  {}", format!("⛔ {} excluded: {}", name, reason).red())?;
+        }
+        if !scan.depegged.is_empty() {
+            writeln!(frame, "  {}", format!("⚠ Depegged: {}", scan.depegged.join(", ")).red())?;
+        }
+
+        {
+            let alerts = self.scanner.alerts();
+            let alerts = alerts.lock().await;
+            let open: Vec<_> = alerts
+                .query(&AlertQuery::default())
+                .into_iter()
+                .filter(|alert| alert.state != AlertState::Resolved)
+                .collect();
+            let silences = alerts.silences(now);
+            if !open.is_empty() || !silences.is_empty() {
+                writeln!(frame, "\n🔔 Alerts: {} open, {} silences", open.len(), silences.len())?;
+            }
+            for alert in open.iter().take(5) {
+                let line = format!("#{} [{} {}] {}", alert.id, alert.alert_type, alert.severity, alert.message);
+                match alert.state {
+                    AlertState::Firing => writeln!(frame, "  {}", line.red())?,
+                    _ => writeln!(frame, "  ✓ {}", line)?,
+                }
+            }
+            for silence in &silences {
+                writeln!(frame, "  🔕 #{} until {}: {}", silence.id,
+                    silence.ends_at.format("%H:%M:%S UTC"), silence.comment)?;
+            }
+        }
+        write!(frame, "\n⏳ Next update in {}ms... (a: acknowledge alerts, s: silence latest for 1h, q or Ctrl+C: exit)",
+            update_interval.as_millis())?;
+
+        // В raw-режиме перевод строки не возвращает каретку
+        execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+        write!(stdout, "{}", frame.replace('\n', "\r\n"))?;
+        stdout.flush()?;
+
         Ok(())
     }
 
@@ -135,4 +254,4 @@ impl App {
         println!("✨ Thank you for using Arbitrage Monitor!");
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,123 @@
+use crate::analysis::{find_opportunity, LifecycleReport, OpportunityTracker, QualityGate};
+use crate::config::Config;
+use crate::fees::FeeModel;
+use crate::transfers::TransferModel;
+use crate::types::{ArbitrageOpportunity, MarketPrice, PricePoint};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Восстанавливает состояние рынка по потоку записанных цен и
+/// проверяет пару на арбитраж при каждом новом тике.
+pub struct Replayer {
+    latest: HashMap<String, HashMap<String, PricePoint>>,
+    fees: FeeModel,
+    transfers: TransferModel,
+    gate: QualityGate,
+    threshold: Decimal,
+}
+
+impl Replayer {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            latest: HashMap::new(),
+            fees: config.fee_model(),
+            // Сети в истории не записываются: стоимость переводов в бэктесте нулевая
+            transfers: config.transfer_model(),
+            gate: config.quality_gate(),
+            threshold: config.arbitrage.min_profit_percentage,
+        }
+    }
+
+    pub fn push(&mut self, point: &PricePoint) -> Option<ArbitrageOpportunity> {
+        let quotes = self.latest.entry(point.symbol.clone()).or_default();
+        quotes.insert(point.exchange.clone(), point.clone());
+
+        // Тот же фильтр качества, что и в живом сканировании, со временем тика вместо текущего
+        let prices: HashMap<String, MarketPrice> = quotes
+            .iter()
+            .map(|(exchange, p)| (exchange.clone(), p.to_market_price()))
+            .collect();
+        let (prices, _) = self.gate.filter(&point.symbol, prices, point.timestamp.timestamp_millis());
+
+        let mut opportunity = find_opportunity(&point.symbol, &prices, &self.fees, &self.transfers, self.threshold)?;
+        opportunity.timestamp = point.timestamp;
+        Some(opportunity)
+    }
+
+    pub fn fees(&self) -> &FeeModel {
+        &self.fees
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct PairStats {
+    pub opportunities: usize,
+    pub best_spread: Decimal,
+    pub fees: Decimal,
+    pub profit: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BacktestReport {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub price_points: usize,
+    pub opportunities: usize,
+    pub trade_amount: Decimal,
+    pub total_fees: Decimal,
+    pub total_profit: Decimal,
+    pub pairs: BTreeMap<String, PairStats>,
+    /// Сколько держались возможности: по парам и связкам бирж
+    pub persistence: LifecycleReport,
+}
+
+/// Прогоняет историю через анализатор. Каждая найденная возможность
+/// считается сделкой объёмом `trade_amount` в котируемой валюте,
+/// PnL считается за вычетом комиссий из `FeeModel`.
+pub fn run_backtest(points: &[PricePoint], config: &Config, trade_amount: Decimal) -> BacktestReport {
+    let mut replayer = Replayer::new(config);
+    let mut report = BacktestReport {
+        start: points.first().map(|p| p.timestamp),
+        end: points.last().map(|p| p.timestamp),
+        price_points: points.len(),
+        opportunities: 0,
+        trade_amount,
+        total_fees: dec!(0),
+        total_profit: dec!(0),
+        pairs: BTreeMap::new(),
+        persistence: LifecycleReport::default(),
+    };
+    let mut tracker = OpportunityTracker::new();
+
+    for point in points {
+        let mut opportunity = replayer.push(point);
+        tracker.update_pair(&point.symbol, opportunity.as_mut(), point.timestamp);
+        if let Some(opportunity) = opportunity {
+            let pnl = replayer.fees().round_trip_pnl(
+                &opportunity.pair,
+                &opportunity.buy_exchange,
+                opportunity.buy_price,
+                &opportunity.sell_exchange,
+                opportunity.sell_price,
+                trade_amount,
+            );
+            let stats = report.pairs.entry(opportunity.pair.clone()).or_default();
+            stats.opportunities += 1;
+            stats.best_spread = stats.best_spread.max(opportunity.spread);
+            stats.fees += pnl.fees;
+            stats.profit += pnl.net;
+            report.opportunities += 1;
+            report.total_fees += pnl.fees;
+            report.total_profit += pnl.net;
+        }
+    }
+
+    if let Some(end) = report.end {
+        tracker.finish(end);
+    }
+    report.persistence = tracker.report();
+    report
+}
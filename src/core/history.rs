@@ -0,0 +1,65 @@
+use crate::types::PricePoint;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Читает историю цен в формате JSON Lines. Битые строки пропускаются с записью в лог.
+pub fn read_history(path: &Path) -> std::io::Result<Vec<PricePoint>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut points = Vec::new();
+
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<PricePoint>(&line) {
+            Ok(point) => points.push(point),
+            Err(e) => log::warn!("{}:{}: skipping malformed record: {}", path.display(), line_no + 1, e),
+        }
+    }
+
+    points.sort_by_key(|p| p.timestamp);
+    Ok(points)
+}
+
+/// Дописывает цены в файл истории.
+pub struct HistoryWriter {
+    writer: BufWriter<File>,
+}
+
+impl HistoryWriter {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn append(&mut self, points: &[PricePoint]) -> std::io::Result<()> {
+        for point in points {
+            serde_json::to_writer(&mut self.writer, point)?;
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.flush()
+    }
+}
+
+pub fn write_csv<W: Write>(out: &mut W, points: &[PricePoint]) -> std::io::Result<()> {
+    writeln!(out, "timestamp,exchange,symbol,price,volume")?;
+    for p in points {
+        writeln!(
+            out,
+            "{},{},{},{},{}",
+            p.timestamp.to_rfc3339(),
+            p.exchange,
+            p.symbol,
+            p.price,
+            p.volume.map(|v| v.to_string()).unwrap_or_default()
+        )?;
+    }
+    Ok(())
+}
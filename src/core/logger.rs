@@ -3,15 +3,21 @@ use std::fs::OpenOptions;
 use std::io::Write;
 use std::sync::Mutex;
 use once_cell::sync::Lazy;
+use log::LevelFilter;
+
+const LOG_PATH: &str = "logs/arbitrage_monitor.log";
 
 static LOG_FILE: Lazy<Mutex<std::fs::File>> = Lazy::new(|| {
-    let file = OpenOptions::new()
+    Mutex::new(open_log_file().expect("Failed to open log file"))
+});
+
+fn open_log_file() -> std::io::Result<std::fs::File> {
+    std::fs::create_dir_all("logs")?;
+    OpenOptions::new()
         .create(true)
         .append(true)
-        .open("logs/arbitrage_monitor.log")
-        .expect("Failed to open log file");
-    Mutex::new(file)
-});
+        .open(LOG_PATH)
+}
 
 pub fn log(message: &str) {
     if let Ok(mut file) = LOG_FILE.lock() {
@@ -19,4 +25,18 @@ pub fn log(message: &str) {
         writeln!(file, "[{}] {}", timestamp, message)
             .expect("Failed to write to log file");
     }
-}
\ No newline at end of file
+}
+
+/// Инициализирует `log`-фасад. В режиме TUI вывод идёт в файл лога,
+/// чтобы не портить экран, иначе — в stderr.
+pub fn init(level: LevelFilter, to_file: bool) -> std::io::Result<()> {
+    let mut builder = env_logger::Builder::new();
+    builder.filter_level(level).parse_default_env();
+
+    if to_file {
+        builder.target(env_logger::Target::Pipe(Box::new(open_log_file()?)));
+    }
+
+    let _ = builder.try_init();
+    Ok(())
+}
@@ -1,3 +1,6 @@
 pub mod app;
+pub mod backtest;
+pub mod history;
 pub mod logger;
-pub mod pairs;
\ No newline at end of file
+pub mod pairs;
+pub mod scanner;
@@ -2,6 +2,8 @@ use std::collections::HashSet;
 use rust_decimal::Decimal;
 use colored::Colorize;
 use chrono::Utc;
+use crate::config::Config;
+use crate::types::ArbitrageOpportunity;
 
 #[derive(Debug, Clone)]
 pub struct PriceData {
@@ -11,24 +13,22 @@ pub struct PriceData {
     pub timestamp: chrono::DateTime<Utc>,
 }
 
-#[derive(Debug)]
-pub struct ArbitrageOpportunity {
-    pub pair: String,
-    pub buy_exchange: String,
-    pub sell_exchange: String,
-    pub buy_price: Decimal,
-    pub sell_price: Decimal,
-    pub spread: Decimal,
-    pub timestamp: chrono::DateTime<Utc>,
-}
-
 pub struct PairsManager {
     pairs: HashSet<String>,
 }
 
+impl Default for PairsManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PairsManager {
     pub fn new() -> Self {
-        let pairs = Self::generate_pairs();
+        Self::with_pairs(Config::default().pairs())
+    }
+
+    pub fn with_pairs(pairs: Vec<String>) -> Self {
         Self {
             pairs: HashSet::from_iter(pairs),
         }
@@ -46,40 +46,34 @@ impl PairsManager {
         format!("Total Trading Pairs: {}", self.pairs.len().to_string().green())
     }
 
-    pub fn generate_pairs() -> Vec<String> {
-        let quote_tokens = vec!["USDT", "USDC", "BUSD", "BTC"];
-        let base_tokens = vec![
-            "BTC", "ETH", "SOL", "BNB", "XRP", "ADA", "AVAX", "DOGE", "TRX", "TON",
-            "DOT", "MATIC", "SHIB", "UNI", "LINK", "BCH", "LTC", "ATOM", "XLM", "ICP",
-            // ... (остальные токены)
-        ];
-
-        let mut pairs = Vec::new();
-        for base in base_tokens {
-            for quote in &quote_tokens {
-                if base != *quote {
-                    pairs.push(format!("{}{}", base, quote));
-                }
-            }
-        }
-        pairs
-    }
-
     pub fn format_opportunity(&self, opp: &ArbitrageOpportunity) -> String {
-        format!(
+        let row = format!(
             "│ {:<8} │ {:<18} │ {:<18} │ {:.4} │ {:.4} │ {:.2}% │",
-            opp.pair,
+            opp.symbols(),
             opp.buy_exchange.blue(),
             opp.sell_exchange.green(),
             opp.buy_price,
             opp.sell_price,
             opp.spread
-        )
+        );
+        match &opp.transfer_warning {
+            Some(warning) => format!("{} {}", row, format!("⚠ {}", warning).red()),
+            None => row,
+        }
+    }
+
+    pub fn table_header() -> String {
+        [
+            "┌──────────┬────────────────────┬────────────────────┬──────────┬──────────┬──────────┐",
+            "│ Pair     │ Buy Exchange       │ Sell Exchange      │ Buy      │ Sell     │ Spread   │",
+            "├──────────┼────────────────────┼────────────────────┼──────────┼──────────┼──────────┤",
+        ]
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
     }
 
     pub fn print_table_header() {
-        println!("┌──────────┬────────────────────┬────────────────────┬──────────┬──────────┬──────────┐");
-        println!("│ Pair     │ Buy Exchange       │ Sell Exchange      │ Buy      │ Sell     │ Spread   │");
-        println!("├──────────┼────────────────────┼────────────────────┼──────────┼──────────┼──────────┤");
+        print!("{}", Self::table_header());
     }
 }
\ No newline at end of file
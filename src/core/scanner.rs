@@ -0,0 +1,536 @@
+use crate::alerts::{Alert, AlertManager, RuleEngine};
+use crate::analysis::{find_cross_opportunity, find_opportunity, DepegMonitor, OpportunityTracker, QuoteConverter, RejectedQuote};
+use crate::config::{CircuitBreakerConfig, Config, ConfigHandle};
+use crate::exchanges::{cex, CircuitBreaker, CircuitState, ClockStats, Exchange, ExchangeError, HttpClient, RateLimitStats};
+use crate::fees::AccountFees;
+use crate::metrics::MetricsCollector;
+use crate::monitoring::MarketMonitor;
+use crate::notifications::NotificationSystem;
+use crate::transfers::AssetNetworks;
+use crate::types::{split_symbol, ArbitrageOpportunity, MarketPrice, PricePoint};
+use futures::future::join_all;
+use rust_decimal::Decimal;
+use log::{debug, info, warn};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+/// Как часто перезапрашиваются фактические ставки комиссий аккаунта.
+const ACCOUNT_FEES_REFRESH: Duration = Duration::from_secs(3600);
+/// Смещение часов, при котором подписанные запросы рискуют выйти за `recvWindow`.
+const CLOCK_OFFSET_WARNING_MS: i64 = 1000;
+/// Через сколько пара без листинга запрашивается снова: её могли залистить,
+/// а пустой ответ биржи мог быть временным.
+const UNLISTED_RECHECK: Duration = Duration::from_secs(600);
+
+/// Результат одного прохода по всем парам.
+#[derive(Debug, Default)]
+pub struct ScanResult {
+    pub opportunities: Vec<ArbitrageOpportunity>,
+    pub prices: Vec<PricePoint>,
+    pub errors: usize,
+    /// Котировки, отброшенные фильтром качества, с причинами
+    pub rejected: Vec<RejectedQuote>,
+    /// Валюты, потерявшие привязку к опорной
+    pub depegged: Vec<String>,
+    /// Биржи, исключённые предохранителем, с причинами
+    pub open_circuits: Vec<(String, String)>,
+}
+
+pub struct Scanner {
+    exchanges: Vec<Arc<dyn Exchange>>,
+    config: ConfigHandle,
+    /// Конфигурация прошлого прохода, чтобы заметить перезагрузку
+    applied: Mutex<Arc<Config>>,
+    account_fees: RwLock<HashMap<String, AccountFees>>,
+    fees_refreshed_at: Mutex<Option<Instant>>,
+    networks: RwLock<HashMap<String, Vec<AssetNetworks>>>,
+    networks_refreshed_at: Mutex<Option<Instant>>,
+    clocks_synced_at: Mutex<Option<Instant>>,
+    depeg: Mutex<DepegMonitor>,
+    alerts: Arc<Mutex<AlertManager>>,
+    /// Правила оповещений; отсчёт `for_secs` переживает проходы
+    rules: Arc<Mutex<RuleEngine>>,
+    metrics: Arc<MetricsCollector>,
+    tracker: Arc<Mutex<OpportunityTracker>>,
+    /// Предохранители по биржам; пока предохранитель не закрыт, биржа не опрашивается
+    breakers: Mutex<HashMap<String, CircuitBreaker>>,
+    /// Пары, которых нет на бирже: `(биржа, символ)` → когда это выяснилось.
+    /// Не запрашиваются `UNLISTED_RECHECK` и до перезагрузки конфигурации
+    unlisted: RwLock<HashMap<(String, String), Instant>>,
+}
+
+impl Scanner {
+    /// Набор бирж фиксируется при создании; пары, пороги и комиссии
+    /// берутся из актуальной конфигурации на каждом проходе.
+    pub fn new(config: ConfigHandle) -> Self {
+        let snapshot = config.borrow().clone();
+        let http = snapshot.http_client().unwrap_or_else(|e| {
+            warn!("Failed to configure HTTP client, using defaults: {}", e);
+            HttpClient::default()
+        });
+
+        let mut exchanges = Vec::new();
+        for (name, exchange_config) in snapshot.enabled_exchanges() {
+            match cex::create(name, exchange_config, &http) {
+                Some(exchange) => exchanges.push(exchange),
+                None => warn!("Exchange {} is not available, skipping", name),
+            }
+        }
+
+        let notifier = NotificationSystem::from_config(&snapshot, &http);
+        if !notifier.is_empty() {
+            info!("Notification channels: {}", notifier.channel_names().join(", "));
+        }
+
+        Self {
+            exchanges,
+            config,
+            applied: Mutex::new(snapshot.clone()),
+            account_fees: RwLock::new(HashMap::new()),
+            fees_refreshed_at: Mutex::new(None),
+            networks: RwLock::new(HashMap::new()),
+            networks_refreshed_at: Mutex::new(None),
+            clocks_synced_at: Mutex::new(None),
+            depeg: Mutex::new(DepegMonitor::new()),
+            alerts: Arc::new(Mutex::new(AlertManager::new(&snapshot.alerts).with_notifier(notifier))),
+            rules: Arc::new(Mutex::new(RuleEngine::new(&snapshot.alerts.rules))),
+            metrics: Arc::new(MetricsCollector::new()),
+            tracker: Arc::new(Mutex::new(OpportunityTracker::new())),
+            breakers: Mutex::new(HashMap::new()),
+            unlisted: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Оповещения, поднятые при сканировании.
+    pub fn alerts(&self) -> Arc<Mutex<AlertManager>> {
+        self.alerts.clone()
+    }
+
+    /// История цен, волатильность и состояние бирж по запросам цен.
+    pub fn metrics(&self) -> Arc<MetricsCollector> {
+        self.metrics.clone()
+    }
+
+    /// Жизненные циклы возможностей между проходами.
+    pub fn tracker(&self) -> Arc<Mutex<OpportunityTracker>> {
+        self.tracker.clone()
+    }
+
+    /// Запрашивает ставки комиссий аккаунта у бирж с `fees.fetch_account_rates`.
+    pub async fn refresh_account_fees(&self) {
+        let config = self.config.borrow().clone();
+
+        for exchange in &self.exchanges {
+            let name = exchange.get_name();
+            let enabled = config
+                .exchanges
+                .iter()
+                .any(|(key, c)| key.eq_ignore_ascii_case(&name) && c.fees.fetch_account_rates);
+            if !enabled {
+                continue;
+            }
+
+            match exchange.get_trading_fees().await {
+                Ok(fees) => {
+                    info!("Fetched {} account fee rates ({} symbols)", name, fees.symbols.len());
+                    self.account_fees.write().await.insert(name.to_lowercase(), fees);
+                }
+                Err(e) => warn!("Failed to fetch {} fee rates: {}", name, e),
+            }
+        }
+
+        *self.fees_refreshed_at.lock().await = Some(Instant::now());
+    }
+
+    /// Запрашивает сети вывода и ввода активов на всех биржах параллельно.
+    pub async fn refresh_asset_networks(&self) {
+        let requests = self.exchanges.iter().map(|exchange| async move {
+            (exchange.get_name(), exchange.get_asset_networks().await)
+        });
+
+        for (name, result) in join_all(requests).await {
+            match result {
+                Ok(assets) => {
+                    info!("Fetched {} transfer networks ({} assets)", name, assets.len());
+                    self.networks.write().await.insert(name.to_lowercase(), assets);
+                }
+                Err(e) => warn!("Failed to fetch {} transfer networks: {}", name, e),
+            }
+        }
+
+        *self.networks_refreshed_at.lock().await = Some(Instant::now());
+    }
+
+    /// Оценивает смещение часов всех бирж параллельно.
+    pub async fn sync_clocks(&self) {
+        let requests = self.exchanges.iter().map(|exchange| async move {
+            (exchange.get_name(), exchange.sync_clock().await)
+        });
+
+        for (name, result) in join_all(requests).await {
+            match result {
+                Ok(stats) => {
+                    let offset = stats.offset_ms.unwrap_or_default();
+                    let rtt = stats.offset_rtt_ms.unwrap_or_default();
+                    if offset.abs() > CLOCK_OFFSET_WARNING_MS {
+                        warn!("{} clock is off by {}ms (±{}ms), check local time sync", name, offset, rtt / 2);
+                    } else {
+                        debug!("{} clock offset {}ms (±{}ms)", name, offset, rtt / 2);
+                    }
+                }
+                Err(e) => warn!("Failed to sync {} clock: {}", name, e),
+            }
+        }
+
+        *self.clocks_synced_at.lock().await = Some(Instant::now());
+    }
+
+    /// Задержка и смещение часов по биржам.
+    pub fn clock_stats(&self) -> Vec<(String, ClockStats)> {
+        self.exchanges
+            .iter()
+            .filter_map(|exchange| Some((exchange.get_name(), exchange.clock_stats()?)))
+            .collect()
+    }
+
+    /// Счётчики ограничителей запросов по биржам.
+    pub fn rate_limit_stats(&self) -> Vec<(String, RateLimitStats)> {
+        self.exchanges
+            .iter()
+            .filter_map(|exchange| Some((exchange.get_name(), exchange.rate_limit_stats()?)))
+            .collect()
+    }
+
+    /// Обновляет предохранители перед проходом: по истечении паузы пробует биржу одним
+    /// запросом цены `probe_symbol` и по итогу возвращает её в анализ или продлевает паузу.
+    async fn probe_circuits(&self, config: &CircuitBreakerConfig, probe_symbol: &str) {
+        let mut probes = Vec::new();
+        {
+            let mut breakers = self.breakers.lock().await;
+            for exchange in &self.exchanges {
+                let breaker = breakers
+                    .entry(exchange.get_name())
+                    .or_insert_with(|| CircuitBreaker::new(config));
+                if breaker.poll() == CircuitState::HalfOpen {
+                    probes.push(exchange.clone());
+                }
+            }
+        }
+
+        let requests = probes.iter().map(|exchange| async move {
+            let started = Instant::now();
+            let result = exchange.get_price(probe_symbol).await;
+            (exchange.get_name(), started.elapsed(), result)
+        });
+        for (name, latency, result) in join_all(requests).await {
+            self.metrics.record_request(&name, latency, result.as_ref().err());
+            // Пары может не быть на бирже — но биржа ответила
+            let (success, error) = match result {
+                Ok(price) => (price.price > Decimal::ZERO, None),
+                Err(ExchangeError::SymbolNotFound { .. }) => (true, None),
+                Err(e) => (false, Some(e.to_string())),
+            };
+            let transition = match self.breakers.lock().await.get_mut(&name) {
+                Some(breaker) => breaker.probe_result(success, error, config),
+                None => continue,
+            };
+            self.raise(transition.alert(&name)).await;
+        }
+    }
+
+    async fn raise(&self, alert: Alert) {
+        let alert_type = alert.alert_type;
+        let delivery = self.alerts.lock().await.send_alert(alert).await;
+        self.metrics.record_alert(alert_type, delivery);
+    }
+
+    pub fn exchange_count(&self) -> usize {
+        self.exchanges.len()
+    }
+
+    pub fn pairs(&self) -> Vec<String> {
+        self.config.borrow().pairs()
+    }
+
+    /// Запрашивает цену пары на всех биржах параллельно. Временные ошибки
+    /// учитываются в счётчике, пары без листинга запоминаются и на время пропускаются.
+    /// Биржи с незакрытым предохранителем не опрашиваются.
+    pub async fn fetch_prices(&self, symbol: &str) -> (HashMap<String, MarketPrice>, usize) {
+        let unlisted = self.unlisted.read().await.clone();
+        let excluded: HashSet<String> = self
+            .breakers
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, breaker)| breaker.state() != CircuitState::Closed)
+            .map(|(name, _)| name.clone())
+            .collect();
+        let requests = self
+            .exchanges
+            .iter()
+            .filter(|exchange| !excluded.contains(&exchange.get_name()))
+            .filter(|exchange| {
+                unlisted
+                    .get(&(exchange.get_name(), symbol.to_string()))
+                    .is_none_or(|at| at.elapsed() >= UNLISTED_RECHECK)
+            })
+            .map(|exchange| async move {
+                let started = Instant::now();
+                let result = exchange.get_price(symbol).await;
+                (exchange.get_name(), started.elapsed(), result)
+            });
+
+        let mut prices = HashMap::new();
+        let mut errors = 0;
+        for (name, latency, result) in join_all(requests).await {
+            self.metrics.record_request(&name, latency, result.as_ref().err());
+            let failed = matches!(&result, Err(e) if !matches!(e, ExchangeError::SymbolNotFound { .. }));
+            if let Some(breaker) = self.breakers.lock().await.get_mut(&name) {
+                breaker.record(!failed);
+            }
+
+            match result {
+                Ok(price) => {
+                    prices.insert(name, price);
+                }
+                Err(ExchangeError::SymbolNotFound { .. }) => {
+                    info!("{} is not listed on {}, skipping it for {}s", symbol, name, UNLISTED_RECHECK.as_secs());
+                    self.unlisted.write().await.insert((name, symbol.to_string()), Instant::now());
+                }
+                Err(e) if e.is_retryable() => {
+                    debug!("{} {}: {}", name, symbol, e);
+                    errors += 1;
+                }
+                Err(e) => {
+                    warn!("{} {}: {}", name, symbol, e);
+                    errors += 1;
+                }
+            }
+        }
+        (prices, errors)
+    }
+
+    pub async fn scan(&self) -> ScanResult {
+        let stale = self
+            .fees_refreshed_at
+            .lock()
+            .await
+            .is_none_or(|at| at.elapsed() > ACCOUNT_FEES_REFRESH);
+        if stale {
+            self.refresh_account_fees().await;
+        }
+
+        let config = self.config.borrow().clone();
+        {
+            let mut applied = self.applied.lock().await;
+            if !Arc::ptr_eq(&applied, &config) {
+                // Пары или адреса бирж могли измениться: листинг проверяется заново
+                self.unlisted.write().await.clear();
+                *applied = config.clone();
+            }
+        }
+        if config.monitoring.clock_sync_interval_secs > 0 {
+            let interval = Duration::from_secs(config.monitoring.clock_sync_interval_secs);
+            let stale = self
+                .clocks_synced_at
+                .lock()
+                .await
+                .is_none_or(|at| at.elapsed() > interval);
+            if stale {
+                self.sync_clocks().await;
+            }
+        }
+        if config.transfers.enabled {
+            let interval = Duration::from_secs(config.transfers.refresh_interval_secs);
+            let stale = self
+                .networks_refreshed_at
+                .lock()
+                .await
+                .is_none_or(|at| at.elapsed() > interval);
+            if stale {
+                self.refresh_asset_networks().await;
+            }
+        }
+
+        let pairs = config.pairs();
+        if !config.circuit_breaker.enabled {
+            self.breakers.lock().await.clear();
+        } else if let Some(probe_symbol) = pairs.first() {
+            self.probe_circuits(&config.circuit_breaker, probe_symbol).await;
+        }
+
+        let fees = config
+            .fee_model()
+            .with_account_fees(self.account_fees.read().await.clone());
+        let min_profit = config.arbitrage.min_profit_percentage;
+
+        // Для кросс-котировок и стоимости переводов дополнительно нужны курсы котируемых
+        // валют к опорной, для контроля привязки — курсы стейблкоинов
+        let quotes = &config.monitoring.supported_quote_tokens;
+        let reference = &config.arbitrage.reference_quote;
+        let mut symbols = pairs.clone();
+        if config.arbitrage.cross_quote || config.transfers.enabled {
+            symbols.extend(QuoteConverter::conversion_symbols(reference, quotes));
+        }
+        if config.depeg.enabled {
+            symbols.extend(DepegMonitor::symbols(reference, &config.depeg.stablecoins));
+        }
+        let mut seen = HashSet::new();
+        symbols.retain(|symbol| seen.insert(symbol.clone()));
+
+        let fetches = symbols.iter().map(|symbol| async move {
+            let (prices, errors) = self.fetch_prices(symbol).await;
+            (symbol.clone(), prices, errors)
+        });
+
+        let gate = config.quality_gate();
+        {
+            let mut alerts = self.alerts.lock().await;
+            alerts.configure(&config.alerts);
+            alerts.resolve_stale(Utc::now());
+        }
+        self.rules.lock().await.configure(&config.alerts.rules);
+        let monitor = MarketMonitor::new(self.metrics.clone(), self.alerts.clone(), self.rules.clone(), &config.volatility);
+        let mut result = ScanResult::default();
+        let mut prices_by_symbol = HashMap::new();
+        for (symbol, prices, errors) in join_all(fetches).await {
+            result.errors += errors;
+            let (prices, rejected) = gate.filter(&symbol, prices, Utc::now().timestamp_millis());
+            for quote in &rejected {
+                debug!("Rejected {} {} @ {}: {}", quote.exchange, quote.symbol, quote.price, quote.reason);
+            }
+            result.rejected.extend(rejected);
+            for (exchange, price) in &prices {
+                self.metrics.record_good_data(exchange);
+                if let Some(breaker) = self.breakers.lock().await.get_mut(exchange) {
+                    breaker.record_good_data();
+                }
+                let point = PricePoint::from_market_price(exchange, &symbol, price);
+                monitor.process_price(&point).await;
+                result.prices.push(point);
+            }
+            monitor.analyze_spreads(&symbol, &prices);
+            monitor.check_regime(&symbol).await;
+            prices_by_symbol.insert(symbol, prices);
+        }
+        for exchange in &self.exchanges {
+            monitor.check_exchange_health(&exchange.get_name());
+        }
+        monitor.evaluate_rules().await;
+
+        // Предохранители оцениваются после прохода: исключённая биржа не участвует
+        // уже в поиске возможностей этого прохода
+        let mut transitions = Vec::new();
+        let mut circuits = Vec::new();
+        {
+            let mut breakers = self.breakers.lock().await;
+            for (name, breaker) in breakers.iter_mut() {
+                if let Some(transition) = breaker.evaluate(&config.circuit_breaker) {
+                    transitions.push((name.clone(), transition));
+                }
+                if let (CircuitState::Open | CircuitState::HalfOpen, Some(reason)) = (breaker.state(), breaker.reason()) {
+                    result.open_circuits.push((name.clone(), reason.to_string()));
+                }
+                circuits.push((name.clone(), breaker.state(), breaker.reason().map(String::from)));
+            }
+        }
+        for (name, transition) in transitions {
+            prices_by_symbol.values_mut().for_each(|prices| {
+                prices.remove(&name);
+            });
+            self.raise(transition.alert(&name)).await;
+        }
+        result.open_circuits.sort();
+        self.metrics.set_circuit_states(circuits);
+
+        let paused = if config.depeg.enabled {
+            let mut depeg = self.depeg.lock().await;
+            for alert in depeg.update(reference, &config.depeg.stablecoins, config.depeg.threshold, &prices_by_symbol) {
+                self.raise(alert).await;
+            }
+            result.depegged = depeg.depegged().iter().cloned().collect();
+            if config.depeg.auto_pause {
+                depeg.depegged().clone()
+            } else {
+                Default::default()
+            }
+        } else {
+            Default::default()
+        };
+        // Пары с отвязавшейся валютой пропускаются
+        let tradable = |symbol: &str| match split_symbol(symbol, quotes) {
+            Some((base, quote)) => !paused.contains(base) && !paused.contains(quote),
+            None => true,
+        };
+
+        let converter = QuoteConverter::from_prices(
+            reference,
+            config.arbitrage.conversion_slippage,
+            quotes,
+            &prices_by_symbol,
+        );
+        // Объём сделки задан в опорной валюте, комиссии переводов — в котируемой валюте пары
+        let transfers = config
+            .transfer_model()
+            .with_networks(self.networks.read().await.clone())
+            .with_converter(converter.clone());
+
+        for pair in pairs.iter().filter(|pair| tradable(pair)) {
+            let Some(prices) = prices_by_symbol.get(pair) else {
+                continue;
+            };
+            if let Some(opportunity) = find_opportunity(pair, prices, &fees, &transfers, min_profit) {
+                result.opportunities.push(opportunity);
+            }
+        }
+
+        if config.arbitrage.cross_quote {
+            for base in &config.monitoring.base_tokens {
+                let listings: Vec<(String, &str)> = quotes
+                    .iter()
+                    .filter(|quote| *quote != base)
+                    .map(|quote| (format!("{}{}", base, quote), quote.as_str()))
+                    .filter(|(symbol, _)| tradable(symbol))
+                    .collect();
+                let markets: Vec<_> = listings
+                    .iter()
+                    .filter_map(|(symbol, quote)| {
+                        prices_by_symbol.get(symbol).map(|prices| (symbol.as_str(), *quote, prices))
+                    })
+                    .collect();
+                if markets.len() < 2 {
+                    continue;
+                }
+                if let Some(opportunity) = find_cross_opportunity(&markets, &converter, &fees, &transfers, min_profit) {
+                    result.opportunities.push(opportunity);
+                }
+            }
+        }
+
+        let mut tracker = self.tracker.lock().await;
+        let closed = tracker.update(&mut result.opportunities, Utc::now());
+        for lifecycle in &closed {
+            debug!("{} {} → {} closed after {:.1}s, peak {:.4}%",
+                lifecycle.pair, lifecycle.buy_exchange, lifecycle.sell_exchange,
+                lifecycle.duration_secs(), lifecycle.peak_spread);
+            self.metrics.record_opportunity_closed(lifecycle);
+        }
+        // Счётчик — по открытиям; возможности, которые держатся с прошлых проходов, видны в датчике
+        self.metrics.set_active_opportunities(result.opportunities.len());
+        for opportunity in result.opportunities.iter().filter(|o| tracker.is_opened(o)) {
+            self.metrics.record_opportunity(opportunity);
+        }
+        drop(tracker);
+        // Возможность на пару (или связку котировок) не больше одной за проход
+        self.metrics
+            .set_best_spreads(result.opportunities.iter().map(|o| (o.symbols(), o.spread)).collect());
+        self.metrics.set_exchange_stats(self.rate_limit_stats(), self.clock_stats());
+        result
+            .opportunities
+            .sort_by_key(|o| std::cmp::Reverse(o.spread));
+        result
+    }
+}
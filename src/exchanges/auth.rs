@@ -0,0 +1,40 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Ключи API биржи. Пустые строки означают, что ключ не задан.
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    pub api_key: String,
+    pub api_secret: String,
+    pub passphrase: String,
+}
+
+impl Credentials {
+    pub fn new(api_key: &str, api_secret: &str, passphrase: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+            passphrase: passphrase.to_string(),
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        !self.api_key.is_empty() && !self.api_secret.is_empty()
+    }
+
+    pub fn sign_hex(&self, message: &str) -> String {
+        hex::encode(hmac_sha256(&self.api_secret, message))
+    }
+
+    pub fn sign_base64(&self, message: &str) -> String {
+        base64::engine::general_purpose::STANDARD.encode(hmac_sha256(&self.api_secret, message))
+    }
+}
+
+fn hmac_sha256(secret: &str, message: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
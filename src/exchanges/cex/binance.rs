@@ -1,22 +1,178 @@
-use crate::exchanges::{Exchange, ExchangeError, Result, OrderBook};
+use crate::exchanges::rate_limit::{LimitHeaders, RateLimitStats, RateLimiter, RateLimits};
+use crate::exchanges::{parse_book, parse_decimal, retry_after, ClockStats, Credentials, Exchange, ExchangeClock, ExchangeError, HttpClient, Result, OrderBook};
+use crate::fees::{AccountFees, FeeRates};
+use crate::transfers::{AssetNetworks, NetworkInfo};
 use crate::types::MarketPrice;
 use async_trait::async_trait;
 use chrono::Utc;
-use rust_decimal::Decimal;
 use serde::Deserialize;
 
+/// Тело ответа с ошибкой: `{"code": -1121, "msg": "Invalid symbol."}`
 #[derive(Debug, Deserialize)]
-struct BinancePrice {
+struct BinanceError {
+    code: i64,
+    msg: String,
+}
+
+/// Статистика за 24 часа; в отличие от `ticker/price` содержит время обновления
+/// и вершину стакана.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceTicker {
+    last_price: String,
+    volume: String,
+    close_time: i64,
+    #[serde(default)]
+    bid_price: String,
+    #[serde(default)]
+    bid_qty: String,
+    #[serde(default)]
+    ask_price: String,
+    #[serde(default)]
+    ask_qty: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceServerTime {
+    server_time: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceTradeFee {
     symbol: String,
-    price: String,
+    maker_commission: String,
+    taker_commission: String,
 }
 
-pub struct Binance;
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceCoin {
+    coin: String,
+    network_list: Vec<BinanceNetwork>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BinanceNetwork {
+    network: String,
+    withdraw_fee: String,
+    withdraw_min: String,
+    deposit_enable: bool,
+    withdraw_enable: bool,
+}
+
+pub struct Binance {
+    credentials: Credentials,
+    limiter: RateLimiter,
+    clock: ExchangeClock,
+    http: HttpClient,
+    base_url: String,
+}
+
+impl Default for Binance {
+    fn default() -> Self {
+        Self::with_credentials(Credentials::default())
+    }
+}
 
 impl Binance {
+    /// Лимит REQUEST_WEIGHT — 6000 в минуту на IP, держим запас.
+    pub const RATE_LIMITS: RateLimits = RateLimits {
+        burst: 600.0,
+        weight_per_second: 80.0,
+        headers: LimitHeaders::UsedWeight { header: "x-mbx-used-weight-1m", limit: 6000 },
+    };
+
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.binance.com";
+
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_credentials(credentials: Credentials) -> Self {
+        Self {
+            credentials,
+            limiter: RateLimiter::new("Binance", Self::RATE_LIMITS),
+            clock: ExchangeClock::new(),
+            http: HttpClient::default(),
+            base_url: Self::DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    pub fn with_rate_limits(mut self, limits: RateLimits) -> Self {
+        self.limiter = RateLimiter::new("Binance", limits);
+        self
+    }
+
+    pub fn with_http(mut self, http: HttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Адрес API без завершающего `/`, например мок-сервер в тестах.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// GET-запрос к приватному SAPI с подписью HMAC-SHA256.
+    async fn signed_get(&self, path: &str, weight: u32) -> Result<reqwest::Response> {
+        if !self.credentials.is_set() {
+            return Err(ExchangeError::not_configured("Binance", "API key"));
+        }
+
+        let query = format!("timestamp={}", self.clock.server_now_ms());
+        let url = format!(
+            "{}{}?{}&signature={}",
+            self.base_url,
+            path,
+            query,
+            self.credentials.sign_hex(&query)
+        );
+        let request = self.http.get(&url).header("X-MBX-APIKEY", &self.credentials.api_key);
+        let response = self.http.send(request, &self.limiter, &self.clock, weight).await?;
+
+        check(response, None).await
+    }
+
+    async fn server_time(&self) -> Result<i64> {
+        let url = format!("{}/api/v3/time", self.base_url);
+        let response = self.http.send(self.http.get(&url), &self.limiter, &self.clock, 1).await?;
+        let time: BinanceServerTime = check(response, None).await?.json().await?;
+        Ok(time.server_time)
+    }
+}
+
+/// Пропускает успешный ответ, иначе разбирает код ошибки Binance.
+async fn check(response: reqwest::Response, symbol: Option<&str>) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
     }
+
+    let retry = retry_after(response.headers());
+    let body = response.text().await.unwrap_or_default();
+    let Ok(error) = serde_json::from_str::<BinanceError>(&body) else {
+        return Err(ExchangeError::from_status("Binance", status, retry, &body));
+    };
+
+    let exchange = "Binance".to_string();
+    let code = error.code.to_string();
+    Err(match error.code {
+        -1003 => ExchangeError::RateLimited { exchange, code, retry_after: retry },
+        -1121 => ExchangeError::SymbolNotFound {
+            exchange,
+            symbol: symbol.unwrap_or_default().to_string(),
+        },
+        -1022 | -2008 | -2014 | -2015 => ExchangeError::Auth { exchange, code, message: error.msg },
+        -1001 | -1016 => ExchangeError::Maintenance { exchange, message: error.msg },
+        _ if status.as_u16() == 429 || status.as_u16() == 418 => {
+            ExchangeError::RateLimited { exchange, code, retry_after: retry }
+        }
+        _ => ExchangeError::Api { exchange, code, message: error.msg },
+    })
 }
 
 #[async_trait]
@@ -26,19 +182,29 @@ impl Exchange for Binance {
     }
 
     async fn get_price(&self, symbol: &str) -> Result<MarketPrice> {
-        let url = format!("https://api.binance.com/api/v3/ticker/price?symbol={}", symbol);
-        let response = reqwest::get(&url).await?;
-        
-        if response.status().is_success() {
-            let price: BinancePrice = response.json().await?;
-            Ok(MarketPrice::new(
-                price.price.parse::<Decimal>().map_err(|e| ExchangeError::Parse(e.to_string()))?,
-                None,
-                Utc::now().timestamp()
-            ))
-        } else {
-            Err(ExchangeError::Exchange(format!("HTTP {}", response.status())))
-        }
+        let url = format!("{}/api/v3/ticker/24hr?symbol={}", self.base_url, symbol);
+        let response = self.http.send(self.http.get(&url), &self.limiter, &self.clock, 2).await?;
+        let response = check(response, Some(symbol)).await?;
+
+        let ticker: BinanceTicker = response.json().await?;
+        Ok(MarketPrice::new(
+            parse_decimal(&ticker.last_price)?,
+            ticker.volume.parse().ok(),
+            self.clock.to_local_ms(Some(ticker.close_time)),
+        )
+        .with_book(parse_book(&ticker.bid_price, &ticker.bid_qty, &ticker.ask_price, &ticker.ask_qty)))
+    }
+
+    fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        Some(self.limiter.stats())
+    }
+
+    fn clock_stats(&self) -> Option<ClockStats> {
+        Some(self.clock.stats())
+    }
+
+    async fn sync_clock(&self) -> Result<ClockStats> {
+        self.clock.sync(|| self.server_time()).await
     }
 
     async fn get_orderbook(&self, _symbol: &str) -> Result<OrderBook> {
@@ -48,4 +214,101 @@ impl Exchange for Binance {
             timestamp: Utc::now().timestamp(),
         })
     }
+
+    async fn get_trading_fees(&self) -> Result<AccountFees> {
+        let response = self.signed_get("/sapi/v1/asset/tradeFee", 1).await?;
+
+        let mut fees = AccountFees::default();
+        for entry in response.json::<Vec<BinanceTradeFee>>().await? {
+            let rates = FeeRates {
+                maker: parse_decimal(&entry.maker_commission)?,
+                taker: parse_decimal(&entry.taker_commission)?,
+            };
+            fees.symbols.insert(entry.symbol, rates);
+        }
+        Ok(fees)
+    }
+
+    async fn get_asset_networks(&self) -> Result<Vec<AssetNetworks>> {
+        let response = self.signed_get("/sapi/v1/capital/config/getall", 10).await?;
+
+        let mut assets = Vec::new();
+        for coin in response.json::<Vec<BinanceCoin>>().await? {
+            let mut networks = Vec::new();
+            for network in coin.network_list {
+                networks.push(NetworkInfo {
+                    network: network.network,
+                    withdraw_fee: parse_decimal(&network.withdraw_fee)?,
+                    withdraw_min: parse_decimal(&network.withdraw_min)?,
+                    deposit_enabled: network.deposit_enable,
+                    withdraw_enabled: network.withdraw_enable,
+                });
+            }
+            assets.push(AssetNetworks { asset: coin.coin, networks });
+        }
+        Ok(assets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HttpConfig;
+    use crate::testing::{MockServer, Reply};
+    use rust_decimal_macros::dec;
+    use std::time::Duration;
+
+    const TICKER: &str = r#"{"lastPrice":"65000.10","volume":"1234.5","closeTime":1700000000000,
+        "bidPrice":"65000.00","bidQty":"1.5","askPrice":"65000.20","askQty":"2"}"#;
+
+    fn binance(server: &MockServer, timeout_ms: u64) -> Binance {
+        let config = HttpConfig {
+            timeout_ms: Some(timeout_ms),
+            max_retries: 2,
+            retry_backoff_ms: 1,
+            ..HttpConfig::default()
+        };
+        Binance::new()
+            .with_http(HttpClient::new(&config, Duration::from_secs(1)).unwrap())
+            .with_base_url(&server.url)
+    }
+
+    #[tokio::test]
+    async fn get_retries_server_errors() {
+        let server = MockServer::start(vec![Reply::Status(502, "bad gateway"), Reply::Status(200, TICKER)]).await;
+
+        let price = binance(&server, 1000).get_price("BTCUSDT").await.unwrap();
+
+        assert_eq!(price.price, dec!(65000.10));
+        assert_eq!(price.book.unwrap().ask, dec!(65000.20));
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|r| r.path == "/api/v3/ticker/24hr?symbol=BTCUSDT"));
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let server = MockServer::start(vec![Reply::Status(400, r#"{"code":-1121,"msg":"Invalid symbol."}"#)]).await;
+
+        let error = binance(&server, 1000).get_price("NOPEUSDT").await.unwrap_err();
+
+        assert!(
+            matches!(&error, ExchangeError::SymbolNotFound { symbol, .. } if symbol == "NOPEUSDT"),
+            "{:?}",
+            error
+        );
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn timeout_is_a_retryable_network_error() {
+        let server = MockServer::start(vec![Reply::Hang]).await;
+
+        let error = binance(&server, 100).get_price("BTCUSDT").await.unwrap_err();
+
+        assert!(matches!(&error, ExchangeError::Network(e) if e.is_timeout()), "{:?}", error);
+        assert!(error.is_retryable());
+        // Первая попытка и два повтора
+        assert_eq!(server.requests().len(), 3);
+    }
 }
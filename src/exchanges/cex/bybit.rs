@@ -1,37 +1,216 @@
 use async_trait::async_trait;
 use serde::Deserialize;
-use crate::exchanges::{Exchange, Result, OrderBook};
+use crate::exchanges::rate_limit::{LimitHeaders, RateLimitStats, RateLimiter, RateLimits};
+use crate::exchanges::{parse_book, parse_decimal, retry_after, ClockStats, Credentials, Exchange, ExchangeClock, ExchangeError, HttpClient, Result, OrderBook};
+use crate::fees::{AccountFees, FeeRates};
+use crate::transfers::{AssetNetworks, NetworkInfo};
 use crate::types::MarketPrice;
 use chrono::Utc;
-use log::{error, warn};
 
-pub struct Bybit;
+const RECV_WINDOW: &str = "5000";
+
+pub struct Bybit {
+    credentials: Credentials,
+    limiter: RateLimiter,
+    clock: ExchangeClock,
+    http: HttpClient,
+    base_url: String,
+}
+
+impl Default for Bybit {
+    fn default() -> Self {
+        Self::with_credentials(Credentials::default())
+    }
+}
 
 #[derive(Debug, Deserialize)]
-struct BybitResponse<T> {
-    retCode: i32,
-    retMsg: String,
-    result: T,
+#[serde(rename_all = "camelCase")]
+struct BybitResponse {
+    ret_code: i32,
+    ret_msg: String,
+    /// При ошибке приходит `{}`, поэтому разбирается только после проверки `retCode`
+    #[serde(default)]
+    result: serde_json::Value,
+    /// Время сервера при формировании ответа, мс
+    time: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct BybitTicker {
-    symbol: String,
-    lastPrice: String,
-    volume24h: String,
-    #[serde(rename = "turnover24h")]
-    turnover: String,
+    last_price: String,
+    #[serde(rename = "volume24h")]
+    volume_24h: String,
+    #[serde(default, rename = "bid1Price")]
+    bid_price: String,
+    #[serde(default, rename = "bid1Size")]
+    bid_size: String,
+    #[serde(default, rename = "ask1Price")]
+    ask_price: String,
+    #[serde(default, rename = "ask1Size")]
+    ask_size: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct BybitResult {
-    category: String,
     list: Vec<BybitTicker>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitFeeRate {
+    symbol: String,
+    maker_fee_rate: String,
+    taker_fee_rate: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BybitFeeResult {
+    list: Vec<BybitFeeRate>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BybitCoinResult {
+    rows: Vec<BybitCoin>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BybitCoin {
+    coin: String,
+    #[serde(default)]
+    chains: Vec<BybitChain>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BybitChain {
+    chain: String,
+    withdraw_fee: String,
+    withdraw_min: String,
+    /// "1" — ввод открыт
+    chain_deposit: String,
+    /// "1" — вывод открыт
+    chain_withdraw: String,
+}
+
 impl Bybit {
+    /// 600 запросов за 5 секунд на IP; остаток приходит в `X-Bapi-Limit-Status`.
+    pub const RATE_LIMITS: RateLimits = RateLimits {
+        burst: 50.0,
+        weight_per_second: 20.0,
+        headers: LimitHeaders::Remaining {
+            header: "x-bapi-limit-status",
+            reset_header: "x-bapi-limit-reset-timestamp",
+            reset_is_absolute: true,
+        },
+    };
+
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.bybit.com";
+
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_credentials(credentials: Credentials) -> Self {
+        Self {
+            credentials,
+            limiter: RateLimiter::new("Bybit", Self::RATE_LIMITS),
+            clock: ExchangeClock::new(),
+            http: HttpClient::default(),
+            base_url: Self::DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    pub fn with_rate_limits(mut self, limits: RateLimits) -> Self {
+        self.limiter = RateLimiter::new("Bybit", limits);
+        self
+    }
+
+    pub fn with_http(mut self, http: HttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Адрес API без завершающего `/`, например мок-сервер в тестах.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// GET-запрос к приватному API v5 с заголовками X-BAPI-*.
+    async fn signed_get<T: serde::de::DeserializeOwned>(&self, path: &str, query: &str) -> Result<T> {
+        if !self.credentials.is_set() {
+            return Err(ExchangeError::not_configured("Bybit", "API key"));
+        }
+
+        let timestamp = self.clock.server_now_ms().to_string();
+        let payload = format!("{}{}{}{}", timestamp, self.credentials.api_key, RECV_WINDOW, query);
+        let request = self
+            .http
+            .get(&format!("{}{}?{}", self.base_url, path, query))
+            .header("X-BAPI-API-KEY", &self.credentials.api_key)
+            .header("X-BAPI-TIMESTAMP", &timestamp)
+            .header("X-BAPI-RECV-WINDOW", RECV_WINDOW)
+            .header("X-BAPI-SIGN", self.credentials.sign_hex(&payload));
+        let response = self.http.send(request, &self.limiter, &self.clock, 1).await?;
+
+        parse_response(response, None).await
+    }
+
+    async fn server_time(&self) -> Result<i64> {
+        let url = format!("{}/v5/market/time", self.base_url);
+        let response = self.http.send(self.http.get(&url), &self.limiter, &self.clock, 1).await?;
+        let (_, time) = parse_envelope::<serde_json::Value>(response, None).await?;
+        time.ok_or_else(|| ExchangeError::Parse("Bybit server time is missing".to_string()))
+    }
+}
+
+/// Разбирает ответ v5: HTTP-ошибки и ненулевой `retCode` превращаются в `ExchangeError`.
+async fn parse_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    symbol: Option<&str>,
+) -> Result<T> {
+    parse_envelope(response, symbol).await.map(|(result, _)| result)
+}
+
+/// Как `parse_response`, но вместе со временем сервера из ответа.
+async fn parse_envelope<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    symbol: Option<&str>,
+) -> Result<(T, Option<i64>)> {
+    let status = response.status();
+    if !status.is_success() {
+        let retry = retry_after(response.headers());
+        let body = response.text().await.unwrap_or_default();
+        // 403 у Bybit означает превышение лимита запросов с IP
+        if status.as_u16() == 403 {
+            return Err(ExchangeError::RateLimited {
+                exchange: "Bybit".to_string(),
+                code: "HTTP 403".to_string(),
+                retry_after: retry,
+            });
+        }
+        return Err(ExchangeError::from_status("Bybit", status, retry, &body));
+    }
+
+    let response = response.json::<BybitResponse>().await?;
+    let exchange = "Bybit".to_string();
+    let code = response.ret_code.to_string();
+    let message = response.ret_msg;
+    match response.ret_code {
+        0 => serde_json::from_value(response.result)
+            .map(|result| (result, response.time))
+            .map_err(|e| ExchangeError::Parse(e.to_string())),
+        10006 | 10018 => Err(ExchangeError::RateLimited { exchange, code, retry_after: None }),
+        10001 if message.to_lowercase().contains("symbol") => Err(ExchangeError::SymbolNotFound {
+            exchange,
+            symbol: symbol.unwrap_or_default().to_string(),
+        }),
+        10003 | 10004 | 10005 | 10007 | 10009 | 10010 | 33004 => {
+            Err(ExchangeError::Auth { exchange, code, message })
+        }
+        10016 => Err(ExchangeError::Maintenance { exchange, message }),
+        _ => Err(ExchangeError::Api { exchange, code, message }),
     }
 }
 
@@ -43,51 +222,35 @@ impl Exchange for Bybit {
 
     async fn get_price(&self, symbol: &str) -> Result<MarketPrice> {
         let url = format!(
-            "https://api.bybit.com/v5/market/tickers?category=spot&symbol={}",
-            symbol
+            "{}/v5/market/tickers?category=spot&symbol={}",
+            self.base_url, symbol
         );
 
-        let response = reqwest::get(&url)
-            .await?
-            .json::<BybitResponse<BybitResult>>()
-            .await?;
-
-        if response.retCode == 0 {
-            if let Some(ticker) = response.result.list.first() {
-                match ticker.lastPrice.parse() {
-                    Ok(price) => {
-                        let volume = ticker.volume24h.parse().ok();
-                        Ok(MarketPrice {
-                            price,
-                            volume_24h: volume,
-                            timestamp: Utc::now().timestamp(),
-                        })
-                    }
-                    Err(e) => {
-                        error!("Failed to parse Bybit price: {}", e);
-                        Ok(MarketPrice {
-                            price: rust_decimal_macros::dec!(0),
-                            volume_24h: None,
-                            timestamp: Utc::now().timestamp(),
-                        })
-                    }
-                }
-            } else {
-                warn!("No price data available for {} on Bybit", symbol);
-                Ok(MarketPrice {
-                    price: rust_decimal_macros::dec!(0),
-                    volume_24h: None,
-                    timestamp: Utc::now().timestamp(),
-                })
-            }
-        } else {
-            error!("Bybit API error: {}", response.retMsg);
-            Ok(MarketPrice {
-                price: rust_decimal_macros::dec!(0),
-                volume_24h: None,
-                timestamp: Utc::now().timestamp(),
-            })
-        }
+        let response = self.http.send(self.http.get(&url), &self.limiter, &self.clock, 1).await?;
+        let (result, time): (BybitResult, _) = parse_envelope(response, Some(symbol)).await?;
+        let ticker = result.list.first().ok_or_else(|| ExchangeError::SymbolNotFound {
+            exchange: self.get_name(),
+            symbol: symbol.to_string(),
+        })?;
+
+        Ok(MarketPrice::new(
+            parse_decimal(&ticker.last_price)?,
+            ticker.volume_24h.parse().ok(),
+            self.clock.to_local_ms(time),
+        )
+        .with_book(parse_book(&ticker.bid_price, &ticker.bid_size, &ticker.ask_price, &ticker.ask_size)))
+    }
+
+    fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        Some(self.limiter.stats())
+    }
+
+    fn clock_stats(&self) -> Option<ClockStats> {
+        Some(self.clock.stats())
+    }
+
+    async fn sync_clock(&self) -> Result<ClockStats> {
+        self.clock.sync(|| self.server_time()).await
     }
 
     async fn get_orderbook(&self, _symbol: &str) -> Result<OrderBook> {
@@ -97,4 +260,45 @@ impl Exchange for Bybit {
             timestamp: Utc::now().timestamp(),
         })
     }
+
+    async fn get_trading_fees(&self) -> Result<AccountFees> {
+        let result: BybitFeeResult = self.signed_get("/v5/account/fee-rate", "category=spot").await?;
+
+        let mut fees = AccountFees::default();
+        for entry in result.list {
+            let rates = FeeRates {
+                maker: parse_decimal(&entry.maker_fee_rate)?,
+                taker: parse_decimal(&entry.taker_fee_rate)?,
+            };
+            fees.symbols.insert(entry.symbol, rates);
+        }
+        Ok(fees)
+    }
+    async fn get_asset_networks(&self) -> Result<Vec<AssetNetworks>> {
+        let result: BybitCoinResult = self.signed_get("/v5/asset/coin/query-info", "").await?;
+
+        let assets = result
+            .rows
+            .into_iter()
+            .map(|coin| AssetNetworks {
+                asset: coin.coin,
+                networks: coin
+                    .chains
+                    .into_iter()
+                    .map(|chain| {
+                        // Пустая комиссия означает, что вывод в этой сети не поддерживается
+                        let withdraw_fee = chain.withdraw_fee.parse().ok();
+                        NetworkInfo {
+                            network: chain.chain,
+                            withdraw_enabled: chain.chain_withdraw == "1" && withdraw_fee.is_some(),
+                            withdraw_fee: withdraw_fee.unwrap_or_default(),
+                            withdraw_min: chain.withdraw_min.parse().unwrap_or_default(),
+                            deposit_enabled: chain.chain_deposit == "1",
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(assets)
+    }
 }
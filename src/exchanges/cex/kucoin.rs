@@ -1,28 +1,163 @@
-use crate::exchanges::{Exchange, ExchangeError, Result, OrderBook};
-use crate::types::MarketPrice;
+use crate::exchanges::rate_limit::{LimitHeaders, RateLimitStats, RateLimiter, RateLimits};
+use crate::exchanges::{parse_book, parse_decimal, retry_after, ClockStats, Credentials, Exchange, ExchangeClock, ExchangeError, HttpClient, Result, OrderBook};
+use crate::fees::{AccountFees, FeeRates};
+use crate::transfers::{AssetNetworks, NetworkInfo};
+use crate::types::{split_symbol, MarketPrice};
 use async_trait::async_trait;
 use chrono::Utc;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 
+/// KuCoin пишет символы через дефис (`BTC-USDT`), поэтому символ делится по котируемой валюте.
+const QUOTE_TOKENS: [&str; 8] = ["USDT", "USDC", "BUSD", "TUSD", "DAI", "BTC", "ETH", "KCS"];
+
+/// Общая обёртка ответов: `code` "200000" означает успех.
 #[derive(Debug, Deserialize)]
 struct KuCoinResponse {
     code: String,
-    data: KuCoinPrice,
+    msg: Option<String>,
+    #[serde(default)]
+    data: serde_json::Value,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct KuCoinPrice {
     price: String,
     size: Option<String>,
-    time: u64,
+    /// Время обновления стакана, мс
+    time: Option<i64>,
+    #[serde(default)]
+    best_bid: String,
+    #[serde(default)]
+    best_bid_size: String,
+    #[serde(default)]
+    best_ask: String,
+    #[serde(default)]
+    best_ask_size: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KuCoinBaseFee {
+    maker_fee_rate: String,
+    taker_fee_rate: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct KuCoinCurrency {
+    currency: String,
+    chains: Option<Vec<KuCoinChain>>,
 }
 
-pub struct KuCoin;
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KuCoinChain {
+    chain_name: String,
+    withdrawal_min_fee: Option<String>,
+    withdrawal_min_size: Option<String>,
+    is_withdraw_enabled: bool,
+    is_deposit_enabled: bool,
+}
+
+pub struct KuCoin {
+    credentials: Credentials,
+    limiter: RateLimiter,
+    clock: ExchangeClock,
+    http: HttpClient,
+    base_url: String,
+}
+
+impl Default for KuCoin {
+    fn default() -> Self {
+        Self::with_credentials(Credentials::default())
+    }
+}
+
+/// Разбирает ответ: HTTP-ошибки и коды, отличные от "200000", превращаются в `ExchangeError`.
+/// Пустое `data` при успешном коде означает неизвестный символ.
+async fn parse_response<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    symbol: Option<&str>,
+) -> Result<T> {
+    let status = response.status();
+    let retry = retry_after(response.headers());
+    let body = response.text().await?;
+    let Ok(envelope) = serde_json::from_str::<KuCoinResponse>(&body) else {
+        return Err(if status.is_success() {
+            ExchangeError::Parse(format!("KuCoin: unexpected response {}", body.chars().take(200).collect::<String>()))
+        } else {
+            ExchangeError::from_status("KuCoin", status, retry, &body)
+        });
+    };
+
+    let exchange = "KuCoin".to_string();
+    let not_found = |exchange| ExchangeError::SymbolNotFound {
+        exchange,
+        symbol: symbol.unwrap_or_default().to_string(),
+    };
+    let message = envelope.msg.unwrap_or_default();
+    match envelope.code.as_str() {
+        "200000" if envelope.data.is_null() => Err(not_found(exchange)),
+        "200000" => serde_json::from_value(envelope.data).map_err(|e| ExchangeError::Parse(e.to_string())),
+        "429000" => Err(ExchangeError::RateLimited { exchange, code: envelope.code, retry_after: retry }),
+        "900001" => Err(not_found(exchange)),
+        "400001" | "400002" | "400003" | "400004" | "400005" | "400006" | "400007" => {
+            Err(ExchangeError::Auth { exchange, code: envelope.code, message })
+        }
+        _ if status.as_u16() == 503 => Err(ExchangeError::Maintenance { exchange, message }),
+        _ => Err(ExchangeError::Api { exchange, code: envelope.code, message }),
+    }
+}
 
 impl KuCoin {
+    /// Публичный пул — 2000 единиц веса за 30 секунд; остаток в `gw-ratelimit-remaining`.
+    pub const RATE_LIMITS: RateLimits = RateLimits {
+        burst: 200.0,
+        weight_per_second: 50.0,
+        headers: LimitHeaders::Remaining {
+            header: "gw-ratelimit-remaining",
+            reset_header: "gw-ratelimit-reset",
+            reset_is_absolute: false,
+        },
+    };
+
+    pub const DEFAULT_BASE_URL: &'static str = "https://api.kucoin.com";
+
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    pub fn with_credentials(credentials: Credentials) -> Self {
+        Self {
+            credentials,
+            limiter: RateLimiter::new("KuCoin", Self::RATE_LIMITS),
+            clock: ExchangeClock::new(),
+            http: HttpClient::default(),
+            base_url: Self::DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    pub fn with_rate_limits(mut self, limits: RateLimits) -> Self {
+        self.limiter = RateLimiter::new("KuCoin", limits);
+        self
+    }
+
+    pub fn with_http(mut self, http: HttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Адрес API без завершающего `/`, например мок-сервер в тестах.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    async fn server_time(&self) -> Result<i64> {
+        let url = format!("{}/api/v1/timestamp", self.base_url);
+        let response = self.http.send(self.http.get(&url), &self.limiter, &self.clock, 1).await?;
+        parse_response(response, None).await
     }
 }
 
@@ -33,28 +168,37 @@ impl Exchange for KuCoin {
     }
 
     async fn get_price(&self, symbol: &str) -> Result<MarketPrice> {
-        let formatted_symbol = symbol.replace("USDT", "-USDT");
+        let formatted_symbol = match split_symbol(symbol, &QUOTE_TOKENS.map(String::from)) {
+            Some((base, quote)) => format!("{}-{}", base, quote),
+            None => symbol.to_string(),
+        };
         let url = format!(
-            "https://api.kucoin.com/api/v1/market/orderbook/level1?symbol={}",
-            formatted_symbol
+            "{}/api/v1/market/orderbook/level1?symbol={}",
+            self.base_url, formatted_symbol
         );
         
-        let response = reqwest::get(&url).await?;
-        
-        if response.status().is_success() {
-            let kucoin_resp: KuCoinResponse = response.json().await?;
-            let volume = kucoin_resp.data.size
-                .and_then(|s| s.parse::<Decimal>().ok());
-            
-            Ok(MarketPrice::new(
-                kucoin_resp.data.price.parse::<Decimal>()
-                    .map_err(|e| ExchangeError::Parse(e.to_string()))?,
-                volume,
-                Utc::now().timestamp()
-            ))
-        } else {
-            Err(ExchangeError::Exchange(format!("HTTP {}", response.status())))
-        }
+        let response = self.http.send(self.http.get(&url), &self.limiter, &self.clock, 2).await?;
+        let price: KuCoinPrice = parse_response(response, Some(symbol)).await?;
+        let volume = price.size.and_then(|s| s.parse::<Decimal>().ok());
+
+        Ok(MarketPrice::new(
+            parse_decimal(&price.price)?,
+            volume,
+            self.clock.to_local_ms(price.time),
+        )
+        .with_book(parse_book(&price.best_bid, &price.best_bid_size, &price.best_ask, &price.best_ask_size)))
+    }
+
+    fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        Some(self.limiter.stats())
+    }
+
+    fn clock_stats(&self) -> Option<ClockStats> {
+        Some(self.clock.stats())
+    }
+
+    async fn sync_clock(&self) -> Result<ClockStats> {
+        self.clock.sync(|| self.server_time()).await
     }
 
     async fn get_orderbook(&self, _symbol: &str) -> Result<OrderBook> {
@@ -64,4 +208,65 @@ impl Exchange for KuCoin {
             timestamp: Utc::now().timestamp(),
         })
     }
+
+    async fn get_trading_fees(&self) -> Result<AccountFees> {
+        if !self.credentials.is_set() || self.credentials.passphrase.is_empty() {
+            return Err(ExchangeError::not_configured("KuCoin", "API key or passphrase"));
+        }
+
+        let endpoint = "/api/v1/base-fee";
+        let timestamp = self.clock.server_now_ms().to_string();
+        let signature = self.credentials.sign_base64(&format!("{}GET{}", timestamp, endpoint));
+        let passphrase = self.credentials.sign_base64(&self.credentials.passphrase);
+
+        let request = self
+            .http
+            .get(&format!("{}{}", self.base_url, endpoint))
+            .header("KC-API-KEY", &self.credentials.api_key)
+            .header("KC-API-SIGN", signature)
+            .header("KC-API-TIMESTAMP", &timestamp)
+            .header("KC-API-PASSPHRASE", passphrase)
+            .header("KC-API-KEY-VERSION", "2");
+        let response = self.http.send(request, &self.limiter, &self.clock, 3).await?;
+        let base_fee: KuCoinBaseFee = parse_response(response, None).await?;
+
+        Ok(AccountFees {
+            default: Some(FeeRates {
+                maker: parse_decimal(&base_fee.maker_fee_rate)?,
+                taker: parse_decimal(&base_fee.taker_fee_rate)?,
+            }),
+            symbols: Default::default(),
+        })
+    }
+
+    /// Публичный эндпоинт, ключи не нужны.
+    async fn get_asset_networks(&self) -> Result<Vec<AssetNetworks>> {
+        let url = format!("{}/api/v3/currencies", self.base_url);
+        let response = self.http.send(self.http.get(&url), &self.limiter, &self.clock, 3).await?;
+        let currencies: Vec<KuCoinCurrency> = parse_response(response, None).await?;
+
+        let parse = |value: Option<String>| value.and_then(|v| v.parse().ok());
+        let assets = currencies
+            .into_iter()
+            .map(|currency| AssetNetworks {
+                asset: currency.currency,
+                networks: currency
+                    .chains
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|chain| {
+                        let withdraw_fee: Option<Decimal> = parse(chain.withdrawal_min_fee);
+                        NetworkInfo {
+                            network: chain.chain_name,
+                            withdraw_enabled: chain.is_withdraw_enabled && withdraw_fee.is_some(),
+                            withdraw_fee: withdraw_fee.unwrap_or_default(),
+                            withdraw_min: parse(chain.withdrawal_min_size).unwrap_or_default(),
+                            deposit_enabled: chain.is_deposit_enabled,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(assets)
+    }
 }
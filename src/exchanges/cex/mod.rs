@@ -5,3 +5,61 @@ pub mod bybit;
 pub use binance::Binance;
 pub use kucoin::KuCoin;
 pub use bybit::Bybit;
+
+use crate::config::{ExchangeConfig, RateLimitConfig};
+use crate::exchanges::{Credentials, Exchange, HttpClient, RateLimits};
+use log::warn;
+use std::sync::Arc;
+
+/// Биржи, для которых есть адаптеры.
+pub const SUPPORTED: &[&str] = &["binance", "bybit", "kucoin"];
+
+pub fn is_supported(name: &str) -> bool {
+    SUPPORTED.contains(&name.to_lowercase().as_str())
+}
+
+/// Создаёт адаптер по имени биржи из конфигурации (`binance`, `bybit`, `kucoin`).
+/// Адаптеры разделяют пул соединений `http`; биржа с прокси получает собственный клиент.
+pub fn create(name: &str, config: &ExchangeConfig, http: &HttpClient) -> Option<Arc<dyn Exchange>> {
+    let credentials = Credentials::new(&config.api_key, &config.api_secret, &config.passphrase);
+    let limits = &config.rate_limit;
+    let http = match &config.proxy {
+        Some(proxy) => match http.with_proxy(proxy) {
+            Ok(http) => http,
+            Err(e) => {
+                warn!("Invalid proxy for {}: {}", name, e);
+                return None;
+            }
+        },
+        None => http.clone(),
+    };
+
+    macro_rules! build {
+        ($adapter:ty) => {{
+            let adapter = <$adapter>::with_credentials(credentials)
+                .with_rate_limits(apply(<$adapter>::RATE_LIMITS, limits))
+                .with_http(http);
+            match &config.base_url {
+                Some(url) => adapter.with_base_url(url),
+                None => adapter,
+            }
+        }};
+    }
+
+    match name.to_lowercase().as_str() {
+        "binance" => Some(Arc::new(build!(Binance))),
+        "bybit" => Some(Arc::new(build!(Bybit))),
+        "kucoin" => Some(Arc::new(build!(KuCoin))),
+        _ => None,
+    }
+}
+
+fn apply(mut limits: RateLimits, config: &RateLimitConfig) -> RateLimits {
+    if let Some(burst) = config.burst {
+        limits.burst = burst;
+    }
+    if let Some(weight_per_second) = config.weight_per_second {
+        limits.weight_per_second = weight_per_second;
+    }
+    limits
+}
@@ -0,0 +1,392 @@
+use crate::alerts::{Alert, AlertData, AlertType};
+use crate::config::CircuitBreakerConfig;
+use crate::metrics::RingBuffer;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Состояние предохранителя биржи.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Биржа участвует в анализе
+    Closed,
+    /// Биржа исключена до конца паузы
+    Open,
+    /// Пауза истекла, следующий запрос — проба
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Значение для метрик: 0 — closed, 1 — half-open, 2 — open.
+    pub fn as_gauge(self) -> u8 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::HalfOpen => 1,
+            CircuitState::Open => 2,
+        }
+    }
+}
+
+/// Смена состояния, о которой нужно оповестить.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transition {
+    Opened { reason: String, pause: Duration },
+    Closed,
+}
+
+impl Transition {
+    /// Оповещение о смене состояния предохранителя биржи `exchange`.
+    pub fn alert(&self, exchange: &str) -> Alert {
+        let (severity, message) = match self {
+            Transition::Opened { reason, pause } => (
+                8,
+                format!(
+                    "Circuit breaker opened for {}: {}. Excluded from analysis for {}s",
+                    exchange,
+                    reason,
+                    pause.as_secs()
+                ),
+            ),
+            Transition::Closed => (
+                4,
+                format!("Circuit breaker closed for {}: probe succeeded, exchange is back in analysis", exchange),
+            ),
+        };
+        Alert::new(AlertType::CircuitBreaker, severity, "", message).with_data(AlertData {
+            exchanges: Some(vec![exchange.to_string()]),
+            ..Default::default()
+        })
+    }
+}
+
+/// Предохранитель биржи: после серии сбоев, низкой доли успешных запросов или долгого
+/// отсутствия годных котировок биржа исключается из анализа. По истечении паузы одна
+/// проба решает, вернуть биржу или продлить паузу вдвое.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    state: CircuitState,
+    /// Исходы запросов с момента последнего закрытия
+    window: RingBuffer<bool>,
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+    pause: Option<Duration>,
+    last_good_data: Instant,
+    reason: Option<String>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: &CircuitBreakerConfig) -> Self {
+        Self::new_at(config, Instant::now())
+    }
+
+    fn new_at(config: &CircuitBreakerConfig, now: Instant) -> Self {
+        Self {
+            state: CircuitState::Closed,
+            window: RingBuffer::new(config.window),
+            consecutive_failures: 0,
+            open_until: None,
+            pause: None,
+            last_good_data: now,
+            reason: None,
+        }
+    }
+
+    /// Текущее состояние; открытый предохранитель по истечении паузы становится полуоткрытым.
+    pub fn poll(&mut self) -> CircuitState {
+        self.poll_at(Instant::now())
+    }
+
+    fn poll_at(&mut self, now: Instant) -> CircuitState {
+        if self.state == CircuitState::Open && self.open_until.is_none_or(|until| now >= until) {
+            self.state = CircuitState::HalfOpen;
+        }
+        self.state
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    /// Почему предохранитель открыт.
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    pub fn record(&mut self, success: bool) {
+        if self.state != CircuitState::Closed {
+            return;
+        }
+        self.window.push(success);
+        if success {
+            self.consecutive_failures = 0;
+        } else {
+            self.consecutive_failures += 1;
+        }
+    }
+
+    /// Биржа отдала котировку, прошедшую фильтр качества.
+    pub fn record_good_data(&mut self) {
+        self.record_good_data_at(Instant::now());
+    }
+
+    fn record_good_data_at(&mut self, now: Instant) {
+        self.last_good_data = now;
+    }
+
+    /// Проверяет пороги после прохода; открывает предохранитель, если биржа нездорова.
+    pub fn evaluate(&mut self, config: &CircuitBreakerConfig) -> Option<Transition> {
+        self.evaluate_at(config, Instant::now())
+    }
+
+    fn evaluate_at(&mut self, config: &CircuitBreakerConfig, now: Instant) -> Option<Transition> {
+        if self.state != CircuitState::Closed {
+            return None;
+        }
+
+        let requests = self.window.len();
+        let successes = self.window.iter().filter(|ok| **ok).count();
+        let data_age = now.saturating_duration_since(self.last_good_data);
+        let reason = if self.consecutive_failures >= config.failure_threshold {
+            format!("{} consecutive failed requests", self.consecutive_failures)
+        } else if requests >= config.window.min(config.min_requests) && (successes as f64) < config.min_success_rate * requests as f64 {
+            format!("success rate {:.1}% over {} requests", successes as f64 / requests as f64 * 100.0, requests)
+        } else if data_age > Duration::from_secs(config.max_data_age_secs) {
+            format!("no valid quotes for {}s", data_age.as_secs())
+        } else {
+            return None;
+        };
+
+        Some(self.open(reason, Duration::from_secs(config.open_secs), now))
+    }
+
+    /// Итог пробы в полуоткрытом состоянии.
+    pub fn probe_result(&mut self, success: bool, error: Option<String>, config: &CircuitBreakerConfig) -> Transition {
+        self.probe_result_at(success, error, config, Instant::now())
+    }
+
+    fn probe_result_at(
+        &mut self,
+        success: bool,
+        error: Option<String>,
+        config: &CircuitBreakerConfig,
+        now: Instant,
+    ) -> Transition {
+        if success {
+            self.state = CircuitState::Closed;
+            self.window = RingBuffer::new(config.window);
+            self.consecutive_failures = 0;
+            self.open_until = None;
+            self.pause = None;
+            self.reason = None;
+            self.last_good_data = now;
+            return Transition::Closed;
+        }
+
+        let pause = self
+            .pause
+            .map_or(Duration::from_secs(config.open_secs), |pause| pause * 2)
+            .min(Duration::from_secs(config.max_open_secs));
+        let reason = format!("probe failed: {}", error.unwrap_or_else(|| "no valid quote".to_string()));
+        self.open(reason, pause, now)
+    }
+
+    fn open(&mut self, reason: String, pause: Duration, now: Instant) -> Transition {
+        self.state = CircuitState::Open;
+        self.open_until = Some(now + pause);
+        self.pause = Some(pause);
+        self.reason = Some(reason.clone());
+        Transition::Opened { reason, pause }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: 3,
+            window: 10,
+            min_requests: 4,
+            max_data_age_secs: 60,
+            open_secs: 30,
+            max_open_secs: 100,
+            ..Default::default()
+        }
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    /// Предохранитель, открытый в момент `start` серией сбоев.
+    fn opened(start: Instant) -> CircuitBreaker {
+        let config = config();
+        let mut breaker = CircuitBreaker::new_at(&config, start);
+        for _ in 0..config.failure_threshold {
+            breaker.record(false);
+        }
+        assert!(breaker.evaluate_at(&config, start).is_some());
+        breaker
+    }
+
+    #[test]
+    fn opens_at_failure_threshold() {
+        let config = config();
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new_at(&config, start);
+        breaker.record(false);
+        breaker.record(false);
+        assert_eq!(breaker.evaluate_at(&config, start), None);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+
+        breaker.record(false);
+        assert_eq!(
+            breaker.evaluate_at(&config, start),
+            Some(Transition::Opened {
+                reason: "3 consecutive failed requests".to_string(),
+                pause: secs(30),
+            })
+        );
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert_eq!(breaker.reason(), Some("3 consecutive failed requests"));
+        // Открытый предохранитель не открывается повторно
+        assert_eq!(breaker.evaluate_at(&config, start), None);
+    }
+
+    #[test]
+    fn success_resets_consecutive_failures() {
+        let config = config();
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new_at(&config, start);
+        for success in [false, false, true, true, false, false, true, true] {
+            breaker.record(success);
+        }
+        assert_eq!(breaker.evaluate_at(&config, start), None);
+    }
+
+    #[test]
+    fn opens_on_low_success_rate() {
+        let config = config();
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new_at(&config, start);
+        breaker.record(false);
+        breaker.record(true);
+        breaker.record(false);
+        // Меньше `min_requests` запросов — по доле не судим
+        assert_eq!(breaker.evaluate_at(&config, start), None);
+
+        breaker.record(false);
+        assert_eq!(
+            breaker.evaluate_at(&config, start),
+            Some(Transition::Opened {
+                reason: "success rate 25.0% over 4 requests".to_string(),
+                pause: secs(30),
+            })
+        );
+    }
+
+    #[test]
+    fn opens_when_good_data_is_too_old() {
+        let config = config();
+        let start = Instant::now();
+        let mut breaker = CircuitBreaker::new_at(&config, start);
+        assert_eq!(breaker.evaluate_at(&config, start + secs(60)), None);
+
+        breaker.record_good_data_at(start + secs(50));
+        assert_eq!(breaker.evaluate_at(&config, start + secs(110)), None);
+        assert_eq!(
+            breaker.evaluate_at(&config, start + secs(111)),
+            Some(Transition::Opened {
+                reason: "no valid quotes for 61s".to_string(),
+                pause: secs(30),
+            })
+        );
+    }
+
+    #[test]
+    fn half_opens_once_pause_expires() {
+        let config = config();
+        let start = Instant::now();
+        let mut breaker = opened(start);
+        assert_eq!(breaker.poll_at(start + secs(29)), CircuitState::Open);
+        assert_eq!(breaker.poll_at(start + secs(30)), CircuitState::HalfOpen);
+
+        // В полуоткрытом состоянии решает только проба: обычные запросы и пороги не учитываются
+        breaker.record(false);
+        breaker.record(false);
+        breaker.record(false);
+        assert_eq!(breaker.evaluate_at(&config, start + secs(30)), None);
+        assert_eq!(breaker.poll_at(start + secs(31)), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn probe_success_closes_with_clean_history() {
+        let config = config();
+        let start = Instant::now();
+        let mut breaker = opened(start);
+        breaker.poll_at(start + secs(30));
+
+        let transition = breaker.probe_result_at(true, None, &config, start + secs(30));
+        assert_eq!(transition, Transition::Closed);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.reason(), None);
+
+        // Сбои до открытия забыты, отсчёт возраста данных начат заново
+        breaker.record(false);
+        breaker.record(false);
+        assert_eq!(breaker.evaluate_at(&config, start + secs(90)), None);
+    }
+
+    #[test]
+    fn probe_failure_doubles_pause_up_to_cap() {
+        let config = config();
+        let start = Instant::now();
+        let mut breaker = opened(start);
+
+        let mut now = start;
+        for expected in [60, 100, 100] {
+            now += breaker.pause.unwrap();
+            assert_eq!(breaker.poll_at(now), CircuitState::HalfOpen);
+            let transition = breaker.probe_result_at(false, Some("timeout".to_string()), &config, now);
+            assert_eq!(
+                transition,
+                Transition::Opened {
+                    reason: "probe failed: timeout".to_string(),
+                    pause: secs(expected),
+                }
+            );
+            assert_eq!(breaker.poll_at(now + secs(expected - 1)), CircuitState::Open);
+        }
+
+        now += secs(100);
+        breaker.poll_at(now);
+        breaker.probe_result_at(false, None, &config, now);
+        assert_eq!(breaker.reason(), Some("probe failed: no valid quote"));
+    }
+
+    #[test]
+    fn transitions_raise_exchange_alerts() {
+        let opened = Transition::Opened {
+            reason: "3 consecutive failed requests".to_string(),
+            pause: secs(30),
+        }
+        .alert("bybit");
+        assert_eq!(opened.alert_type, AlertType::CircuitBreaker);
+        assert_eq!(opened.severity, 8);
+        assert_eq!(opened.symbol, "");
+        assert_eq!(
+            opened.message,
+            "Circuit breaker opened for bybit: 3 consecutive failed requests. Excluded from analysis for 30s"
+        );
+        assert_eq!(opened.data.and_then(|data| data.exchanges), Some(vec!["bybit".to_string()]));
+
+        let closed = Transition::Closed.alert("bybit");
+        assert_eq!(closed.severity, 4);
+        assert_eq!(
+            closed.message,
+            "Circuit breaker closed for bybit: probe succeeded, exchange is back in analysis"
+        );
+        assert_eq!(closed.data.and_then(|data| data.exchanges), Some(vec!["bybit".to_string()]));
+    }
+}
@@ -0,0 +1,99 @@
+use super::Result;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+
+/// Замеров времени сервера за одну синхронизацию; берётся замер с наименьшей задержкой.
+const SYNC_SAMPLES: usize = 3;
+/// Вес нового замера в сглаженной задержке.
+const LATENCY_SMOOTHING: f64 = 0.2;
+
+/// Задержка запросов и смещение часов биржи для мониторинга.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ClockStats {
+    /// Задержка последнего запроса (туда и обратно), мс
+    pub latency_ms: Option<u64>,
+    /// Сглаженная задержка, мс
+    pub avg_latency_ms: Option<f64>,
+    /// Часы биржи минус локальные, мс
+    pub offset_ms: Option<i64>,
+    /// Задержка замера, по которому оценено смещение; погрешность — половина её
+    pub offset_rtt_ms: Option<u64>,
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// Часы биржи: задержка запросов и оценка смещения по эндпоинту времени сервера.
+/// Смещение переводит время событий биржи в локальные часы и нужно для подписи запросов.
+#[derive(Debug, Default)]
+pub struct ExchangeClock {
+    stats: Mutex<ClockStats>,
+}
+
+impl ExchangeClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_latency(&self, latency: Duration) {
+        let ms = latency.as_secs_f64() * 1000.0;
+        let mut stats = self.stats.lock();
+        stats.latency_ms = Some(ms as u64);
+        stats.avg_latency_ms = Some(match stats.avg_latency_ms {
+            Some(avg) => avg + (ms - avg) * LATENCY_SMOOTHING,
+            None => ms,
+        });
+    }
+
+    /// Оценивает смещение по нескольким запросам времени сервера (мс от эпохи).
+    /// Время сервера относится к середине запроса, как в NTP.
+    pub async fn sync<F, Fut>(&self, mut server_time: F) -> Result<ClockStats>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<i64>>,
+    {
+        let mut best: Option<(i64, u64)> = None;
+        for _ in 0..SYNC_SAMPLES {
+            let sent = Utc::now().timestamp_millis();
+            let server = server_time().await?;
+            let received = Utc::now().timestamp_millis();
+
+            let rtt = (received - sent).max(0);
+            let offset = server - (sent + rtt / 2);
+            if best.is_none_or(|(_, best_rtt)| (rtt as u64) < best_rtt) {
+                best = Some((offset, rtt as u64));
+            }
+        }
+
+        let mut stats = self.stats.lock();
+        if let Some((offset, rtt)) = best {
+            stats.offset_ms = Some(offset);
+            stats.offset_rtt_ms = Some(rtt);
+            stats.synced_at = Some(Utc::now());
+        }
+        Ok(stats.clone())
+    }
+
+    /// Смещение часов биржи; 0, пока синхронизации не было.
+    pub fn offset_ms(&self) -> i64 {
+        self.stats.lock().offset_ms.unwrap_or(0)
+    }
+
+    /// Текущее время по часам биржи — для `timestamp` в подписанных запросах.
+    pub fn server_now_ms(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.offset_ms()
+    }
+
+    /// Время события биржи в локальных часах. Без времени от биржи — момент получения ответа.
+    pub fn to_local_ms(&self, server_ms: Option<i64>) -> i64 {
+        match server_ms {
+            Some(ms) if ms > 0 => ms - self.offset_ms(),
+            _ => Utc::now().timestamp_millis(),
+        }
+    }
+
+    pub fn stats(&self) -> ClockStats {
+        self.stats.lock().clone()
+    }
+}
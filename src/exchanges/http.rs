@@ -0,0 +1,131 @@
+use super::{ExchangeClock, RateLimiter};
+use crate::config::HttpConfig;
+use log::debug;
+use reqwest::{Method, Request, RequestBuilder, Response, StatusCode};
+use std::time::{Duration, Instant};
+
+/// Общий HTTP-клиент адаптеров и оповещений: таймауты, пул keep-alive соединений,
+/// повторы идемпотентных GET-запросов с экспоненциальной паузой и случайным разбросом.
+///
+/// Клонирование дешёвое и разделяет пул соединений.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    config: HttpConfig,
+    timeout: Duration,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(&HttpConfig::default(), Duration::from_secs(10))
+            .expect("default HTTP client configuration is valid")
+    }
+}
+
+impl HttpClient {
+    /// `timeout` используется, если в `config` не задан `timeout_ms`.
+    pub fn new(config: &HttpConfig, timeout: Duration) -> reqwest::Result<Self> {
+        let timeout = config.timeout_ms.map(Duration::from_millis).unwrap_or(timeout);
+        let client = builder(config, timeout).build()?;
+        Ok(Self {
+            client,
+            config: config.clone(),
+            timeout,
+        })
+    }
+
+    /// Клиент с теми же настройками, но через прокси (`http://`, `https://`, `socks5://`).
+    /// У него собственный пул соединений.
+    pub fn with_proxy(&self, proxy: &str) -> reqwest::Result<Self> {
+        let client = builder(&self.config, self.timeout)
+            .proxy(reqwest::Proxy::all(proxy)?)
+            .build()?;
+        Ok(Self {
+            client,
+            config: self.config.clone(),
+            timeout: self.timeout,
+        })
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    /// Отправляет запрос через ограничитель биржи и замеряет задержку. GET повторяется при сетевых
+    /// ошибках и ответах 500/502/503/504; 429 не повторяется — паузу выдерживает ограничитель.
+    pub async fn send(
+        &self,
+        request: RequestBuilder,
+        limiter: &RateLimiter,
+        clock: &ExchangeClock,
+        weight: u32,
+    ) -> reqwest::Result<Response> {
+        let request = request.build()?;
+        let retries = if request.method() == Method::GET { self.config.max_retries } else { 0 };
+
+        let mut attempt = 0;
+        loop {
+            // У GET нет потокового тела, поэтому копия есть всегда
+            let current = match request.try_clone() {
+                Some(current) if attempt < retries => current,
+                _ => return self.execute(request, limiter, clock, weight).await,
+            };
+
+            let result = self.execute(current, limiter, clock, weight).await;
+            let retryable = match &result {
+                Ok(response) => matches!(
+                    response.status(),
+                    StatusCode::INTERNAL_SERVER_ERROR
+                        | StatusCode::BAD_GATEWAY
+                        | StatusCode::SERVICE_UNAVAILABLE
+                        | StatusCode::GATEWAY_TIMEOUT
+                ),
+                Err(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            };
+            if !retryable {
+                return result;
+            }
+
+            let delay = self.backoff(attempt);
+            debug!("Retrying {} in {:?} (attempt {} of {})", request.url(), delay, attempt + 1, retries);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn execute(
+        &self,
+        request: Request,
+        limiter: &RateLimiter,
+        clock: &ExchangeClock,
+        weight: u32,
+    ) -> reqwest::Result<Response> {
+        limiter.acquire(weight).await;
+        let started = Instant::now();
+        let response = self.client.execute(request).await?;
+        clock.record_latency(started.elapsed());
+        limiter.observe(response.status(), response.headers());
+        Ok(response)
+    }
+
+    /// `retry_backoff_ms * 2^attempt`, разброс ±50%, чтобы повторы разных бирж не совпадали.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let base = self.config.retry_backoff_ms.saturating_mul(1 << attempt.min(10));
+        let jitter = 0.5 + fastrand::f64();
+        Duration::from_millis((base as f64 * jitter) as u64)
+    }
+}
+
+fn builder(config: &HttpConfig, timeout: Duration) -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_secs(config.pool_idle_timeout_secs))
+        .tcp_keepalive(Duration::from_secs(60))
+        .user_agent(concat!("arbitrage-system/", env!("CARGO_PKG_VERSION")))
+}
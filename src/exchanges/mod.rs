@@ -1,9 +1,24 @@
+pub mod auth;
 pub mod cex;
+pub mod circuit;
+pub mod clock;
+pub mod http;
+pub mod rate_limit;
+
+pub use auth::Credentials;
+pub use circuit::{CircuitBreaker, CircuitState, Transition};
+pub use clock::{ClockStats, ExchangeClock};
+pub use http::HttpClient;
+pub use rate_limit::{RateLimitStats, RateLimiter, RateLimits};
 
 use async_trait::async_trait;
 use thiserror::Error;
-use crate::types::MarketPrice;
+use crate::fees::AccountFees;
+use crate::transfers::AssetNetworks;
+use crate::types::{MarketPrice, TopOfBook};
+use reqwest::StatusCode;
 use rust_decimal::Decimal;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct OrderBook {
@@ -14,16 +29,143 @@ pub struct OrderBook {
 
 pub type Result<T> = std::result::Result<T, ExchangeError>;
 
+/// Ошибки биржевого слоя. Коды бирж сохраняются, чтобы вызывающий код мог решить:
+/// повторить запрос, пропустить пару или поднять оповещение.
 #[derive(Error, Debug)]
 pub enum ExchangeError {
     #[error("Network error: {0}")]
-    Network(#[from] reqwest::Error),
-    
+    Network(reqwest::Error),
+
+    #[error("{exchange}: rate limited (code {code})")]
+    RateLimited {
+        exchange: String,
+        code: String,
+        retry_after: Option<Duration>,
+    },
+
+    #[error("{exchange}: symbol {symbol} not found")]
+    SymbolNotFound { exchange: String, symbol: String },
+
+    #[error("{exchange}: under maintenance: {message}")]
+    Maintenance { exchange: String, message: String },
+
+    #[error("{exchange}: authentication failed (code {code}): {message}")]
+    Auth {
+        exchange: String,
+        code: String,
+        message: String,
+    },
+
     #[error("Parse error: {0}")]
     Parse(String),
-    
-    #[error("Exchange error: {0}")]
-    Exchange(String),
+
+    #[error("{exchange}: API error {code}: {message}")]
+    Api {
+        exchange: String,
+        code: String,
+        message: String,
+    },
+
+    #[error("{0} is not supported")]
+    Unsupported(String),
+}
+
+impl From<reqwest::Error> for ExchangeError {
+    fn from(e: reqwest::Error) -> Self {
+        // Ошибки разбора JSON reqwest тоже отдаёт как reqwest::Error
+        if e.is_decode() {
+            ExchangeError::Parse(e.to_string())
+        } else {
+            ExchangeError::Network(e)
+        }
+    }
+}
+
+impl ExchangeError {
+    /// Ошибка временная, запрос имеет смысл повторить позже.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ExchangeError::Network(_) | ExchangeError::RateLimited { .. } | ExchangeError::Maintenance { .. }
+        )
+    }
+
+    /// Вид ошибки для меток метрик: `network`, `rate_limited`, ...
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ExchangeError::Network(_) => "network",
+            ExchangeError::RateLimited { .. } => "rate_limited",
+            ExchangeError::SymbolNotFound { .. } => "symbol_not_found",
+            ExchangeError::Maintenance { .. } => "maintenance",
+            ExchangeError::Auth { .. } => "auth",
+            ExchangeError::Parse(_) => "parse",
+            ExchangeError::Api { .. } => "api",
+            ExchangeError::Unsupported(_) => "unsupported",
+        }
+    }
+
+    /// Код ошибки биржи или HTTP-статус.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            ExchangeError::RateLimited { code, .. }
+            | ExchangeError::Auth { code, .. }
+            | ExchangeError::Api { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
+    pub fn not_configured(exchange: &str, what: &str) -> Self {
+        ExchangeError::Auth {
+            exchange: exchange.to_string(),
+            code: "not_configured".to_string(),
+            message: format!("{} is not configured", what),
+        }
+    }
+
+    /// Ошибка по HTTP-статусу, когда тело ответа не содержит кода биржи.
+    pub fn from_status(exchange: &str, status: StatusCode, retry_after: Option<Duration>, body: &str) -> Self {
+        let exchange = exchange.to_string();
+        let code = format!("HTTP {}", status.as_u16());
+        let message = body.chars().take(200).collect::<String>();
+        match status.as_u16() {
+            // 418 — бан Binance за игнорирование 429
+            429 | 418 => ExchangeError::RateLimited { exchange, code, retry_after },
+            401 | 403 => ExchangeError::Auth { exchange, code, message },
+            503 => ExchangeError::Maintenance { exchange, message },
+            _ => ExchangeError::Api { exchange, code, message },
+        }
+    }
+}
+
+/// Значение заголовка `Retry-After` в секундах.
+pub fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+/// Биржи отдают числа строками.
+pub fn parse_decimal(value: &str) -> Result<Decimal> {
+    value
+        .parse()
+        .map_err(|e: rust_decimal::Error| ExchangeError::Parse(format!("{:?}: {}", value, e)))
+}
+
+/// Вершина стакана из строковых полей ответа. Пустая сторона стакана приходит
+/// пустой строкой или нулём — тогда `None`.
+pub fn parse_book(bid: &str, bid_size: &str, ask: &str, ask_size: &str) -> Option<TopOfBook> {
+    let book = TopOfBook {
+        bid: bid.parse().ok()?,
+        bid_size: bid_size.parse().ok()?,
+        ask: ask.parse().ok()?,
+        ask_size: ask_size.parse().ok()?,
+    };
+    (book.bid > Decimal::ZERO && book.ask > Decimal::ZERO).then_some(book)
 }
 
 #[async_trait]
@@ -31,4 +173,29 @@ pub trait Exchange: Send + Sync {
     fn get_name(&self) -> String;
     async fn get_price(&self, symbol: &str) -> Result<MarketPrice>;
     async fn get_orderbook(&self, symbol: &str) -> Result<OrderBook>;
+
+    /// Счётчики ограничителя запросов, если он есть у адаптера.
+    fn rate_limit_stats(&self) -> Option<RateLimitStats> {
+        None
+    }
+
+    /// Задержка запросов и смещение часов биржи.
+    fn clock_stats(&self) -> Option<ClockStats> {
+        None
+    }
+
+    /// Оценивает смещение часов по эндпоинту времени сервера.
+    async fn sync_clock(&self) -> Result<ClockStats> {
+        Err(ExchangeError::Unsupported(format!("{} server time API", self.get_name())))
+    }
+
+    /// Фактические ставки комиссий аккаунта через приватный API.
+    async fn get_trading_fees(&self) -> Result<AccountFees> {
+        Err(ExchangeError::Unsupported(format!("{} fee rates API", self.get_name())))
+    }
+
+    /// Сети вывода и ввода по всем активам: комиссии, минимумы, статус.
+    async fn get_asset_networks(&self) -> Result<Vec<AssetNetworks>> {
+        Err(ExchangeError::Unsupported(format!("{} asset networks API", self.get_name())))
+    }
 }
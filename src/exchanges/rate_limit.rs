@@ -0,0 +1,322 @@
+use log::warn;
+use parking_lot::Mutex;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+// Часы tokio: в тестах время ограничителя управляется `tokio::time::pause`
+use tokio::time::Instant;
+
+/// Пауза после первого ответа 429; каждый следующий подряд удваивает её.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Доля серверного лимита веса, после которой запросы ждут новой минуты.
+const WEIGHT_SAFETY: f64 = 0.9;
+
+/// Заголовки, в которых биржа сообщает об израсходованном лимите.
+#[derive(Debug, Clone, Copy)]
+pub enum LimitHeaders {
+    None,
+    /// Израсходованный за минуту вес (Binance `X-MBX-USED-WEIGHT-1M`)
+    UsedWeight { header: &'static str, limit: u32 },
+    /// Остаток запросов и момент сброса: абсолютный (мс от эпохи) или относительный (мс)
+    Remaining {
+        header: &'static str,
+        reset_header: &'static str,
+        reset_is_absolute: bool,
+    },
+}
+
+/// Лимиты биржи в единицах веса запроса.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimits {
+    /// Ёмкость ведра — допустимый всплеск
+    pub burst: f64,
+    pub weight_per_second: f64,
+    pub headers: LimitHeaders,
+}
+
+/// Счётчики ограничителя для мониторинга.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RateLimitStats {
+    pub requests: u64,
+    /// Сколько раз запрос ждал освобождения лимита
+    pub throttled: u64,
+    pub throttled_ms: u64,
+    /// Ответы 429/418
+    pub rejected: u64,
+    /// Последний вес или остаток, сообщённый биржей
+    pub server_usage: Option<u64>,
+}
+
+#[derive(Debug)]
+struct State {
+    tokens: f64,
+    last_refill: Instant,
+    blocked_until: Option<Instant>,
+    backoff: Duration,
+}
+
+/// Ведро токенов на биржу: каждый запрос забирает свой вес, ведро пополняется
+/// с постоянной скоростью. Ответы биржи о израсходованном лимите и 429/418
+/// блокируют запросы до сброса окна или на время экспоненциальной паузы.
+#[derive(Debug)]
+pub struct RateLimiter {
+    name: String,
+    limits: RateLimits,
+    state: Mutex<State>,
+    requests: AtomicU64,
+    throttled: AtomicU64,
+    throttled_ms: AtomicU64,
+    rejected: AtomicU64,
+    server_usage: AtomicU64,
+}
+
+impl RateLimiter {
+    pub fn new(name: &str, limits: RateLimits) -> Self {
+        Self {
+            name: name.to_string(),
+            limits,
+            state: Mutex::new(State {
+                tokens: limits.burst,
+                last_refill: Instant::now(),
+                blocked_until: None,
+                backoff: INITIAL_BACKOFF,
+            }),
+            requests: AtomicU64::new(0),
+            throttled: AtomicU64::new(0),
+            throttled_ms: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            server_usage: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    /// Ждёт, пока запрос весом `weight` можно отправить.
+    pub async fn acquire(&self, weight: u32) {
+        let weight = f64::from(weight).min(self.limits.burst);
+        loop {
+            let wait = {
+                let mut state = self.state.lock();
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * self.limits.weight_per_second).min(self.limits.burst);
+                state.last_refill = now;
+
+                match state.blocked_until {
+                    Some(until) if until > now => until - now,
+                    _ if state.tokens >= weight => {
+                        state.tokens -= weight;
+                        self.requests.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    _ => Duration::from_secs_f64((weight - state.tokens) / self.limits.weight_per_second),
+                }
+            };
+
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+            self.throttled_ms.fetch_add(wait.as_millis() as u64, Ordering::Relaxed);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Учитывает ответ: заголовки лимитов и статусы 429/418.
+    pub fn observe(&self, status: StatusCode, headers: &HeaderMap) {
+        let now = Instant::now();
+        let mut state = self.state.lock();
+
+        if status == StatusCode::TOO_MANY_REQUESTS || status.as_u16() == 418 {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            let pause = super::retry_after(headers).unwrap_or(state.backoff);
+            state.backoff = (state.backoff * 2).min(MAX_BACKOFF);
+            state.blocked_until = Some(now + pause);
+            state.tokens = 0.0;
+            warn!("{}: HTTP {}, pausing requests for {:?}", self.name, status.as_u16(), pause);
+            return;
+        }
+        if status.is_success() {
+            state.backoff = INITIAL_BACKOFF;
+        }
+
+        match self.limits.headers {
+            LimitHeaders::None => {}
+            LimitHeaders::UsedWeight { header, limit } => {
+                let Some(used) = header_u64(headers, header) else {
+                    return;
+                };
+                self.server_usage.store(used, Ordering::Relaxed);
+                let limit = f64::from(limit);
+                // Окно веса у Binance — календарная минута
+                if used as f64 >= limit * WEIGHT_SAFETY {
+                    let into_minute = chrono::Utc::now().timestamp_millis().rem_euclid(60_000) as u64;
+                    let pause = Duration::from_millis(60_000 - into_minute);
+                    state.blocked_until = Some(now + pause);
+                    warn!("{}: used weight {} of {}, pausing for {:?}", self.name, used, limit, pause);
+                }
+            }
+            LimitHeaders::Remaining { header, reset_header, reset_is_absolute } => {
+                let Some(remaining) = header_u64(headers, header) else {
+                    return;
+                };
+                self.server_usage.store(remaining, Ordering::Relaxed);
+                if remaining > 0 {
+                    return;
+                }
+                let pause = header_u64(headers, reset_header)
+                    .map(|reset| {
+                        if reset_is_absolute {
+                            reset.saturating_sub(chrono::Utc::now().timestamp_millis().max(0) as u64)
+                        } else {
+                            reset
+                        }
+                    })
+                    .map(Duration::from_millis)
+                    .unwrap_or(INITIAL_BACKOFF)
+                    .min(MAX_BACKOFF);
+                state.blocked_until = Some(now + pause);
+                warn!("{}: request limit exhausted, pausing for {:?}", self.name, pause);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> RateLimitStats {
+        let server_usage = self.server_usage.load(Ordering::Relaxed);
+        RateLimitStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            throttled: self.throttled.load(Ordering::Relaxed),
+            throttled_ms: self.throttled_ms.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            server_usage: (server_usage != u64::MAX).then_some(server_usage),
+        }
+    }
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    /// Ведро на 2 единицы веса, пополняется на 1 в секунду.
+    fn limiter(headers: LimitHeaders) -> RateLimiter {
+        RateLimiter::new(
+            "Test",
+            RateLimits {
+                burst: 2.0,
+                weight_per_second: 1.0,
+                headers,
+            },
+        )
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    /// Сколько ждал `acquire` по часам tokio.
+    async fn waited(limiter: &RateLimiter, weight: u32) -> Duration {
+        let started = Instant::now();
+        limiter.acquire(weight).await;
+        started.elapsed()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn token_bucket_refills_at_configured_rate() {
+        let limiter = limiter(LimitHeaders::None);
+        assert_eq!(waited(&limiter, 1).await, Duration::ZERO);
+        assert_eq!(waited(&limiter, 1).await, Duration::ZERO);
+        assert_eq!(waited(&limiter, 1).await, Duration::from_secs(1));
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        // Ведро не копит больше `burst`
+        assert_eq!(waited(&limiter, 2).await, Duration::ZERO);
+        // Вес больше ёмкости ведра ограничивается ею
+        assert_eq!(waited(&limiter, 5).await, Duration::from_secs(2));
+
+        let stats = limiter.stats();
+        assert_eq!(stats.requests, 5);
+        assert_eq!(stats.throttled, 2);
+        assert_eq!(stats.throttled_ms, 3000);
+        assert_eq!(stats.rejected, 0);
+        assert_eq!(stats.server_usage, None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rejections_double_backoff_until_success() {
+        let limiter = limiter(LimitHeaders::None);
+        let empty = HeaderMap::new();
+
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS, &empty);
+        assert_eq!(waited(&limiter, 1).await, Duration::from_secs(1));
+        limiter.observe(StatusCode::from_u16(418).unwrap(), &empty);
+        assert_eq!(waited(&limiter, 1).await, Duration::from_secs(2));
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS, &empty);
+        assert_eq!(waited(&limiter, 1).await, Duration::from_secs(4));
+
+        // Успешный ответ сбрасывает паузу к начальной
+        limiter.observe(StatusCode::OK, &empty);
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS, &empty);
+        assert_eq!(waited(&limiter, 1).await, Duration::from_secs(1));
+
+        // Retry-After важнее собственной паузы
+        limiter.observe(StatusCode::TOO_MANY_REQUESTS, &headers(&[("retry-after", "7")]));
+        assert_eq!(waited(&limiter, 1).await, Duration::from_secs(7));
+
+        let stats = limiter.stats();
+        assert_eq!(stats.rejected, 5);
+        assert_eq!(stats.throttled, 5);
+        assert_eq!(stats.throttled_ms, 15_000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn used_weight_near_limit_pauses_until_next_minute() {
+        let limiter = limiter(LimitHeaders::UsedWeight {
+            header: "x-mbx-used-weight-1m",
+            limit: 6000,
+        });
+
+        // 89% лимита — ещё можно
+        limiter.observe(StatusCode::OK, &headers(&[("x-mbx-used-weight-1m", "5340")]));
+        assert_eq!(waited(&limiter, 1).await, Duration::ZERO);
+        assert_eq!(limiter.stats().server_usage, Some(5340));
+
+        limiter.observe(StatusCode::OK, &headers(&[("X-MBX-USED-WEIGHT-1M", "5400")]));
+        let pause = waited(&limiter, 1).await;
+        assert!(pause > Duration::ZERO && pause <= Duration::from_secs(60), "{:?}", pause);
+
+        let stats = limiter.stats();
+        assert_eq!(stats.server_usage, Some(5400));
+        assert_eq!(stats.throttled, 1);
+        assert_eq!(stats.throttled_ms, pause.as_millis() as u64);
+        assert_eq!(stats.rejected, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn exhausted_remaining_pauses_until_reset() {
+        let limiter = limiter(LimitHeaders::Remaining {
+            header: "gw-ratelimit-remaining",
+            reset_header: "gw-ratelimit-reset",
+            reset_is_absolute: false,
+        });
+
+        limiter.observe(
+            StatusCode::OK,
+            &headers(&[("gw-ratelimit-remaining", "3"), ("gw-ratelimit-reset", "1500")]),
+        );
+        assert_eq!(waited(&limiter, 1).await, Duration::ZERO);
+
+        limiter.observe(
+            StatusCode::OK,
+            &headers(&[("gw-ratelimit-remaining", "0"), ("gw-ratelimit-reset", "1500")]),
+        );
+        assert_eq!(waited(&limiter, 1).await, Duration::from_millis(1500));
+        assert_eq!(limiter.stats().server_usage, Some(0));
+    }
+}
@@ -0,0 +1,192 @@
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Комиссия для бирж без расписания.
+pub const DEFAULT_FEE: Decimal = dec!(0.001);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Liquidity {
+    Maker,
+    Taker,
+}
+
+/// Ставки комиссии в долях: 0.001 = 0.1%.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeRates {
+    pub maker: Decimal,
+    pub taker: Decimal,
+}
+
+impl FeeRates {
+    pub fn get(&self, liquidity: Liquidity) -> Decimal {
+        match liquidity {
+            Liquidity::Maker => self.maker,
+            Liquidity::Taker => self.taker,
+        }
+    }
+}
+
+/// VIP-уровень: ставки действуют начиная с указанного 30-дневного объёма.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeTier {
+    pub min_volume_30d: Decimal,
+    pub maker: Decimal,
+    pub taker: Decimal,
+}
+
+/// Расписание комиссий биржи из конфигурации.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeSchedule {
+    pub maker: Decimal,
+    pub taker: Decimal,
+    /// Торговый объём аккаунта за 30 дней в USD, определяет VIP-уровень
+    pub volume_30d: Decimal,
+    pub tiers: Vec<FeeTier>,
+    /// Токен биржи для оплаты комиссий со скидкой (BNB, KCS, ...)
+    pub discount_token: Option<String>,
+    /// Скидка при оплате токеном, доля: 0.25 = 25%
+    pub token_discount: Decimal,
+    pub pay_with_token: bool,
+    /// Ставки для отдельных пар, перекрывают уровни
+    pub symbols: HashMap<String, FeeRates>,
+    /// Запрашивать фактические ставки аккаунта через приватный API
+    pub fetch_account_rates: bool,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self {
+            maker: DEFAULT_FEE,
+            taker: DEFAULT_FEE,
+            volume_30d: dec!(0),
+            tiers: Vec::new(),
+            discount_token: None,
+            token_discount: dec!(0),
+            pay_with_token: false,
+            symbols: HashMap::new(),
+            fetch_account_rates: false,
+        }
+    }
+}
+
+impl FeeSchedule {
+    /// Ставки уровня, соответствующего `volume_30d`.
+    pub fn tier_rates(&self) -> FeeRates {
+        self.tiers
+            .iter()
+            .filter(|tier| tier.min_volume_30d <= self.volume_30d)
+            .max_by_key(|tier| tier.min_volume_30d)
+            .map(|tier| FeeRates { maker: tier.maker, taker: tier.taker })
+            .unwrap_or(FeeRates { maker: self.maker, taker: self.taker })
+    }
+
+    fn discount_multiplier(&self) -> Decimal {
+        if self.pay_with_token && self.discount_token.is_some() {
+            dec!(1) - self.token_discount
+        } else {
+            dec!(1)
+        }
+    }
+}
+
+/// Ставки, полученные через приватный API биржи.
+#[derive(Debug, Clone, Default)]
+pub struct AccountFees {
+    pub default: Option<FeeRates>,
+    pub symbols: HashMap<String, FeeRates>,
+}
+
+/// Результат сделки «купить на одной бирже, продать на другой».
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct TradePnl {
+    pub gross: Decimal,
+    pub fees: Decimal,
+    pub net: Decimal,
+}
+
+/// Модель комиссий: расписания из конфигурации плюс фактические ставки аккаунта.
+/// Используется анализом, бэктестом и расчётом PnL.
+#[derive(Debug, Clone, Default)]
+pub struct FeeModel {
+    schedules: HashMap<String, FeeSchedule>,
+    account: HashMap<String, AccountFees>,
+}
+
+impl FeeModel {
+    pub fn new(schedules: HashMap<String, FeeSchedule>) -> Self {
+        Self {
+            schedules: schedules
+                .into_iter()
+                .map(|(name, schedule)| (name.to_lowercase(), schedule))
+                .collect(),
+            account: HashMap::new(),
+        }
+    }
+
+    pub fn with_account_fees(mut self, account: HashMap<String, AccountFees>) -> Self {
+        self.account = account
+            .into_iter()
+            .map(|(name, fees)| (name.to_lowercase(), fees))
+            .collect();
+        self
+    }
+
+    pub fn set_account_fees(&mut self, exchange: &str, fees: AccountFees) {
+        self.account.insert(exchange.to_lowercase(), fees);
+    }
+
+    /// Ставка комиссии. Порядок: ставка аккаунта по паре → ставка аккаунта по умолчанию →
+    /// ставка пары из конфигурации → VIP-уровень; затем скидка за оплату токеном.
+    pub fn rate(&self, exchange: &str, symbol: &str, liquidity: Liquidity) -> Decimal {
+        let exchange = exchange.to_lowercase();
+        let Some(schedule) = self.schedules.get(&exchange) else {
+            return DEFAULT_FEE;
+        };
+
+        let account = self.account.get(&exchange);
+        let rates = account
+            .and_then(|a| a.symbols.get(symbol).copied().or(a.default))
+            .or_else(|| schedule.symbols.get(symbol).copied())
+            .unwrap_or_else(|| schedule.tier_rates());
+
+        rates.get(liquidity) * schedule.discount_multiplier()
+    }
+
+    pub fn taker(&self, exchange: &str, symbol: &str) -> Decimal {
+        self.rate(exchange, symbol, Liquidity::Taker)
+    }
+
+    pub fn maker(&self, exchange: &str, symbol: &str) -> Decimal {
+        self.rate(exchange, symbol, Liquidity::Maker)
+    }
+
+    /// PnL покупки на `quote_amount` по `buy_price` и продажи того же количества
+    /// по `sell_price`, обе ноги — рыночные (taker) ордера.
+    pub fn round_trip_pnl(
+        &self,
+        symbol: &str,
+        buy_exchange: &str,
+        buy_price: Decimal,
+        sell_exchange: &str,
+        sell_price: Decimal,
+        quote_amount: Decimal,
+    ) -> TradePnl {
+        if buy_price <= dec!(0) {
+            return TradePnl::default();
+        }
+        let quantity = quote_amount / buy_price;
+        let proceeds = quantity * sell_price;
+        let fees = quote_amount * self.taker(buy_exchange, symbol)
+            + proceeds * self.taker(sell_exchange, symbol);
+        let gross = proceeds - quote_amount;
+
+        TradePnl {
+            gross,
+            fees,
+            net: gross - fees,
+        }
+    }
+}
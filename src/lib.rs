@@ -1,2 +1,16 @@
+pub mod alerts;
+pub mod api;
+pub mod analysis;
+pub mod cli;
+pub mod config;
 pub mod core;
-pub mod exchanges;
\ No newline at end of file
+pub mod exchanges;
+pub mod fees;
+pub mod metrics;
+pub mod monitoring;
+pub mod notifications;
+pub mod transfers;
+pub mod types;
+
+#[cfg(test)]
+mod testing;
@@ -1,67 +1,267 @@
-mod pairs;
-mod logger;
-
-use pairs::TradingPairs;
-use crossterm::{
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType},
-    cursor::{Hide, Show, MoveTo},
+use arbitrage_system::api;
+use arbitrage_system::cli::{Cli, Command, ExportFormat};
+use arbitrage_system::config::{self, Config, ConfigHandle, ConfigLoader, ConfigWatcher};
+use arbitrage_system::core::{
+    app::App,
+    backtest::{run_backtest, Replayer},
+    history::{read_history, write_csv, HistoryWriter},
+    logger::{self, log},
+    pairs::PairsManager,
+    scanner::Scanner,
 };
-use std::io::stdout;
-use chrono::Utc;
-use colored::*;
-use arbitrage_system::core::{app::App, logger::log};
+use arbitrage_system::notifications::{Notification, NotificationPriority, NotificationSystem};
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use log::{error, info, warn};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+type AppResult = Result<(), Box<dyn std::error::Error>>;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> AppResult {
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Monitor);
+
+    logger::init(cli.log_level.into(), matches!(command, Command::Monitor))?;
     log("Starting Arbitrage Monitor");
-    
-    let app = App::new("mobistyle");
-    app.run().await?;
-    
+
+    let loader = ConfigLoader::new(&cli.config)
+        .environment(cli.env.clone())
+        .overrides(cli.overrides.clone());
+    let (config, report) = match loader.load_validated() {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Configuration error: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    if matches!(command, Command::CheckConfig) {
+        print!("{}", serde_yaml::to_string(&config.redacted())?);
+        println!("\n{}", "─".repeat(70));
+        print!("{}", report);
+        let errors = report.errors().count();
+        println!("{}: {} errors, {} warnings", loader.path().display(), errors, report.warnings().count());
+        std::process::exit(if errors > 0 { 1 } else { 0 });
+    }
+
+    for warning in report.warnings() {
+        warn!("Config {}", warning);
+    }
+    if report.has_errors() {
+        for error in report.errors() {
+            eprintln!("{}", error);
+        }
+        eprintln!("Refusing to start: configuration has errors (run check-config for details)");
+        std::process::exit(2);
+    }
+
+    match command {
+        Command::Monitor => {
+            let handle = spawn_config_watcher(loader, config);
+            let app = App::new(&cli.user, handle.clone());
+            spawn_api(&handle.borrow(), app.scanner());
+            app.run().await
+        }
+        Command::Daemon { record } => {
+            let handle = spawn_config_watcher(loader, config);
+            run_daemon(handle, record).await
+        }
+        Command::ScanOnce { json } => scan_once(config, json).await,
+        Command::Backtest { input, trade_amount, json } => {
+            let points = read_history(&input)?;
+            let report = run_backtest(&points, &config, trade_amount);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("Backtest over {} price points ({} → {})",
+                    report.price_points,
+                    report.start.map(|t| t.to_rfc3339()).unwrap_or_default(),
+                    report.end.map(|t| t.to_rfc3339()).unwrap_or_default());
+                for (pair, stats) in &report.pairs {
+                    println!("  {:<10} opportunities: {:<5} best spread: {:.4}% fees: {:.4} net profit: {:.4}",
+                        pair, stats.opportunities, stats.best_spread, stats.fees, stats.profit);
+                }
+                println!("Total: {} opportunities, fees {:.4}, net profit {:.4} at {} per trade",
+                    report.opportunities, report.total_fees, report.total_profit, report.trade_amount);
+                let persistence = &report.persistence.total;
+                if persistence.count > 0 {
+                    println!("Persistence: {} opportunities lasted {:.1}s median, {:.1}s p90, {:.1}s max; spread decayed {:.4}% from peak on average",
+                        persistence.count, persistence.duration_p50_secs, persistence.duration_p90_secs,
+                        persistence.duration_max_secs, persistence.mean_decay);
+                    for (route, stats) in &report.persistence.by_route {
+                        println!("  {:<20} {:<5} median {:.1}s p90 {:.1}s peak {:.4}%",
+                            route, stats.count, stats.duration_p50_secs, stats.duration_p90_secs, stats.mean_peak_spread);
+                    }
+                }
+            }
+            Ok(())
+        }
+        Command::Replay { input, speed } => replay(&config, &input, speed).await,
+        Command::CheckConfig => unreachable!("handled above"),
+        Command::Export { input, output, format, symbol } => {
+            export(&input, output.as_deref(), format, symbol.as_deref())
+        }
+        Command::NotifyTest { channel } => notify_test(&config, channel.as_deref()).await,
+    }
+}
+
+/// Отправляет пробное уведомление в каналы; код выхода 1, если хоть один не принял.
+async fn notify_test(config: &Config, channel: Option<&str>) -> AppResult {
+    let notifier = NotificationSystem::from_config(config, &config.http_client()?);
+    let notification = Notification::new(
+        "Test notification",
+        "Notification channel is configured correctly",
+        NotificationPriority::Low,
+    );
+    let results = notifier.send_notification(&notification, channel).await;
+    if results.is_empty() {
+        eprintln!("No notification channels configured{}",
+            channel.map(|name| format!(" named {}", name)).unwrap_or_default());
+        std::process::exit(1);
+    }
+
+    let mut failed = false;
+    for (name, result) in results {
+        match result {
+            Ok(()) => println!("  ✓ {}", name),
+            Err(e) => {
+                failed = true;
+                println!("  ✗ {}: {}", name, e);
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
     Ok(())
 }
 
-    let start_time = Utc::now();
-    let mut counter = 0;
+fn spawn_config_watcher(loader: ConfigLoader, config: Config) -> ConfigHandle {
+    let (watcher, handle) = ConfigWatcher::new(loader, config);
+    tokio::spawn(watcher.run());
+    handle
+}
 
-    loop {
-        counter += 1;
-        let now = Utc::now();
-        
-        // Очистка экрана и обновление информации
-        execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
-
-        // Вывод шапки
-        println!("╔════════════════════════════════════════════════════════════════╗");
-        println!("║ 🤖 Arbitrage Monitor v1.0                                      ║");
-        println!("║ 👤 User: {:<52} ║", "mobistyle".bright_blue());
-        println!("║ 🕒 Started: {:<48} ║", 
-            start_time.format("%Y-%m-%d %H:%M:%S UTC"));
-        println!("║ ⌛ Uptime: {:<50} ║",
-            format!("{}", (now - start_time).num_seconds() / 3600).bright_yellow());
-        println!("║ 📊 {:<56} ║", trading_pairs.display_pair_info());
-        println!("╚════════════════════════════════════════════════════════════════╝\n");
-
-        // Обновление данных
-        println!("Update #{} - {}", 
-            counter.to_string().bright_yellow(),
-            now.format("%Y-%m-%d %H:%M:%S UTC").bright_blue()
-        );
-        println!("{}\n", "─".repeat(70));
-
-        // Вывод таблицы пар
-        TradingPairs::print_table_header();
+async fn scan_once(config: Config, json: bool) -> AppResult {
+    let pairs_manager = PairsManager::new();
+    let scanner = Scanner::new(config::fixed(config));
+    let result = scanner.scan().await;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result.opportunities)?);
+    } else {
+        PairsManager::print_table_header();
+        for opportunity in &result.opportunities {
+            println!("{}", pairs_manager.format_opportunity(opportunity));
+        }
         println!("└──────────┴────────────────────┴────────────────────┴──────────┴──────────┴──────────┘");
+        println!("{} opportunities, {} quotes, {} errors",
+            result.opportunities.len(), result.prices.len(), result.errors);
+    }
+    Ok(())
+}
 
-        // Статистика
-        println!("\n📈 Performance Stats:");
-        println!("  📊 Pairs monitored: {}", trading_pairs.get_pairs_count());
-        
-        // Индикатор обновления
-        print!("\n⏳ Next update in 1s... (Press Ctrl+C to exit)");
-        std::io::Write::flush(&mut stdout)?;
+/// Запускает HTTP API, если он включён; ошибка запуска не останавливает мониторинг.
+fn spawn_api(config: &Config, scanner: &Scanner) {
+    if !config.api.enabled {
+        return;
+    }
+    let Ok(addr) = config.api.listen.parse() else {
+        return;
+    };
+    let server = api::start_api_server(addr, scanner.metrics(), scanner.alerts(), scanner.tracker());
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            error!("API server on {} failed: {}", addr, e);
+        }
+    });
+}
+
+async fn run_daemon(config: ConfigHandle, record: Option<PathBuf>) -> AppResult {
+    let scanner = Scanner::new(config.clone());
+    spawn_api(&config.borrow(), &scanner);
+    let mut writer = record.as_deref().map(HistoryWriter::open).transpose()?;
+
+    info!("Daemon started: {} pairs, {} exchanges", scanner.pairs().len(), scanner.exchange_count());
+
+    // Ctrl+C прерывает и проход, и паузу между проходами
+    let shutdown = tokio::signal::ctrl_c();
+    tokio::pin!(shutdown);
+    loop {
+        let result = tokio::select! {
+            _ = &mut shutdown => break,
+            result = scanner.scan() => result,
+        };
+        for opp in &result.opportunities {
+            info!("{} buy {} @ {} sell {} @ {} spread {:.4}% (transfers {:.4}%)",
+                opp.symbols(), opp.buy_exchange, opp.buy_price,
+                opp.sell_exchange, opp.sell_price, opp.spread, opp.transfer_cost);
+            if let Some(warning) = &opp.transfer_warning {
+                warn!("{} {} → {}: {}", opp.symbols(), opp.buy_exchange, opp.sell_exchange, warning);
+            }
+        }
+        if let Some(writer) = writer.as_mut() {
+            if let Err(e) = writer.append(&result.prices) {
+                error!("Failed to record prices: {}", e);
+            }
+        }
+
+        let interval = Duration::from_millis(config.borrow().monitoring.update_interval_ms);
+        tokio::select! {
+            _ = &mut shutdown => break,
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+
+    info!("Received Ctrl+C, shutting down");
+    Ok(())
+}
+
+async fn replay(config: &Config, input: &Path, speed: f64) -> AppResult {
+    let points = read_history(input)?;
+    let mut replayer = Replayer::new(config);
+    let mut previous: Option<DateTime<Utc>> = None;
 
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    for point in &points {
+        if let (Some(prev), true) = (previous, speed > 0.0) {
+            let gap = (point.timestamp - prev).to_std().unwrap_or_default();
+            tokio::time::sleep(gap.div_f64(speed)).await;
+        }
+        previous = Some(point.timestamp);
+
+        if let Some(opp) = replayer.push(point) {
+            println!("[{}] {} buy {} @ {} sell {} @ {} spread {:.4}%",
+                opp.timestamp.format("%Y-%m-%d %H:%M:%S%.3f"),
+                opp.pair, opp.buy_exchange, opp.buy_price,
+                opp.sell_exchange, opp.sell_price, opp.spread);
+        }
+    }
+
+    println!("Replayed {} price points", points.len());
+    Ok(())
+}
+
+fn export(input: &Path, output: Option<&Path>, format: ExportFormat, symbol: Option<&str>) -> AppResult {
+    let mut points = read_history(input)?;
+    if let Some(symbol) = symbol {
+        points.retain(|p| p.symbol.eq_ignore_ascii_case(symbol));
     }
-}
\ No newline at end of file
+
+    let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(std::io::BufWriter::new(std::fs::File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    match format {
+        ExportFormat::Csv => write_csv(&mut out, &points)?,
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &points)?;
+            writeln!(out)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
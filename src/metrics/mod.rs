@@ -1,56 +1,507 @@
+pub mod prometheus;
+pub mod statistics;
+pub mod store;
+
+use crate::config::VolatilityConfig;
+use crate::exchanges::{CircuitState, ClockStats, ExchangeError, RateLimitStats};
+use crate::alerts::{AlertType, Delivery};
+use crate::analysis::{median, OpportunityLifecycle};
+use crate::types::{ArbitrageOpportunity, CrossableSpread, PricePoint};
+use prometheus::{Encoder, Histogram, LATENCY_BUCKETS, PERSISTENCE_BUCKETS};
 use std::sync::atomic::{AtomicU64, Ordering};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
+use serde::Serialize;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub use statistics::{RealizedVolatility, Regime, RegimeReading};
+pub use store::{Candle, PriceStore, RingBuffer, Sample};
+
+/// Точек в истории на пару и биржу.
+pub const HISTORY_CAPACITY: usize = 1000;
+/// Последних запросов к бирже, по которым считается её состояние.
+const HEALTH_WINDOW: usize = 100;
+
+/// Состояние биржи по последним запросам цен.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExchangeHealth {
+    /// Запросов в окне
+    pub requests: usize,
+    pub errors: usize,
+    /// Доля успешных запросов в окне
+    pub success_rate: f64,
+    /// Средняя задержка успешных запросов
+    #[serde(serialize_with = "serialize_millis")]
+    pub latency: Duration,
+    #[serde(serialize_with = "serialize_millis")]
+    pub p50_latency: Duration,
+    #[serde(serialize_with = "serialize_millis")]
+    pub p99_latency: Duration,
+    /// Ошибок подряд с последнего успешного запроса
+    pub consecutive_failures: u32,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Когда биржа последний раз отдала котировку, прошедшую фильтр качества
+    pub last_good_data: Option<DateTime<Utc>>,
+    pub circuit: CircuitState,
+    /// Почему биржа исключена из анализа
+    pub circuit_reason: Option<String>,
+}
 
 #[derive(Debug)]
-pub struct PricePoint {
-    pub price: Decimal,
-    pub timestamp: DateTime<Utc>,
+struct RequestOutcome {
+    latency: Duration,
+    success: bool,
+}
+
+#[derive(Debug)]
+struct HealthHistory {
+    outcomes: RingBuffer<RequestOutcome>,
+    consecutive_failures: u32,
+    last_success: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+    last_good_data: Option<DateTime<Utc>>,
+}
+
+impl HealthHistory {
+    fn new() -> Self {
+        Self {
+            outcomes: RingBuffer::new(HEALTH_WINDOW),
+            consecutive_failures: 0,
+            last_success: None,
+            last_error: None,
+            last_good_data: None,
+        }
+    }
 }
 
+/// Связка возможности для меток: `(pair, buy_exchange, sell_exchange)`.
+type Route = (String, String, String);
+
+/// Метрики системы: история цен по биржам, волатильность, состояние бирж и возможности.
+/// Все методы синхронные: блокировки держатся на время копирования, без `await`.
+///
+/// Биржи в ключах и метках — в нижнем регистре, как в конфигурации.
 #[derive(Debug)]
 pub struct MetricsCollector {
+    started_at: Instant,
     total_opportunities: AtomicU64,
     total_volume_checked: AtomicU64,
-    price_history: RwLock<HashMap<String, Vec<PricePoint>>>,
-    profit_history: RwLock<Vec<(DateTime<Utc>, Decimal)>>,
+    price_history: PriceStore,
+    profit_history: RwLock<RingBuffer<(DateTime<Utc>, Decimal)>>,
+    health: RwLock<HashMap<String, HealthHistory>>,
+    opportunities: RwLock<HashMap<Route, u64>>,
+    /// Длительность закрытых возможностей по связке бирж `(buy, sell)`
+    persistence: RwLock<HashMap<(String, String), Histogram>>,
+    active_opportunities: AtomicU64,
+    /// Лучший спред по паре за последний проход
+    best_spreads: RwLock<HashMap<String, Decimal>>,
+    /// Исполнимый спред по вершинам стаканов, по паре
+    crossable_spreads: RwLock<HashMap<String, CrossableSpread>>,
+    fetch_latency: RwLock<HashMap<String, Histogram>>,
+    fetch_errors: RwLock<HashMap<(String, &'static str), u64>>,
+    rate_limits: RwLock<Vec<(String, RateLimitStats)>>,
+    clocks: RwLock<Vec<(String, ClockStats)>>,
+    /// Состояние предохранителей и причина открытия
+    circuits: RwLock<HashMap<String, (CircuitState, Option<String>)>>,
+    /// Последняя оценка режима по паре
+    regimes: RwLock<HashMap<String, RegimeReading>>,
+    alerts: RwLock<HashMap<AlertType, u64>>,
+    /// Повторы, подавленные AlertManager
+    suppressed_alerts: RwLock<HashMap<AlertType, u64>>,
+    silenced_alerts: RwLock<HashMap<AlertType, u64>>,
+}
+
+impl Default for MetricsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MetricsCollector {
     pub fn new() -> Self {
         Self {
+            started_at: Instant::now(),
             total_opportunities: AtomicU64::new(0),
             total_volume_checked: AtomicU64::new(0),
-            price_history: RwLock::new(HashMap::new()),
-            profit_history: RwLock::new(Vec::new()),
+            price_history: PriceStore::new(HISTORY_CAPACITY),
+            profit_history: RwLock::new(RingBuffer::new(HISTORY_CAPACITY)),
+            health: RwLock::new(HashMap::new()),
+            opportunities: RwLock::new(HashMap::new()),
+            persistence: RwLock::new(HashMap::new()),
+            active_opportunities: AtomicU64::new(0),
+            best_spreads: RwLock::new(HashMap::new()),
+            crossable_spreads: RwLock::new(HashMap::new()),
+            fetch_latency: RwLock::new(HashMap::new()),
+            fetch_errors: RwLock::new(HashMap::new()),
+            rate_limits: RwLock::new(Vec::new()),
+            clocks: RwLock::new(Vec::new()),
+            circuits: RwLock::new(HashMap::new()),
+            regimes: RwLock::new(HashMap::new()),
+            alerts: RwLock::new(HashMap::new()),
+            suppressed_alerts: RwLock::new(HashMap::new()),
+            silenced_alerts: RwLock::new(HashMap::new()),
         }
     }
 
-    pub fn record_price(&self, symbol: &str, price: Decimal) {
-        let mut history = self.price_history.write();
-        let entry = history.entry(symbol.to_string())
-            .or_insert_with(Vec::new);
-        
-        entry.push(PricePoint {
-            price,
-            timestamp: Utc::now(),
+    pub fn record_price(&self, point: &PricePoint) {
+        self.price_history.insert(
+            &point.exchange.to_lowercase(),
+            &point.symbol,
+            Sample {
+                timestamp: point.timestamp,
+                price: point.price,
+                volume: point.volume,
+            },
+        );
+    }
+
+    /// Учитывает запрос цены к бирже: задержку и исход. Отсутствие пары на бирже
+    /// считается ошибкой в метриках, но не ухудшает состояние биржи.
+    pub fn record_request(&self, exchange: &str, latency: Duration, error: Option<&ExchangeError>) {
+        let exchange = exchange.to_lowercase();
+        self.fetch_latency
+            .write()
+            .entry(exchange.clone())
+            .or_insert_with(|| Histogram::new(&LATENCY_BUCKETS))
+            .observe(latency.as_secs_f64());
+        if let Some(error) = error {
+            *self.fetch_errors.write().entry((exchange.clone(), error.kind())).or_default() += 1;
+        }
+
+        let error = error
+            .filter(|e| !matches!(e, ExchangeError::SymbolNotFound { .. }))
+            .map(ToString::to_string);
+        let mut health = self.health.write();
+        let history = health.entry(exchange).or_insert_with(HealthHistory::new);
+        history.outcomes.push(RequestOutcome {
+            latency,
+            success: error.is_none(),
         });
+        match error {
+            Some(error) => {
+                history.consecutive_failures += 1;
+                history.last_error = Some(error);
+            }
+            None => {
+                history.consecutive_failures = 0;
+                history.last_success = Some(Utc::now());
+            }
+        }
+    }
+
+    /// Биржа отдала котировку, прошедшую фильтр качества.
+    pub fn record_good_data(&self, exchange: &str) {
+        self.health
+            .write()
+            .entry(exchange.to_lowercase())
+            .or_insert_with(HealthHistory::new)
+            .last_good_data = Some(Utc::now());
+    }
 
-        if entry.len() > 1000 {
-            entry.remove(0);
+    /// Ряды цен по биржам и парам.
+    pub fn price_history(&self) -> &PriceStore {
+        &self.price_history
+    }
+
+    /// Последние `n` цен пары на бирже, от старых к новым.
+    pub fn get_price_history(&self, exchange: &str, symbol: &str, n: usize) -> Vec<Sample> {
+        self.price_history.last_n(exchange, symbol, n)
+    }
+
+    /// Последняя цена пары по биржам.
+    pub fn get_all_current_prices(&self, symbol: &str) -> HashMap<String, Decimal> {
+        self.price_history
+            .latest_by_exchange(symbol)
+            .into_iter()
+            .map(|(exchange, sample)| (exchange, sample.price))
+            .collect()
+    }
+
+    /// Реализованная волатильность пары на бирже за последние `window`.
+    pub fn realized_volatility(&self, exchange: &str, symbol: &str, window: Duration) -> Option<RealizedVolatility> {
+        let window = chrono::Duration::from_std(window).ok()?;
+        statistics::realized_volatility(&self.price_history.last_window(&exchange.to_lowercase(), symbol, window))
+    }
+
+    /// Средний истинный диапазон свечей длины `candle` за последние `window`, % от цены.
+    pub fn average_true_range(&self, exchange: &str, symbol: &str, candle: Duration, window: Duration) -> Option<Decimal> {
+        let candle = chrono::Duration::from_std(candle).ok()?;
+        let window = chrono::Duration::from_std(window).ok()?;
+        let candles = self
+            .price_history
+            .ohlc(&exchange.to_lowercase(), symbol, Utc::now() - window, candle);
+        statistics::average_true_range(&candles)
+    }
+
+    /// Переоценивает режим пары по медианам волатильности и ATR между биржами.
+    /// Возвращает новую оценку и прежний режим; `None`, пока истории не хватает.
+    pub fn update_regime(&self, symbol: &str, config: &VolatilityConfig) -> Option<(RegimeReading, Option<Regime>)> {
+        let short = Duration::from_secs(config.short_window_secs);
+        let long = Duration::from_secs(config.long_window_secs);
+        let candle = Duration::from_secs(config.atr_candle_secs);
+
+        let exchanges: Vec<String> = self.price_history.latest_by_exchange(symbol).into_keys().collect();
+        let mut short_volatility = Vec::new();
+        let mut long_volatility = Vec::new();
+        let mut atr = Vec::new();
+        for exchange in &exchanges {
+            let (Some(recent), Some(background)) = (
+                self.realized_volatility(exchange, symbol, short),
+                self.realized_volatility(exchange, symbol, long),
+            ) else {
+                continue;
+            };
+            short_volatility.push(recent.hourly);
+            long_volatility.push(background.hourly);
+            atr.extend(self.average_true_range(exchange, symbol, candle, long));
         }
+
+        let short_volatility = median(short_volatility)?;
+        let long_volatility = median(long_volatility)?;
+        let reading = RegimeReading {
+            regime: Regime::classify(short_volatility, long_volatility, config.calm_ratio, config.turbulent_ratio),
+            short_volatility,
+            long_volatility,
+            atr: median(atr),
+        };
+        let previous = self
+            .regimes
+            .write()
+            .insert(symbol.to_string(), reading)
+            .map(|previous| previous.regime);
+        Some((reading, previous))
+    }
+
+    /// Последние оценки режима по парам.
+    pub fn regimes(&self) -> Vec<(String, RegimeReading)> {
+        sorted(&self.regimes.read())
+    }
+
+    /// Режим пары; пока не оценён — обычный.
+    pub fn regime(&self, symbol: &str) -> Regime {
+        self.regimes.read().get(symbol).map_or(Regime::Normal, |reading| reading.regime)
+    }
+
+    pub fn get_exchange_health(&self, exchange: &str) -> Option<ExchangeHealth> {
+        let health = self.health.read();
+        let history = health.get(&exchange.to_lowercase())?;
+
+        let requests = history.outcomes.len();
+        let mut successful: Vec<Duration> = history
+            .outcomes
+            .iter()
+            .filter(|o| o.success)
+            .map(|o| o.latency)
+            .collect();
+        successful.sort();
+        let latency = match successful.len() {
+            0 => Duration::ZERO,
+            n => successful.iter().sum::<Duration>() / n as u32,
+        };
+        let (circuit, circuit_reason) = self
+            .circuits
+            .read()
+            .get(&exchange.to_lowercase())
+            .cloned()
+            .unwrap_or((CircuitState::Closed, None));
+
+        Some(ExchangeHealth {
+            requests,
+            errors: requests - successful.len(),
+            success_rate: if requests == 0 { 1.0 } else { successful.len() as f64 / requests as f64 },
+            latency,
+            p50_latency: percentile(&successful, 0.5),
+            p99_latency: percentile(&successful, 0.99),
+            consecutive_failures: history.consecutive_failures,
+            last_success: history.last_success,
+            last_error: history.last_error.clone(),
+            last_good_data: history.last_good_data,
+            circuit,
+            circuit_reason,
+        })
     }
 
-    pub fn record_opportunity(&self, profit: Decimal) {
+    /// Состояние всех бирж, по которым были запросы.
+    pub fn exchange_health(&self) -> Vec<(String, ExchangeHealth)> {
+        let mut names: Vec<String> = self.health.read().keys().cloned().collect();
+        names.sort();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let health = self.get_exchange_health(&name)?;
+                Some((name, health))
+            })
+            .collect()
+    }
+
+    /// Учитывает новую возможность; повторные наблюдения той же связки не считаются.
+    pub fn record_opportunity(&self, opportunity: &ArbitrageOpportunity) {
         self.total_opportunities.fetch_add(1, Ordering::SeqCst);
-        let mut history = self.profit_history.write();
-        history.push((Utc::now(), profit));
+        self.profit_history.write().push((Utc::now(), opportunity.potential_profit));
+        let route = (
+            opportunity.symbols(),
+            opportunity.buy_exchange.to_lowercase(),
+            opportunity.sell_exchange.to_lowercase(),
+        );
+        *self.opportunities.write().entry(route).or_default() += 1;
+    }
+
+    /// Учитывает закрытую возможность: сколько она продержалась.
+    pub fn record_opportunity_closed(&self, lifecycle: &OpportunityLifecycle) {
+        self.persistence
+            .write()
+            .entry((lifecycle.buy_exchange.to_lowercase(), lifecycle.sell_exchange.to_lowercase()))
+            .or_insert_with(|| Histogram::new(&PERSISTENCE_BUCKETS))
+            .observe(lifecycle.duration_secs());
+    }
 
-        if history.len() > 1000 {
-            history.remove(0);
+    pub fn set_active_opportunities(&self, count: usize) {
+        self.active_opportunities.store(count as u64, Ordering::SeqCst);
+    }
+
+    /// Лучшие спреды по парам за проход; пары без возможностей из метрики пропадают.
+    pub fn set_best_spreads(&self, spreads: HashMap<String, Decimal>) {
+        *self.best_spreads.write() = spreads;
+    }
+
+    /// Исполнимый спред пары; `None` убирает пару из метрики, когда стаканов меньше двух.
+    pub fn set_crossable_spread(&self, symbol: &str, spread: Option<CrossableSpread>) {
+        let mut spreads = self.crossable_spreads.write();
+        match spread {
+            Some(spread) => spreads.insert(symbol.to_string(), spread),
+            None => spreads.remove(symbol),
+        };
+    }
+
+    /// Исполнимые спреды по парам.
+    pub fn crossable_spreads(&self) -> Vec<(String, CrossableSpread)> {
+        sorted(&self.crossable_spreads.read())
+    }
+
+    /// Снимок счётчиков ограничителей и часов бирж.
+    pub fn set_exchange_stats(&self, rate_limits: Vec<(String, RateLimitStats)>, clocks: Vec<(String, ClockStats)>) {
+        *self.rate_limits.write() = rate_limits;
+        *self.clocks.write() = clocks;
+    }
+
+    /// Снимок предохранителей бирж: состояние и причина открытия.
+    pub fn set_circuit_states(&self, circuits: Vec<(String, CircuitState, Option<String>)>) {
+        *self.circuits.write() = circuits
+            .into_iter()
+            .map(|(exchange, state, reason)| (exchange.to_lowercase(), (state, reason)))
+            .collect();
+    }
+
+    pub fn record_alert(&self, alert_type: AlertType, delivery: Delivery) {
+        let counters = match delivery {
+            Delivery::Sent | Delivery::Escalated => &self.alerts,
+            Delivery::Suppressed => &self.suppressed_alerts,
+            Delivery::Silenced => &self.silenced_alerts,
+        };
+        *counters.write().entry(alert_type).or_default() += 1;
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Все метрики в текстовом формате Prometheus.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = Encoder::new();
+
+        out.family("arb_uptime_seconds", "gauge", "Seconds since the process started");
+        out.sample("arb_uptime_seconds", &[], self.uptime().as_secs_f64());
+
+        out.family("arb_opportunities_total", "counter", "Arbitrage opportunities opened");
+        for ((pair, buy, sell), count) in sorted(&self.opportunities.read()) {
+            out.sample("arb_opportunities_total", &[("pair", &pair), ("buy_exchange", &buy), ("sell_exchange", &sell)], count as f64);
+        }
+
+        out.family("arb_opportunities_active", "gauge", "Opportunities present in the last scan");
+        out.sample("arb_opportunities_active", &[], self.active_opportunities.load(Ordering::SeqCst) as f64);
+
+        out.family("arb_opportunity_duration_seconds", "histogram", "How long closed opportunities persisted across scans");
+        for ((buy, sell), histogram) in sorted(&self.persistence.read()) {
+            out.histogram("arb_opportunity_duration_seconds", &[("buy_exchange", &buy), ("sell_exchange", &sell)], &histogram);
+        }
+
+        out.family("arb_best_spread_percent", "gauge", "Best net spread per pair in the last scan");
+        for (pair, spread) in sorted(&self.best_spreads.read()) {
+            out.sample("arb_best_spread_percent", &[("pair", &pair)], spread.to_f64().unwrap_or(f64::NAN));
+        }
+
+        let crossable = self.crossable_spreads();
+        out.family("arb_crossable_spread_percent", "gauge", "Best bid on one venue over best ask on another, per pair");
+        for (pair, spread) in &crossable {
+            let labels = [("pair", pair.as_str()), ("buy_exchange", &spread.buy_exchange.to_lowercase()), ("sell_exchange", &spread.sell_exchange.to_lowercase())];
+            out.sample("arb_crossable_spread_percent", &labels, spread.spread.to_f64().unwrap_or(f64::NAN));
+        }
+        out.family("arb_crossable_size", "gauge", "Base asset size available at both top-of-book prices, per pair");
+        for (pair, spread) in &crossable {
+            out.sample("arb_crossable_size", &[("pair", pair)], spread.size.to_f64().unwrap_or(f64::NAN));
         }
+
+        out.family("arb_fetch_latency_seconds", "histogram", "Price request latency per exchange");
+        for (exchange, histogram) in sorted(&self.fetch_latency.read()) {
+            out.histogram("arb_fetch_latency_seconds", &[("exchange", &exchange)], &histogram);
+        }
+
+        out.family("arb_fetch_errors_total", "counter", "Failed price requests per exchange and error kind");
+        for ((exchange, kind), count) in sorted(&self.fetch_errors.read()) {
+            out.sample("arb_fetch_errors_total", &[("exchange", &exchange), ("kind", kind)], count as f64);
+        }
+
+        let rate_limits = self.rate_limits.read();
+        out.family("arb_rate_limit_throttled_total", "counter", "Requests delayed by the client-side rate limiter");
+        for (exchange, stats) in rate_limits.iter() {
+            out.sample("arb_rate_limit_throttled_total", &[("exchange", &exchange.to_lowercase())], stats.throttled as f64);
+        }
+        out.family("arb_rate_limit_rejected_total", "counter", "Responses rejected by the exchange with 429/418");
+        for (exchange, stats) in rate_limits.iter() {
+            out.sample("arb_rate_limit_rejected_total", &[("exchange", &exchange.to_lowercase())], stats.rejected as f64);
+        }
+
+        let clocks = self.clocks.read();
+        out.family("arb_clock_offset_seconds", "gauge", "Exchange clock minus local clock");
+        for (exchange, clock) in clocks.iter() {
+            if let Some(offset) = clock.offset_ms {
+                out.sample("arb_clock_offset_seconds", &[("exchange", &exchange.to_lowercase())], offset as f64 / 1000.0);
+            }
+        }
+
+        let regimes = self.regimes();
+        out.family("arb_realized_volatility_percent", "gauge", "Median short-window realized volatility per pair, percent per sqrt(hour)");
+        for (pair, reading) in &regimes {
+            out.sample("arb_realized_volatility_percent", &[("pair", pair)], reading.short_volatility);
+        }
+        out.family("arb_market_regime", "gauge", "Market regime per pair: 0 calm, 1 normal, 2 turbulent");
+        for (pair, reading) in &regimes {
+            out.sample("arb_market_regime", &[("pair", pair)], reading.regime.as_gauge());
+        }
+
+        out.family("arb_circuit_state", "gauge", "Exchange circuit breaker: 0 closed, 1 half-open, 2 open");
+        for (exchange, (state, _)) in sorted(&self.circuits.read()) {
+            out.sample("arb_circuit_state", &[("exchange", &exchange)], state.as_gauge());
+        }
+
+        out.family("arb_alerts_total", "counter", "Alerts raised per type");
+        for (kind, count) in sorted(&self.alerts.read()) {
+            out.sample("arb_alerts_total", &[("type", kind.as_str())], count as f64);
+        }
+        out.family("arb_alerts_suppressed_total", "counter", "Repeated alerts suppressed by cooldown per type");
+        for (kind, count) in sorted(&self.suppressed_alerts.read()) {
+            out.sample("arb_alerts_suppressed_total", &[("type", kind.as_str())], count as f64);
+        }
+        out.family("arb_alerts_silenced_total", "counter", "Alerts dropped by silences per type");
+        for (kind, count) in sorted(&self.silenced_alerts.read()) {
+            out.sample("arb_alerts_silenced_total", &[("type", kind.as_str())], count as f64);
+        }
+
+        out.finish()
     }
 
     pub fn get_statistics(&self) -> HashMap<String, String> {
@@ -66,3 +517,23 @@ impl MetricsCollector {
         stats
     }
 }
+
+/// Пары ключ-значение в порядке ключей: вывод `/metrics` стабилен между запросами.
+fn sorted<K: Ord + Clone, V: Clone>(map: &HashMap<K, V>) -> Vec<(K, V)> {
+    let mut entries: Vec<(K, V)> = map.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+/// Перцентиль по отсортированной выборке, ближайший ранг.
+fn percentile(sorted: &[Duration], q: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (q * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn serialize_millis<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
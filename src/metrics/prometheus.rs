@@ -0,0 +1,108 @@
+use std::fmt::Write;
+
+/// Границы корзин гистограммы задержек, секунды.
+pub const LATENCY_BUCKETS: [f64; 9] = [0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+/// Границы корзин длительности возможностей, секунды.
+pub const PERSISTENCE_BUCKETS: [f64; 10] = [0.0, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 180.0, 600.0, 1800.0];
+
+/// Гистограмма с фиксированными корзинами в формате Prometheus.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    /// Наблюдений в каждой корзине (не накопительно)
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        if let Some(bucket) = self.bounds.iter().position(|bound| value <= *bound) {
+            self.counts[bucket] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Собирает ответ `/metrics` в текстовом формате Prometheus 0.0.4.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    out: String,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Заголовок семейства: `# HELP` и `# TYPE` (`counter`, `gauge`, `histogram`).
+    pub fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+    }
+
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Into<f64>) {
+        self.line(name, labels, None, value.into());
+    }
+
+    pub fn histogram(&mut self, name: &str, labels: &[(&str, &str)], histogram: &Histogram) {
+        let bucket = format!("{}_bucket", name);
+        let mut cumulative = 0;
+        for (bound, count) in histogram.bounds.iter().zip(&histogram.counts) {
+            cumulative += count;
+            self.line(&bucket, labels, Some(&format_value(*bound)), cumulative as f64);
+        }
+        self.line(&bucket, labels, Some("+Inf"), histogram.count as f64);
+        self.line(&format!("{}_sum", name), labels, None, histogram.sum);
+        self.line(&format!("{}_count", name), labels, None, histogram.count as f64);
+    }
+
+    fn line(&mut self, name: &str, labels: &[(&str, &str)], le: Option<&str>, value: f64) {
+        self.out.push_str(name);
+        let le = le.map(|le| ("le", le));
+        let mut labels = labels.iter().copied().chain(le).peekable();
+        if labels.peek().is_some() {
+            self.out.push('{');
+            for (i, (key, value)) in labels.enumerate() {
+                if i > 0 {
+                    self.out.push(',');
+                }
+                let _ = write!(self.out, "{}=\"{}\"", key, escape(value));
+            }
+            self.out.push('}');
+        }
+        let _ = writeln!(self.out, " {}", format_value(value));
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,275 @@
+use super::{Candle, Sample};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+
+const SECONDS_PER_HOUR: f64 = 3600.0;
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * SECONDS_PER_HOUR;
+/// Меньше доходностей — оценка волатильности ничего не значит.
+const MIN_RETURNS: usize = 3;
+
+/// Реализованная волатильность по логарифмическим доходностям. Точки приходят
+/// с неравными интервалами, поэтому дисперсия делится на суммарное время, а не на
+/// число доходностей: `σ² = Σ r² / Σ Δt`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RealizedVolatility {
+    /// Доходностей в оценке
+    pub returns: usize,
+    /// Интервал, покрытый точками, секунды
+    pub span_secs: f64,
+    /// В процентах за √час
+    pub hourly: f64,
+    /// В процентах за √год
+    pub annualized: f64,
+}
+
+pub fn realized_volatility(samples: &[Sample]) -> Option<RealizedVolatility> {
+    let mut returns = 0;
+    let mut sum_squares = 0.0;
+    let mut span_secs = 0.0;
+    for pair in samples.windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+        let dt = (next.timestamp - prev.timestamp).num_milliseconds() as f64 / 1000.0;
+        let (Some(p0), Some(p1)) = (prev.price.to_f64(), next.price.to_f64()) else {
+            continue;
+        };
+        // Повтор котировки с тем же временем биржи — не новая точка
+        if dt <= 0.0 || p0 <= 0.0 || p1 <= 0.0 {
+            continue;
+        }
+        let r = (p1 / p0).ln();
+        sum_squares += r * r;
+        span_secs += dt;
+        returns += 1;
+    }
+    if returns < MIN_RETURNS || span_secs <= 0.0 {
+        return None;
+    }
+
+    let rate = sum_squares / span_secs;
+    Some(RealizedVolatility {
+        returns,
+        span_secs,
+        hourly: (rate * SECONDS_PER_HOUR).sqrt() * 100.0,
+        annualized: (rate * SECONDS_PER_YEAR).sqrt() * 100.0,
+    })
+}
+
+/// Средний истинный диапазон свечей в процентах от последнего закрытия.
+/// Истинный диапазон учитывает разрыв с закрытием предыдущей свечи:
+/// `max(high − low, |high − prev_close|, |low − prev_close|)`.
+pub fn average_true_range(candles: &[Candle]) -> Option<Decimal> {
+    let last = candles.last()?;
+    if last.close <= dec!(0) {
+        return None;
+    }
+
+    let mut prev_close: Option<Decimal> = None;
+    let mut total = dec!(0);
+    for candle in candles {
+        let range = candle.high - candle.low;
+        let range = match prev_close {
+            Some(close) => range.max((candle.high - close).abs()).max((candle.low - close).abs()),
+            None => range,
+        };
+        total += range;
+        prev_close = Some(candle.close);
+    }
+    let atr = total / Decimal::from(candles.len());
+    Some((atr / last.close * dec!(100)).round_dp(4))
+}
+
+/// Режим рынка по паре: отношение недавней волатильности к фоновой.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Regime {
+    Calm,
+    Normal,
+    Turbulent,
+}
+
+impl Regime {
+    /// `short / long` ниже `calm_ratio` — затишье, выше `turbulent_ratio` — турбулентность.
+    pub fn classify(short: f64, long: f64, calm_ratio: f64, turbulent_ratio: f64) -> Self {
+        if long <= 0.0 {
+            return if short > 0.0 { Regime::Turbulent } else { Regime::Calm };
+        }
+        let ratio = short / long;
+        if ratio >= turbulent_ratio {
+            Regime::Turbulent
+        } else if ratio <= calm_ratio {
+            Regime::Calm
+        } else {
+            Regime::Normal
+        }
+    }
+
+    /// Значение для метрик: 0 — calm, 1 — normal, 2 — turbulent.
+    pub fn as_gauge(self) -> u8 {
+        match self {
+            Regime::Calm => 0,
+            Regime::Normal => 1,
+            Regime::Turbulent => 2,
+        }
+    }
+}
+
+/// Оценка режима пары: медианы по биржам.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RegimeReading {
+    pub regime: Regime,
+    /// Волатильность за короткое окно, % за √час
+    pub short_volatility: f64,
+    /// Волатильность за длинное окно, % за √час
+    pub long_volatility: f64,
+    /// Средний истинный диапазон свечи, % от цены
+    pub atr: Option<Decimal>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    /// Точки `(секунды от начала, цена)`.
+    fn samples(points: &[(i64, Decimal)]) -> Vec<Sample> {
+        let start = Utc::now();
+        points
+            .iter()
+            .map(|&(secs, price)| Sample {
+                timestamp: start + Duration::seconds(secs),
+                price,
+                volume: None,
+            })
+            .collect()
+    }
+
+    fn candle(high: Decimal, low: Decimal, close: Decimal) -> Candle {
+        Candle {
+            start: Utc::now(),
+            open: close,
+            high,
+            low,
+            close,
+            count: 1,
+        }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn volatility_normalizes_log_returns_by_elapsed_time() {
+        // Три доходности ±ln(1.1) за час: σ² = 3·ln²(1.1) за час
+        let expected = 1.1f64.ln() * 3f64.sqrt() * 100.0;
+        let even = realized_volatility(&samples(&[
+            (0, dec!(100)),
+            (1200, dec!(110)),
+            (2400, dec!(100)),
+            (3600, dec!(110)),
+        ]))
+        .unwrap();
+        assert_eq!(even.returns, 3);
+        assert_close(even.span_secs, 3600.0);
+        assert_close(even.hourly, expected);
+        assert_close(even.annualized, expected * (365.0f64 * 24.0).sqrt());
+
+        // Неравные интервалы с тем же суммарным временем дают ту же оценку
+        let uneven = realized_volatility(&samples(&[
+            (0, dec!(100)),
+            (600, dec!(110)),
+            (2400, dec!(100)),
+            (3600, dec!(110)),
+        ]))
+        .unwrap();
+        assert_close(uneven.hourly, expected);
+
+        // Те же доходности за вдвое большее время — волатильность меньше в √2 раз
+        let slow = realized_volatility(&samples(&[
+            (0, dec!(100)),
+            (2400, dec!(110)),
+            (4800, dec!(100)),
+            (7200, dec!(110)),
+        ]))
+        .unwrap();
+        assert_close(slow.hourly, expected / 2f64.sqrt());
+    }
+
+    #[test]
+    fn volatility_skips_repeated_timestamps_and_bad_prices() {
+        let volatility = realized_volatility(&samples(&[
+            (0, dec!(100)),
+            (1200, dec!(110)),
+            (1200, dec!(110)),
+            (2400, dec!(100)),
+            (3000, dec!(0)),
+            (3600, dec!(110)),
+            (4200, dec!(100)),
+        ]))
+        .unwrap();
+        // Из шести пар остаются три: повтор времени и обе пары с нулевой ценой отброшены,
+        // их интервалы в суммарное время не входят
+        assert_eq!(volatility.returns, 3);
+        assert_close(volatility.span_secs, 3000.0);
+    }
+
+    #[test]
+    fn volatility_needs_enough_returns() {
+        assert_eq!(realized_volatility(&[]), None);
+        assert_eq!(realized_volatility(&samples(&[(0, dec!(100))])), None);
+        assert_eq!(
+            realized_volatility(&samples(&[(0, dec!(100)), (60, dec!(101)), (120, dec!(102))])),
+            None
+        );
+        assert!(realized_volatility(&samples(&[
+            (0, dec!(100)),
+            (60, dec!(101)),
+            (120, dec!(102)),
+            (180, dec!(103)),
+        ]))
+        .is_some());
+    }
+
+    #[test]
+    fn atr_counts_gaps_from_previous_close() {
+        let candles = [
+            candle(dec!(105), dec!(95), dec!(100)),
+            // Разрыв вверх: 120 − 100 больше диапазона свечи 5
+            candle(dec!(120), dec!(115), dec!(118)),
+            // Разрыв вниз: |96 − 118| больше диапазона свечи 4
+            candle(dec!(100), dec!(96), dec!(98)),
+        ];
+        // (10 + 20 + 22) / 3 / 98 · 100
+        assert_eq!(average_true_range(&candles), Some(dec!(17.6871)));
+
+        // Без разрывов ATR — средний диапазон свечи
+        let candles = [candle(dec!(102), dec!(98), dec!(100)), candle(dec!(101), dec!(99), dec!(100))];
+        assert_eq!(average_true_range(&candles), Some(dec!(3)));
+    }
+
+    #[test]
+    fn atr_needs_a_priced_close() {
+        assert_eq!(average_true_range(&[]), None);
+        assert_eq!(average_true_range(&[candle(dec!(1), dec!(0), dec!(0))]), None);
+    }
+
+    #[test]
+    fn classify_thresholds_are_inclusive() {
+        let cases = [
+            (0.4, 1.0, Regime::Calm),
+            (0.5, 1.0, Regime::Calm),
+            (0.51, 1.0, Regime::Normal),
+            (1.99, 1.0, Regime::Normal),
+            (2.0, 1.0, Regime::Turbulent),
+            (3.0, 1.0, Regime::Turbulent),
+            // Без фоновой волатильности любое движение — турбулентность
+            (0.1, 0.0, Regime::Turbulent),
+            (0.0, 0.0, Regime::Calm),
+        ];
+        for (short, long, expected) in cases {
+            assert_eq!(Regime::classify(short, long, 0.5, 2.0), expected, "short {} long {}", short, long);
+        }
+    }
+}
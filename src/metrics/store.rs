@@ -0,0 +1,321 @@
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+/// Шардов по умолчанию: запись по разным парам почти не конкурирует за блокировку.
+const DEFAULT_SHARDS: usize = 16;
+
+/// Точка ряда цен одной пары на одной бирже.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Sample {
+    pub timestamp: DateTime<Utc>,
+    pub price: Decimal,
+    pub volume: Option<Decimal>,
+}
+
+/// Свеча за интервал агрегации.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candle {
+    /// Начало интервала, кратное его длине
+    pub start: DateTime<Utc>,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// Точек в интервале
+    pub count: usize,
+}
+
+/// Кольцевой буфер фиксированной ёмкости: при заполнении вытесняется самая старая точка.
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        if self.items.len() == self.capacity {
+            self.items.pop_front();
+        }
+        self.items.push_back(item);
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// От старых к новым.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut T> + ExactSizeIterator {
+        self.items.iter_mut()
+    }
+}
+
+impl RingBuffer<Sample> {
+    /// Вставка с сохранением порядка по времени; запоздавшая точка встаёт на своё место.
+    fn insert_sample(&mut self, sample: Sample) {
+        if self.items.back().is_none_or(|last| last.timestamp <= sample.timestamp) {
+            self.push(sample);
+            return;
+        }
+        let index = self.items.partition_point(|s| s.timestamp <= sample.timestamp);
+        if index == 0 && self.items.len() == self.capacity {
+            // Старше всего буфера — вытеснилась бы сразу
+            return;
+        }
+        if self.items.len() == self.capacity {
+            self.items.pop_front();
+            self.items.insert(index - 1, sample);
+        } else {
+            self.items.insert(index, sample);
+        }
+    }
+
+    /// Индекс первой точки не старше `since`.
+    fn position_since(&self, since: DateTime<Utc>) -> usize {
+        self.items.partition_point(|s| s.timestamp < since)
+    }
+}
+
+/// `биржа → пара → ряд`: поиск по `&str` без выделения ключа.
+type Shard = RwLock<HashMap<String, HashMap<String, RingBuffer<Sample>>>>;
+
+/// Хранилище рядов цен по `(биржа, пара)`: кольцевой буфер на ряд, ряды разложены
+/// по шардам с отдельными блокировками.
+#[derive(Debug)]
+pub struct PriceStore {
+    shards: Vec<Shard>,
+    capacity: usize,
+}
+
+impl PriceStore {
+    /// `capacity` — точек на ряд.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_shards(capacity, DEFAULT_SHARDS)
+    }
+
+    pub fn with_shards(capacity: usize, shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| RwLock::new(HashMap::new())).collect(),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn shard(&self, exchange: &str, symbol: &str) -> &Shard {
+        let mut hasher = DefaultHasher::new();
+        exchange.hash(&mut hasher);
+        symbol.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+
+    fn read<R>(&self, exchange: &str, symbol: &str, f: impl FnOnce(&RingBuffer<Sample>) -> R) -> Option<R> {
+        let shard = self.shard(exchange, symbol).read();
+        shard.get(exchange)?.get(symbol).map(f)
+    }
+
+    pub fn insert(&self, exchange: &str, symbol: &str, sample: Sample) {
+        let mut shard = self.shard(exchange, symbol).write();
+        // Ключи выделяются только для нового ряда
+        if let Some(series) = shard.get_mut(exchange).and_then(|symbols| symbols.get_mut(symbol)) {
+            series.insert_sample(sample);
+            return;
+        }
+        let mut series = RingBuffer::new(self.capacity);
+        series.push(sample);
+        shard
+            .entry(exchange.to_string())
+            .or_default()
+            .insert(symbol.to_string(), series);
+    }
+
+    pub fn latest(&self, exchange: &str, symbol: &str) -> Option<Sample> {
+        self.read(exchange, symbol, |series| series.last().copied()).flatten()
+    }
+
+    /// Последние `n` точек, от старых к новым.
+    pub fn last_n(&self, exchange: &str, symbol: &str, n: usize) -> Vec<Sample> {
+        self.read(exchange, symbol, |series| {
+            series.iter().skip(series.len().saturating_sub(n)).copied().collect()
+        })
+        .unwrap_or_default()
+    }
+
+    /// Точки начиная с `since`, от старых к новым.
+    pub fn since(&self, exchange: &str, symbol: &str, since: DateTime<Utc>) -> Vec<Sample> {
+        self.read(exchange, symbol, |series| {
+            series.iter().skip(series.position_since(since)).copied().collect()
+        })
+        .unwrap_or_default()
+    }
+
+    /// Точки за последние `window` относительно текущего времени.
+    pub fn last_window(&self, exchange: &str, symbol: &str, window: Duration) -> Vec<Sample> {
+        self.since(exchange, symbol, Utc::now() - window)
+    }
+
+    /// Свечи по интервалам длины `bucket` начиная с `since`; пустые интервалы пропускаются.
+    pub fn ohlc(&self, exchange: &str, symbol: &str, since: DateTime<Utc>, bucket: Duration) -> Vec<Candle> {
+        let bucket_ms = bucket.num_milliseconds().max(1);
+        self.read(exchange, symbol, |series| {
+            let mut candles: Vec<Candle> = Vec::new();
+            for sample in series.iter().skip(series.position_since(since)) {
+                let start_ms = sample.timestamp.timestamp_millis().div_euclid(bucket_ms) * bucket_ms;
+                let start = DateTime::from_timestamp_millis(start_ms).unwrap_or(sample.timestamp);
+                match candles.last_mut() {
+                    Some(candle) if candle.start == start => {
+                        candle.high = candle.high.max(sample.price);
+                        candle.low = candle.low.min(sample.price);
+                        candle.close = sample.price;
+                        candle.count += 1;
+                    }
+                    _ => candles.push(Candle {
+                        start,
+                        open: sample.price,
+                        high: sample.price,
+                        low: sample.price,
+                        close: sample.price,
+                        count: 1,
+                    }),
+                }
+            }
+            candles
+        })
+        .unwrap_or_default()
+    }
+
+    /// Последняя цена пары по биржам.
+    pub fn latest_by_exchange(&self, symbol: &str) -> HashMap<String, Sample> {
+        let mut latest = HashMap::new();
+        for shard in &self.shards {
+            for (exchange, symbols) in shard.read().iter() {
+                if let Some(sample) = symbols.get(symbol).and_then(RingBuffer::last) {
+                    latest.insert(exchange.clone(), *sample);
+                }
+            }
+        }
+        latest
+    }
+
+    /// Все ряды: `(биржа, пара)`.
+    pub fn keys(&self) -> Vec<(String, String)> {
+        let mut keys = Vec::new();
+        for shard in &self.shards {
+            for (exchange, symbols) in shard.read().iter() {
+                keys.extend(symbols.keys().map(|symbol| (exchange.clone(), symbol.clone())));
+            }
+        }
+        keys
+    }
+
+    /// Точек во всех рядах.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.read().values().flat_map(|symbols| symbols.values()).map(RingBuffer::len).sum::<usize>())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| shard.read().is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn sample(start: DateTime<Utc>, secs: i64) -> Sample {
+        Sample {
+            timestamp: start + Duration::seconds(secs),
+            price: Decimal::from(secs),
+            volume: None,
+        }
+    }
+
+    fn buffer(start: DateTime<Utc>, capacity: usize, secs: &[i64]) -> RingBuffer<Sample> {
+        let mut buffer = RingBuffer::new(capacity);
+        for &s in secs {
+            buffer.insert_sample(sample(start, s));
+        }
+        buffer
+    }
+
+    fn seconds(buffer: &RingBuffer<Sample>, start: DateTime<Utc>) -> Vec<i64> {
+        buffer.iter().map(|s| (s.timestamp - start).num_seconds()).collect()
+    }
+
+    #[test]
+    fn in_order_samples_evict_the_oldest() {
+        let start = Utc::now();
+        let buffer = buffer(start, 3, &[10, 20, 30, 40]);
+
+        assert_eq!(seconds(&buffer, start), vec![20, 30, 40]);
+        assert_eq!(buffer.last().unwrap().price, dec!(40));
+    }
+
+    #[test]
+    fn late_sample_takes_its_place_in_a_partial_buffer() {
+        let start = Utc::now();
+        let buffer = buffer(start, 5, &[10, 30, 20, 5]);
+
+        assert_eq!(seconds(&buffer, start), vec![5, 10, 20, 30]);
+    }
+
+    #[test]
+    fn late_sample_in_a_full_buffer_evicts_the_oldest() {
+        let start = Utc::now();
+
+        let mut full = buffer(start, 3, &[10, 20, 30]);
+        full.insert_sample(sample(start, 15));
+        assert_eq!(seconds(&full, start), vec![15, 20, 30]);
+
+        let mut full = buffer(start, 3, &[10, 20, 30]);
+        full.insert_sample(sample(start, 25));
+        assert_eq!(seconds(&full, start), vec![20, 25, 30]);
+
+        // Точка с меткой самой старой вытесняет её, а не отбрасывается
+        let mut full = buffer(start, 3, &[10, 20, 30]);
+        full.insert_sample(sample(start, 10));
+        assert_eq!(seconds(&full, start), vec![10, 20, 30]);
+        assert_eq!(full.len(), 3);
+    }
+
+    #[test]
+    fn sample_older_than_a_full_buffer_is_dropped() {
+        let start = Utc::now();
+        let mut full = buffer(start, 3, &[10, 20, 30]);
+
+        full.insert_sample(sample(start, 5));
+
+        assert_eq!(seconds(&full, start), vec![10, 20, 30]);
+    }
+}
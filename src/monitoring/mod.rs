@@ -1,13 +1,17 @@
-use crate::alerts::{Alert, AlertManager, AlertType, AlertData};
-use crate::metrics::{MetricsCollector, PricePoint};
+use crate::alerts::{Alert, AlertData, AlertManager, AlertType, Metric, Observation, RuleEngine};
+use crate::config::VolatilityConfig;
+use crate::metrics::{MetricsCollector, Regime};
+use crate::types::{CrossableSpread, MarketPrice, PricePoint, TopOfBook};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use chrono::{DateTime, Utc};
+use log::info;
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
-use tracing::info;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketSnapshot {
@@ -18,150 +22,243 @@ pub struct MarketSnapshot {
     pub spread: Decimal,
 }
 
+/// Следит за рынком по метрикам: волатильность и режим рынка, спреды между биржами,
+/// состояние бирж. Значения метрик за проход собираются и проверяются правилами
+/// из `alerts.rules` в `evaluate_rules`.
 #[derive(Debug)]
 pub struct MarketMonitor {
     metrics: Arc<MetricsCollector>,
     alert_manager: Arc<Mutex<AlertManager>>,
-    volatility_threshold: Decimal,
-    spread_threshold: Decimal,
-    min_profit_threshold: Decimal,
+    rules: Arc<Mutex<RuleEngine>>,
+    volatility: VolatilityConfig,
+    observations: parking_lot::Mutex<Vec<Observation>>,
 }
 
 impl MarketMonitor {
     pub fn new(
         metrics: Arc<MetricsCollector>,
         alert_manager: Arc<Mutex<AlertManager>>,
+        rules: Arc<Mutex<RuleEngine>>,
+        volatility: &VolatilityConfig,
     ) -> Self {
         Self {
             metrics,
             alert_manager,
-            volatility_threshold: dec!(5),  // 5% волатильность
-            spread_threshold: dec!(2),      // 2% спред
-            min_profit_threshold: dec!(0.5), // 0.5% минимальная прибыль
+            rules,
+            volatility: volatility.clone(),
+            observations: parking_lot::Mutex::new(Vec::new()),
         }
     }
 
-    pub async fn process_price(&self, price_point: PricePoint) {
+    fn observe(&self, observation: Observation) {
+        self.observations.lock().push(observation);
+    }
+
+    pub async fn process_price(&self, price_point: &PricePoint) {
         // Записываем цену в метрики
-        self.metrics.record_price(price_point.clone()).await;
+        self.metrics.record_price(price_point);
 
-        // Проверяем волатильность
-        if let Some(volatility) = self.metrics.get_volatility(
+        // Волатильность за короткое окно
+        if let Some(volatility) = self.metrics.realized_volatility(
             &price_point.exchange,
             &price_point.symbol,
-            20 // окно в 20 точек
-        ).await {
-            if volatility > self.volatility_threshold {
-                let alert = Alert {
-                    alert_type: AlertType::HighVolatility,
-                    message: format!("High volatility detected for {} on {}: {}%",
-                        price_point.symbol, price_point.exchange, volatility),
-                    timestamp: Utc::now(),
-                    symbol: price_point.symbol.clone(),
-                    severity: 7,
-                    data: Some(AlertData {
-                        price_difference: Some(volatility),
-                        volume: price_point.volume,
-                        exchanges: Some(vec![price_point.exchange.clone()]),
-                        latency: None,
-                    }),
-                };
-                let mut alert_manager = self.alert_manager.lock().await;
-                alert_manager.send_alert(alert).await;
-            }
+            Duration::from_secs(self.volatility.short_window_secs),
+        ) {
+            self.observe(
+                Observation::new(Metric::Volatility, &price_point.symbol, vec![price_point.exchange.clone()], volatility.hourly)
+                    .with_volume(price_point.volume),
+            );
         }
-
-        // Анализируем спреды между биржами
-        self.analyze_spreads(&price_point.symbol).await;
     }
 
-    async fn analyze_spreads(&self, symbol: &str) {
-        let mut best_bid = Decimal::MIN;
-        let mut best_ask = Decimal::MAX;
-        let mut bid_exchange = String::new();
-        let mut ask_exchange = String::new();
-
-        let prices = self.metrics.get_all_current_prices(symbol).await;
-        
-        for (exchange, price) in prices {
-            if price > best_bid {
-                best_bid = price;
-                bid_exchange = exchange.clone();
-            }
-            if price < best_ask {
-                best_ask = price;
-                ask_exchange = exchange.clone();
-            }
-        }
+    /// Спред, который можно снять прямо сейчас: лучшая покупка на одной бирже против
+    /// лучшей продажи на другой, объём — меньший из объёмов на вершинах стаканов.
+    /// Биржи без стакана не участвуют: цена последней сделки не исполнима.
+    /// Для правил учитывается спред каждой связки бирж, возвращается лучший.
+    pub fn analyze_spreads(&self, symbol: &str, prices: &HashMap<String, MarketPrice>) -> Option<CrossableSpread> {
+        let books: Vec<(&String, TopOfBook)> = prices
+            .iter()
+            .filter_map(|(exchange, price)| Some((exchange, price.book?)))
+            .collect();
 
-        if !bid_exchange.is_empty() && !ask_exchange.is_empty() {
-            let spread = ((best_bid - best_ask) * dec!(100)) / best_ask;
-            
-            if spread > self.spread_threshold {
-                let alert = Alert {
-                    alert_type: AlertType::PriceArbitrage,
-                    message: format!(
-                        "Significant spread detected for {}: {:.2}% between {} and {}",
-                        symbol, spread, ask_exchange, bid_exchange
-                    ),
-                    timestamp: Utc::now(),
+        let mut best: Option<CrossableSpread> = None;
+        for (sell_exchange, sell) in &books {
+            for (buy_exchange, buy) in &books {
+                if sell_exchange == buy_exchange || buy.ask <= dec!(0) {
+                    continue;
+                }
+                let spread = CrossableSpread {
                     symbol: symbol.to_string(),
-                    severity: 8,
-                    data: Some(AlertData {
-                        price_difference: Some(spread),
-                        volume: None,
-                        exchanges: Some(vec![ask_exchange, bid_exchange]),
-                        latency: None,
-                    }),
+                    buy_exchange: buy_exchange.to_string(),
+                    ask: buy.ask,
+                    sell_exchange: sell_exchange.to_string(),
+                    bid: sell.bid,
+                    spread: ((sell.bid - buy.ask) * dec!(100) / buy.ask).round_dp(4),
+                    size: buy.ask_size.min(sell.bid_size),
                 };
-                let mut alert_manager = self.alert_manager.lock().await;
-                alert_manager.send_alert(alert).await;
+                self.observe(
+                    Observation::new(
+                        Metric::Spread,
+                        symbol,
+                        vec![spread.buy_exchange.clone(), spread.sell_exchange.clone()],
+                        spread.spread.to_f64().unwrap_or_default(),
+                    )
+                    .with_volume(Some(spread.size)),
+                );
+                if best.as_ref().is_none_or(|best| spread.spread > best.spread) {
+                    best = Some(spread);
+                }
             }
         }
+        self.metrics.set_crossable_spread(symbol, best.clone());
+        best
     }
 
-    pub async fn check_exchange_health(&self, exchange: &str) {
-        if let Some(metrics) = self.metrics.get_exchange_health(exchange).await {
-            if metrics.success_rate < 0.95 { // Менее 95% успешных запросов
-                let alert = Alert {
-                    alert_type: AlertType::ExchangeLatency,
-                    message: format!(
-                        "Exchange {} showing degraded performance. Success rate: {:.2}%",
-                        exchange, metrics.success_rate * 100.0
-                    ),
-                    timestamp: Utc::now(),
-                    symbol: String::new(),
-                    severity: 9,
-                    data: Some(AlertData {
-                        price_difference: None,
-                        volume: None,
-                        exchanges: Some(vec![exchange.to_string()]),
-                        latency: Some(metrics.latency.as_millis() as u64),
-                    }),
-                };
-                let mut alert_manager = self.alert_manager.lock().await;
-                alert_manager.send_alert(alert).await;
-            }
+    /// Переоценивает режим пары; оповещает о переходе в турбулентность.
+    pub async fn check_regime(&self, symbol: &str) {
+        let Some((reading, previous)) = self.metrics.update_regime(symbol, &self.volatility) else {
+            return;
+        };
+        if previous == Some(reading.regime) {
+            return;
+        }
+
+        if reading.regime == Regime::Turbulent {
+            let message = format!(
+                "{} turned turbulent: volatility {:.2}% per √hour vs {:.2}% background",
+                symbol, reading.short_volatility, reading.long_volatility
+            );
+            let alert = Alert::new(AlertType::MarketRegime, 6, symbol, message).with_data(AlertData {
+                price_difference: Decimal::from_f64_retain(reading.short_volatility).map(|v| v.round_dp(4)),
+                ..Default::default()
+            });
+            self.raise(alert).await;
+        } else if previous.is_some() {
+            info!("{} market regime is now {:?}", symbol, reading.regime);
         }
     }
-}
 
-// Добавляем метод в MetricsCollector
-impl MetricsCollector {
-    pub async fn get_all_current_prices(&self, symbol: &str) -> HashMap<String, Decimal> {
-        let history = self.price_history.lock().await;
-        let mut current_prices = HashMap::new();
-
-        for (key, prices) in history.iter() {
-            if let Some(exchange) = key.split('_').next() {
-                if let Some(latest) = prices.last() {
-                    if latest.symbol == symbol {
-                        current_prices.insert(exchange.to_string(), latest.price);
-                    }
-                }
+    pub fn check_exchange_health(&self, exchange: &str) {
+        if let Some(health) = self.metrics.get_exchange_health(exchange) {
+            if health.requests == 0 {
+                return;
             }
+            let exchanges = vec![exchange.to_string()];
+            let latency = health.p99_latency.as_millis() as u64;
+            self.observe(
+                Observation::new(Metric::SuccessRate, "", exchanges.clone(), health.success_rate)
+                    .with_latency(health.latency.as_millis() as u64),
+            );
+            self.observe(Observation::new(Metric::Latency, "", exchanges, latency as f64).with_latency(latency));
+        }
+    }
+
+    /// Проверяет правила по значениям, собранным за проход, и поднимает оповещения.
+    pub async fn evaluate_rules(&self) {
+        let observations = std::mem::take(&mut *self.observations.lock());
+        let alerts = self.rules.lock().await.evaluate(&observations, Utc::now());
+        for alert in alerts {
+            self.raise(alert).await;
         }
+    }
+
+    async fn raise(&self, alert: Alert) {
+        let alert_type = alert.alert_type;
+        let delivery = self.alert_manager.lock().await.send_alert(alert).await;
+        self.metrics.record_alert(alert_type, delivery);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AlertsConfig;
+
+    fn monitor() -> MarketMonitor {
+        MarketMonitor::new(
+            Arc::new(MetricsCollector::new()),
+            Arc::new(Mutex::new(AlertManager::new(&AlertsConfig {
+                silences_file: None,
+                ..AlertsConfig::default()
+            }))),
+            Arc::new(Mutex::new(RuleEngine::new(&[]))),
+            &VolatilityConfig::default(),
+        )
+    }
+
+    /// Котировка с ценой последней сделки `last` и вершиной стакана.
+    fn quote(last: Decimal, bid: Decimal, bid_size: Decimal, ask: Decimal, ask_size: Decimal) -> MarketPrice {
+        MarketPrice::new(last, None, 0).with_book(Some(TopOfBook {
+            bid,
+            bid_size,
+            ask,
+            ask_size,
+        }))
+    }
+
+    #[test]
+    fn crossed_books_give_positive_spread_sized_at_smaller_top() {
+        let monitor = monitor();
+        let prices = HashMap::from([
+            ("binance".to_string(), quote(dec!(100), dec!(99.9), dec!(3), dec!(100), dec!(2))),
+            ("bybit".to_string(), quote(dec!(100.4), dec!(100.5), dec!(0.5), dec!(100.6), dec!(4))),
+            // Без стакана биржа не участвует, как бы далеко ни ушла последняя сделка
+            ("kucoin".to_string(), MarketPrice::new(dec!(105), None, 0)),
+        ]);
+
+        let spread = monitor.analyze_spreads("BTCUSDT", &prices).unwrap();
+        assert_eq!(spread.buy_exchange, "binance");
+        assert_eq!(spread.ask, dec!(100));
+        assert_eq!(spread.sell_exchange, "bybit");
+        assert_eq!(spread.bid, dec!(100.5));
+        assert_eq!(spread.spread, dec!(0.5));
+        assert_eq!(spread.size, dec!(0.5));
+
+        // Правила видят обе связки бирж со стаканами
+        let observations = monitor.observations.lock();
+        assert_eq!(observations.len(), 2);
+        assert!(observations.iter().all(|o| o.metric == Metric::Spread && !o.exchanges.contains(&"kucoin".to_string())));
+
+        let published = monitor.metrics.crossable_spreads();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].1.spread, dec!(0.5));
+    }
+
+    #[test]
+    fn uncrossed_books_give_best_negative_spread() {
+        let monitor = monitor();
+        let prices = HashMap::from([
+            ("binance".to_string(), quote(dec!(100), dec!(99.9), dec!(1), dec!(100), dec!(1))),
+            ("bybit".to_string(), quote(dec!(100), dec!(99.95), dec!(2), dec!(100.05), dec!(2))),
+        ]);
+
+        let spread = monitor.analyze_spreads("BTCUSDT", &prices).unwrap();
+        // Лучшее из (99.95 − 100) / 100 и (99.9 − 100.05) / 100.05
+        assert_eq!(spread.buy_exchange, "binance");
+        assert_eq!(spread.sell_exchange, "bybit");
+        assert_eq!(spread.spread, dec!(-0.05));
+        assert_eq!(spread.size, dec!(1));
+
+        let observations = monitor.observations.lock();
+        let mut values: Vec<f64> = observations.iter().map(|o| o.value).collect();
+        values.sort_by(f64::total_cmp);
+        assert_eq!(values, vec![-0.1499, -0.05]);
+    }
+
+    #[test]
+    fn single_book_clears_published_spread() {
+        let monitor = monitor();
+        let crossed = HashMap::from([
+            ("binance".to_string(), quote(dec!(100), dec!(99.9), dec!(1), dec!(100), dec!(1))),
+            ("bybit".to_string(), quote(dec!(101), dec!(101), dec!(1), dec!(101.1), dec!(1))),
+        ]);
+        assert!(monitor.analyze_spreads("BTCUSDT", &crossed).is_some());
 
-        current_prices
+        let one_book = HashMap::from([
+            ("binance".to_string(), quote(dec!(100), dec!(99.9), dec!(1), dec!(100), dec!(1))),
+            ("bybit".to_string(), MarketPrice::new(dec!(101), None, 0)),
+        ]);
+        assert!(monitor.analyze_spreads("BTCUSDT", &one_book).is_none());
+        assert!(monitor.metrics.crossable_spreads().is_empty());
     }
 }
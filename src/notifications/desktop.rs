@@ -0,0 +1,21 @@
+use super::{Notification, NotificationChannel, NotificationError};
+use async_trait::async_trait;
+use tokio::process::Command;
+
+/// Всплывающее уведомление через `notify-send`; на других системах ничего не делает.
+#[derive(Debug)]
+pub struct DesktopChannel;
+
+#[async_trait]
+impl NotificationChannel for DesktopChannel {
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError> {
+        if cfg!(target_os = "linux") {
+            Command::new("notify-send")
+                .arg(&notification.title)
+                .arg(&notification.message)
+                .output()
+                .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use super::{check_response, Notification, NotificationChannel, NotificationError};
+use crate::exchanges::HttpClient;
+use async_trait::async_trait;
+
+/// Discord не принимает сообщения длиннее 2000 символов.
+const MAX_CONTENT: usize = 2000;
+
+/// Вебхук канала Discord.
+#[derive(Debug)]
+pub struct DiscordChannel {
+    http: HttpClient,
+    webhook_url: String,
+}
+
+impl DiscordChannel {
+    pub fn new(http: HttpClient, webhook_url: &str) -> Self {
+        Self {
+            http,
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for DiscordChannel {
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError> {
+        let content: String = format!("**{}**\n{}", notification.title, notification.text())
+            .chars()
+            .take(MAX_CONTENT)
+            .collect();
+        let response = self
+            .http
+            .post(&self.webhook_url)
+            .json(&serde_json::json!({ "content": content }))
+            .send()
+            .await?;
+        check_response(response).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::NotificationPriority;
+    use crate::testing::{MockServer, Reply};
+
+    #[tokio::test]
+    async fn posts_truncated_content() {
+        let server = MockServer::start(vec![Reply::Status(204, "")]).await;
+        let channel = DiscordChannel::new(HttpClient::default(), &server.url);
+
+        let notification = Notification::new("Volatility", "x".repeat(3000), NotificationPriority::Medium);
+        channel.send(&notification).await.unwrap();
+
+        let content = server.requests()[0].json()["content"].as_str().unwrap().to_string();
+        assert!(content.starts_with("**Volatility**\nxxx"));
+        assert_eq!(content.chars().count(), MAX_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn non_success_status_is_an_error() {
+        let server = MockServer::start(vec![Reply::Status(429, r#"{"retry_after":1}"#)]).await;
+        let channel = DiscordChannel::new(HttpClient::default(), &server.url);
+
+        let notification = Notification::new("Volatility", "ETHUSDT", NotificationPriority::Medium);
+        assert!(matches!(
+            channel.send(&notification).await,
+            Err(NotificationError::Status { status: 429, .. })
+        ));
+    }
+}
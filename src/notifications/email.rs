@@ -0,0 +1,238 @@
+use super::{Notification, NotificationChannel, NotificationError};
+use async_trait::async_trait;
+use base64::Engine;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use std::time::Duration;
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Письмо через SMTP-сервер. Без TLS: к внешним серверам — через локальный релей.
+#[derive(Debug)]
+pub struct EmailChannel {
+    host: String,
+    port: u16,
+    /// Логин и пароль для AUTH LOGIN
+    credentials: Option<(String, String)>,
+    from: String,
+    to: Vec<String>,
+    /// На весь диалог с сервером
+    timeout: Duration,
+}
+
+impl EmailChannel {
+    pub fn new(host: &str, port: u16, credentials: Option<(String, String)>, from: &str, to: Vec<String>) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            credentials,
+            from: from.to_string(),
+            to,
+            timeout: SMTP_TIMEOUT,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn message(&self, notification: &Notification) -> String {
+        let mut body = String::new();
+        for line in format!("{}\n{}", notification.title, notification.text()).lines() {
+            // Строка из одной точки закончила бы письмо
+            if line.starts_with('.') {
+                body.push('.');
+            }
+            body.push_str(line);
+            body.push_str("\r\n");
+        }
+        format!(
+            "From: {}\r\nTo: {}\r\nSubject: [{:?}] {}\r\nDate: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}",
+            self.from,
+            self.to.join(", "),
+            notification.priority,
+            notification.title,
+            notification.timestamp.to_rfc2822(),
+            body
+        )
+    }
+
+    async fn deliver(&self, notification: &Notification) -> Result<(), NotificationError> {
+        let stream = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        let mut session = Session {
+            stream: BufReader::new(stream),
+        };
+        session.expect(220).await?;
+        session.command("EHLO arbitrage-system", 250).await?;
+        if let Some((username, password)) = &self.credentials {
+            let encode = |value: &str| base64::engine::general_purpose::STANDARD.encode(value);
+            session.command("AUTH LOGIN", 334).await?;
+            session.command(&encode(username), 334).await?;
+            session.command(&encode(password), 235).await?;
+        }
+        session.command(&format!("MAIL FROM:<{}>", self.from), 250).await?;
+        for recipient in &self.to {
+            session.command(&format!("RCPT TO:<{}>", recipient), 250).await?;
+        }
+        session.command("DATA", 354).await?;
+        session.command(&format!("{}.", self.message(notification)), 250).await?;
+        session.command("QUIT", 221).await
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for EmailChannel {
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError> {
+        tokio::time::timeout(self.timeout, self.deliver(notification))
+            .await
+            .map_err(|_| NotificationError::Smtp(format!("{}:{} timed out", self.host, self.port)))?
+    }
+}
+
+/// Команда — ответ по одной строке протокола.
+struct Session {
+    stream: BufReader<TcpStream>,
+}
+
+impl Session {
+    async fn command(&mut self, line: &str, code: u16) -> Result<(), NotificationError> {
+        self.stream.get_mut().write_all(format!("{}\r\n", line).as_bytes()).await?;
+        self.expect(code).await
+    }
+
+    /// Читает ответ, в том числе многострочный (`250-...`), и сверяет код.
+    async fn expect(&mut self, code: u16) -> Result<(), NotificationError> {
+        loop {
+            let mut line = String::new();
+            if self.stream.read_line(&mut line).await? == 0 {
+                return Err(NotificationError::Smtp("connection closed".to_string()));
+            }
+            let reply: Option<u16> = line.get(..3).and_then(|c| c.parse().ok());
+            if reply != Some(code) {
+                return Err(NotificationError::Smtp(format!("expected {}, got {:?}", code, line.trim_end())));
+            }
+            if line.as_bytes().get(3) != Some(&b'-') {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::NotificationPriority;
+    use tokio::net::TcpListener;
+
+    /// SMTP-сервер по сценарию: на каждую команду — заданный ответ. Возвращает
+    /// принятые команды и тело письма.
+    async fn smtp_server(
+        greeting: &'static str,
+        script: Vec<(&'static str, &'static str)>,
+    ) -> (u16, tokio::task::JoinHandle<(Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+            stream.get_mut().write_all(greeting.as_bytes()).await.unwrap();
+            let (mut commands, mut data) = (Vec::new(), String::new());
+            for (command, reply) in script {
+                let mut line = String::new();
+                stream.read_line(&mut line).await.unwrap();
+                assert!(line.starts_with(command), "expected {}, got {:?}", command, line);
+                commands.push(line.trim_end().to_string());
+                if command == "DATA" {
+                    stream.get_mut().write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n").await.unwrap();
+                    loop {
+                        let mut line = String::new();
+                        stream.read_line(&mut line).await.unwrap();
+                        if line == ".\r\n" {
+                            break;
+                        }
+                        data.push_str(&line);
+                    }
+                }
+                stream.get_mut().write_all(reply.as_bytes()).await.unwrap();
+            }
+            (commands, data)
+        });
+        (port, handle)
+    }
+
+    fn notification() -> Notification {
+        Notification::new("Depeg", "USDT 0.985\n.\n.hidden", NotificationPriority::Critical)
+    }
+
+    #[tokio::test]
+    async fn delivers_with_auth_and_dot_stuffing() {
+        let (port, server) = smtp_server(
+            "220 mx ESMTP\r\n",
+            vec![
+                ("EHLO", "250-mx\r\n250-AUTH LOGIN PLAIN\r\n250 8BITMIME\r\n"),
+                ("AUTH LOGIN", "334 VXNlcm5hbWU6\r\n"),
+                ("Ym90", "334 UGFzc3dvcmQ6\r\n"),
+                ("aHVudGVyMg==", "235 Authentication successful\r\n"),
+                ("MAIL FROM:<bot@example.com>", "250 OK\r\n"),
+                ("RCPT TO:<ops@example.com>", "250 OK\r\n"),
+                ("RCPT TO:<cto@example.com>", "250 OK\r\n"),
+                ("DATA", "250 Queued\r\n"),
+                ("QUIT", "221 Bye\r\n"),
+            ],
+        )
+        .await;
+        let channel = EmailChannel::new(
+            "127.0.0.1",
+            port,
+            Some(("bot".to_string(), "hunter2".to_string())),
+            "bot@example.com",
+            vec!["ops@example.com".to_string(), "cto@example.com".to_string()],
+        );
+
+        channel.send(&notification()).await.unwrap();
+
+        let (commands, data) = server.await.unwrap();
+        assert_eq!(commands.len(), 9);
+        assert!(data.contains("Subject: [Critical] Depeg\r\n"));
+        assert!(data.contains("To: ops@example.com, cto@example.com\r\n"));
+        // Точка в начале строки удвоена: одиночная точка не обрывает письмо
+        assert!(data.contains("\r\nUSDT 0.985\r\n..\r\n..hidden\r\n"), "{:?}", data);
+    }
+
+    #[tokio::test]
+    async fn unexpected_reply_is_an_error() {
+        let (port, _server) = smtp_server(
+            "220 mx ESMTP\r\n",
+            vec![
+                ("EHLO", "250 mx\r\n"),
+                ("MAIL FROM:<bot@example.com>", "550 Sender rejected\r\n"),
+            ],
+        )
+        .await;
+        let channel = EmailChannel::new("127.0.0.1", port, None, "bot@example.com", vec!["ops@example.com".to_string()]);
+
+        match channel.send(&notification()).await {
+            Err(NotificationError::Smtp(message)) => assert!(message.contains("550 Sender rejected"), "{}", message),
+            other => panic!("expected SMTP error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn silent_server_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Соединение принимается, но приветствия нет
+        let _server = tokio::spawn(async move {
+            let _connection = listener.accept().await;
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+        let channel = EmailChannel::new("127.0.0.1", port, None, "bot@example.com", vec!["ops@example.com".to_string()])
+            .with_timeout(Duration::from_millis(100));
+
+        match channel.send(&notification()).await {
+            Err(NotificationError::Smtp(message)) => assert!(message.ends_with("timed out"), "{}", message),
+            other => panic!("expected timeout, got {:?}", other),
+        }
+    }
+}
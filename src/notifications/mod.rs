@@ -1,7 +1,47 @@
+mod desktop;
+mod discord;
+mod email;
+mod slack;
+mod telegram;
+mod webhook;
+
+pub use desktop::DesktopChannel;
+pub use discord::DiscordChannel;
+pub use email::EmailChannel;
+pub use slack::SlackChannel;
+pub use telegram::TelegramChannel;
+pub use webhook::WebhookChannel;
+
+use crate::alerts::Alert;
+use crate::config::{telegram_api_url, ChannelKind, Config};
+use crate::exchanges::HttpClient;
+use async_trait::async_trait;
+use futures::future::join_all;
+use log::warn;
 use serde::Serialize;
-use std::process::Command;
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    #[error("HTTP error: {0}")]
+    Http(reqwest::Error),
+    #[error("HTTP {status}: {body}")]
+    Status { status: u16, body: String },
+    #[error("SMTP error: {0}")]
+    Smtp(String),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<reqwest::Error> for NotificationError {
+    fn from(e: reqwest::Error) -> Self {
+        // В адресе вебхука или Bot API токен: в логи он не попадает
+        NotificationError::Http(e.without_url())
+    }
+}
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum NotificationPriority {
     Low,
     Medium,
@@ -9,62 +49,193 @@ pub enum NotificationPriority {
     Critical,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Notification {
     pub title: String,
     pub message: String,
     pub priority: NotificationPriority,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Оповещение, из которого собрано уведомление
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alert: Option<Alert>,
 }
 
-pub struct NotificationSystem {
-    telegram_bot_token: String,
-    telegram_chat_id: String,
+impl Notification {
+    pub fn new(title: impl Into<String>, message: impl Into<String>, priority: NotificationPriority) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            priority,
+            timestamp: chrono::Utc::now(),
+            alert: None,
+        }
+    }
+
+    /// Текст для мессенджеров: заголовок, сообщение, приоритет и время.
+    fn text(&self) -> String {
+        format!(
+            "{}\nPriority: {:?}\nTime: {}",
+            self.message,
+            self.priority,
+            self.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+        )
+    }
 }
 
-impl NotificationSystem {
-    pub fn new(telegram_bot_token: String, telegram_chat_id: String) -> Self {
+impl NotificationPriority {
+    /// Приоритет по важности оповещения (1–10).
+    pub fn from_severity(severity: u8) -> Self {
+        match severity {
+            0..=3 => NotificationPriority::Low,
+            4..=6 => NotificationPriority::Medium,
+            7..=8 => NotificationPriority::High,
+            _ => NotificationPriority::Critical,
+        }
+    }
+}
+
+impl From<&Alert> for Notification {
+    fn from(alert: &Alert) -> Self {
+        let title = if alert.symbol.is_empty() {
+            alert.alert_type.to_string()
+        } else {
+            format!("{} {}", alert.alert_type, alert.symbol)
+        };
         Self {
-            telegram_bot_token,
-            telegram_chat_id,
+            title,
+            message: alert.message.clone(),
+            priority: NotificationPriority::from_severity(alert.severity),
+            timestamp: alert.timestamp,
+            alert: Some(alert.clone()),
         }
     }
+}
+
+/// Куда доставляются уведомления: мессенджер, вебхук, почта.
+#[async_trait]
+pub trait NotificationChannel: Send + Sync {
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError>;
+}
+
+/// Проверяет ответ вебхука: не-2xx — ошибка с началом тела ответа.
+async fn check_response(response: reqwest::Response) -> Result<(), NotificationError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(NotificationError::Status {
+        status: status.as_u16(),
+        body: body.chars().take(200).collect(),
+    })
+}
+
+struct Channel {
+    name: String,
+    min_severity: u8,
+    sender: Box<dyn NotificationChannel>,
+}
+
+/// Рассылает оповещения по каналам. Оповещение с `route` уходит только в канал
+/// с этим именем, без него — во все каналы, чей порог важности оно проходит.
+#[derive(Default)]
+pub struct NotificationSystem {
+    channels: Vec<Channel>,
+}
+
+impl fmt::Debug for NotificationSystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.channels.iter().map(|c| &c.name)).finish()
+    }
+}
+
+impl NotificationSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    pub async fn send_notification(&self, notification: &Notification) -> Result<(), Box<dyn std::error::Error>> {
-        // Отправка в Telegram
-        let message = format!(
-            "*{}*\n{}\nPriority: {:?}\nTime: {}",
-            notification.title,
-            notification.message,
-            notification.priority,
-            notification.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
-        );
-
-        let url = format!(
-            "https://api.telegram.org/bot{}/sendMessage",
-            self.telegram_bot_token
-        );
-
-        let client = reqwest::Client::new();
-        let _ = client
-            .post(&url)
-            .form(&[
-                ("chat_id", &self.telegram_chat_id),
-                ("text", &message),
-                ("parse_mode", &"Markdown".to_string()),
-            ])
-            .send()
-            .await?;
-
-        // Также отправляем локальное уведомление
-        if cfg!(target_os = "linux") {
-            Command::new("notify-send")
-                .arg(&notification.title)
-                .arg(&notification.message)
-                .output()
-                .ok();
+    pub fn with_channel(mut self, name: &str, min_severity: u8, channel: impl NotificationChannel + 'static) -> Self {
+        self.channels.push(Channel {
+            name: name.to_string(),
+            min_severity,
+            sender: Box::new(channel),
+        });
+        self
+    }
+
+    /// Каналы из `notifications.channels` и секции `telegram`.
+    pub fn from_config(config: &Config, http: &HttpClient) -> Self {
+        let notifications = &config.notifications;
+        let mut system = Self::new();
+        if config.telegram.enabled {
+            system = system.with_channel(
+                "telegram",
+                notifications.min_severity,
+                TelegramChannel::new(http.clone(), &telegram_api_url(), &config.telegram.token, &config.telegram.chat_id),
+            );
+        }
+
+        for channel in notifications.channels.iter().filter(|c| c.enabled) {
+            let name = channel.name.as_str();
+            let min_severity = channel.min_severity.unwrap_or(notifications.min_severity);
+            system = match &channel.kind {
+                ChannelKind::Telegram { token, chat_id, api_url } => {
+                    system.with_channel(name, min_severity, TelegramChannel::new(http.clone(), api_url, token, chat_id))
+                }
+                ChannelKind::Slack { webhook_url } => {
+                    system.with_channel(name, min_severity, SlackChannel::new(http.clone(), webhook_url))
+                }
+                ChannelKind::Discord { webhook_url } => {
+                    system.with_channel(name, min_severity, DiscordChannel::new(http.clone(), webhook_url))
+                }
+                ChannelKind::Webhook { url, headers } => {
+                    system.with_channel(name, min_severity, WebhookChannel::new(http.clone(), url, headers.clone()))
+                }
+                ChannelKind::Email { host, port, username, password, from, to } => {
+                    let credentials = username.clone().zip(password.clone());
+                    system.with_channel(name, min_severity, EmailChannel::new(host, *port, credentials, from, to.clone()))
+                }
+                ChannelKind::Desktop => system.with_channel(name, min_severity, DesktopChannel),
+            };
         }
+        system
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    pub fn channel_names(&self) -> Vec<&str> {
+        self.channels.iter().map(|c| c.name.as_str()).collect()
+    }
+
+    /// Отправляет оповещение в подходящие каналы параллельно; ошибки логируются.
+    pub async fn notify(&self, alert: &Alert) {
+        let notification = Notification::from(alert);
+        let targets = self.channels.iter().filter(|channel| match &alert.route {
+            Some(route) => &channel.name == route,
+            None => alert.severity >= channel.min_severity,
+        });
+        let sends = targets.map(|channel| async {
+            if let Err(e) = channel.sender.send(&notification).await {
+                warn!("Failed to send alert #{} to {}: {}", alert.id, channel.name, e);
+            }
+        });
+        join_all(sends).await;
+    }
 
-        Ok(())
+    /// Отправляет уведомление во все каналы или в один по имени, без порогов важности.
+    /// Результат по каждому каналу.
+    pub async fn send_notification(
+        &self,
+        notification: &Notification,
+        only: Option<&str>,
+    ) -> Vec<(String, Result<(), NotificationError>)> {
+        let sends = self
+            .channels
+            .iter()
+            .filter(|channel| only.is_none_or(|name| channel.name == name))
+            .map(|channel| async { (channel.name.clone(), channel.sender.send(notification).await) });
+        join_all(sends).await
     }
 }
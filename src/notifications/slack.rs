@@ -0,0 +1,71 @@
+use super::{check_response, Notification, NotificationChannel, NotificationError};
+use crate::exchanges::HttpClient;
+use async_trait::async_trait;
+
+/// Incoming webhook Slack: текст в mrkdwn.
+#[derive(Debug)]
+pub struct SlackChannel {
+    http: HttpClient,
+    webhook_url: String,
+}
+
+impl SlackChannel {
+    pub fn new(http: HttpClient, webhook_url: &str) -> Self {
+        Self {
+            http,
+            webhook_url: webhook_url.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for SlackChannel {
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError> {
+        let text = format!("*{}*\n{}", notification.title, notification.text());
+        let response = self
+            .http
+            .post(&self.webhook_url)
+            .json(&serde_json::json!({ "text": text }))
+            .send()
+            .await?;
+        check_response(response).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::NotificationPriority;
+    use crate::testing::{MockServer, Reply};
+
+    #[tokio::test]
+    async fn posts_text_payload() {
+        let server = MockServer::start(vec![Reply::Status(200, "ok")]).await;
+        let channel = SlackChannel::new(HttpClient::default(), &format!("{}/services/T0/B0/X", server.url));
+
+        let notification = Notification::new("Spread", "BTCUSDT 1.2%", NotificationPriority::High);
+        channel.send(&notification).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/services/T0/B0/X");
+        let text = requests[0].json()["text"].as_str().unwrap().to_string();
+        assert!(text.starts_with("*Spread*\nBTCUSDT 1.2%\nPriority: High"), "{}", text);
+    }
+
+    #[tokio::test]
+    async fn non_success_status_is_an_error() {
+        let server = MockServer::start(vec![Reply::Status(404, "no_service")]).await;
+        let channel = SlackChannel::new(HttpClient::default(), &server.url);
+
+        let notification = Notification::new("Spread", "BTCUSDT 1.2%", NotificationPriority::High);
+        match channel.send(&notification).await {
+            Err(NotificationError::Status { status, body }) => {
+                assert_eq!(status, 404);
+                assert_eq!(body, "no_service");
+            }
+            other => panic!("expected status error, got {:?}", other),
+        }
+    }
+}
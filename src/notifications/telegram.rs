@@ -0,0 +1,136 @@
+use super::{check_response, Notification, NotificationChannel, NotificationError};
+use crate::exchanges::HttpClient;
+use async_trait::async_trait;
+
+/// Сообщение от бота в чат через Bot API `sendMessage`.
+#[derive(Debug)]
+pub struct TelegramChannel {
+    http: HttpClient,
+    url: String,
+    chat_id: String,
+}
+
+impl TelegramChannel {
+    pub fn new(http: HttpClient, api_url: &str, token: &str, chat_id: &str) -> Self {
+        Self {
+            http,
+            url: format!("{}/bot{}/sendMessage", api_url.trim_end_matches('/'), token),
+            chat_id: chat_id.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for TelegramChannel {
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError> {
+        let text = format!("<b>{}</b>\n{}", escape_html(&notification.title), escape_html(&notification.text()));
+        let response = self
+            .http
+            .post(&self.url)
+            .form(&[
+                ("chat_id", self.chat_id.as_str()),
+                ("text", text.as_str()),
+                ("parse_mode", "HTML"),
+            ])
+            .send()
+            .await?;
+        check_response(response).await
+    }
+}
+
+/// Разметка HTML, а не Markdown: в Markdown `_` из `price_arbitrage` и имён
+/// правил открывает курсив, и Bot API отклоняет сообщение.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifications::NotificationPriority;
+    use crate::testing::{MockServer, Reply};
+
+    #[tokio::test]
+    async fn posts_form_to_bot_api() {
+        let server = MockServer::start(vec![Reply::Status(200, r#"{"ok":true}"#)]).await;
+        let channel = TelegramChannel::new(HttpClient::default(), &format!("{}/", server.url), "123:abc", "-100");
+
+        let notification = Notification::new("Test", "hello", NotificationPriority::Low);
+        channel.send(&notification).await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/bot123:abc/sendMessage");
+        assert_eq!(request.header("content-type"), Some("application/x-www-form-urlencoded"));
+        assert!(request.body.starts_with("chat_id=-100&text=%3Cb%3ETest%3C%2Fb%3E%0Ahello%0APriority%3A+Low"), "{}", request.body);
+        assert!(request.body.ends_with("&parse_mode=HTML"));
+    }
+
+    #[tokio::test]
+    async fn alert_markup_characters_are_sent_as_text() {
+        let server = MockServer::start(vec![Reply::Status(200, r#"{"ok":true}"#)]).await;
+        let channel = TelegramChannel::new(HttpClient::default(), &server.url, "123:abc", "-100");
+
+        let notification = Notification::new(
+            "price_arbitrage BTCUSDT",
+            "wide_spread: BTCUSDT spread 2.5% on Binance→Bybit > 2% *now* <b> & [x]",
+            NotificationPriority::High,
+        );
+        channel.send(&notification).await.unwrap();
+
+        let body = &server.requests()[0].body;
+        let text = body
+            .split('&')
+            .find_map(|field| field.strip_prefix("text="))
+            .map(percent_decode)
+            .unwrap();
+        assert!(text.starts_with("<b>price_arbitrage BTCUSDT</b>\nwide_spread: BTCUSDT"), "{}", text);
+        // `_` и `*` в HTML не разметка; угловые скобки и `&` экранированы
+        assert!(text.contains("&gt; 2% *now* &lt;b&gt; &amp; [x]"), "{}", text);
+        assert!(body.ends_with("&parse_mode=HTML"));
+    }
+
+    /// Декодирует поле `application/x-www-form-urlencoded`.
+    fn percent_decode(field: &str) -> String {
+        let bytes = field.as_bytes();
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'+' => out.push(b' '),
+                b'%' => {
+                    out.push(u8::from_str_radix(&field[i + 1..i + 3], 16).unwrap());
+                    i += 2;
+                }
+                byte => out.push(byte),
+            }
+            i += 1;
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[tokio::test]
+    async fn non_success_status_is_an_error() {
+        let server = MockServer::start(vec![Reply::Status(401, r#"{"ok":false}"#)]).await;
+        let channel = TelegramChannel::new(HttpClient::default(), &server.url, "bad", "-100");
+
+        let notification = Notification::new("Test", "hello", NotificationPriority::Low);
+        assert!(matches!(
+            channel.send(&notification).await,
+            Err(NotificationError::Status { status: 401, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn network_error_hides_token() {
+        // Порт свободен: соединение отклоняется
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let channel = TelegramChannel::new(HttpClient::default(), &url, "123:abc", "-100");
+
+        let notification = Notification::new("Test", "hello", NotificationPriority::Low);
+        let error = channel.send(&notification).await.unwrap_err();
+        assert!(matches!(error, NotificationError::Http(_)));
+        assert!(!error.to_string().contains("123:abc"), "{}", error);
+    }
+}
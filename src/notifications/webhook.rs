@@ -0,0 +1,73 @@
+use super::{check_response, Notification, NotificationChannel, NotificationError};
+use crate::exchanges::HttpClient;
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+
+/// POST уведомления целиком в JSON, вместе с оповещением. Заголовки — например, токен доступа.
+#[derive(Debug)]
+pub struct WebhookChannel {
+    http: HttpClient,
+    url: String,
+    headers: BTreeMap<String, String>,
+}
+
+impl WebhookChannel {
+    pub fn new(http: HttpClient, url: &str, headers: BTreeMap<String, String>) -> Self {
+        Self {
+            http,
+            url: url.to_string(),
+            headers,
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationChannel for WebhookChannel {
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError> {
+        let mut request = self.http.post(&self.url).json(notification);
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        check_response(request.send().await?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::{Alert, AlertType};
+    use crate::testing::{MockServer, Reply};
+
+    #[tokio::test]
+    async fn posts_notification_with_headers() {
+        let server = MockServer::start(vec![Reply::Status(202, "")]).await;
+        let headers = BTreeMap::from([("Authorization".to_string(), "Bearer s3cr3t".to_string())]);
+        let channel = WebhookChannel::new(HttpClient::default(), &format!("{}/hook", server.url), headers);
+
+        let alert = Alert::new(AlertType::ExchangeLatency, 9, "", "bybit p99 latency 2500ms");
+        channel.send(&Notification::from(&alert)).await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/hook");
+        assert_eq!(request.header("authorization"), Some("Bearer s3cr3t"));
+        let payload = request.json();
+        assert_eq!(payload["message"], "bybit p99 latency 2500ms");
+        assert_eq!(payload["priority"], "Critical");
+        assert_eq!(payload["alert"]["severity"], 9);
+    }
+
+    #[tokio::test]
+    async fn non_success_status_is_an_error() {
+        let server = MockServer::start(vec![Reply::Status(500, "boom")]).await;
+        let channel = WebhookChannel::new(HttpClient::default(), &server.url, BTreeMap::new());
+
+        let alert = Alert::new(AlertType::ExchangeLatency, 9, "", "bybit p99 latency 2500ms");
+        match channel.send(&Notification::from(&alert)).await {
+            Err(NotificationError::Status { status, body }) => {
+                assert_eq!(status, 500);
+                assert_eq!(body, "boom");
+            }
+            other => panic!("expected status error, got {:?}", other),
+        }
+    }
+}
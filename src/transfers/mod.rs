@@ -0,0 +1,340 @@
+use crate::analysis::QuoteConverter;
+use crate::types::split_symbol;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Параметры вывода и ввода актива в одной сети.
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkInfo {
+    pub network: String,
+    /// Комиссия вывода в единицах актива
+    pub withdraw_fee: Decimal,
+    pub withdraw_min: Decimal,
+    pub deposit_enabled: bool,
+    pub withdraw_enabled: bool,
+}
+
+/// Сети, доступные для актива на бирже.
+#[derive(Debug, Clone, Serialize)]
+pub struct AssetNetworks {
+    pub asset: String,
+    pub networks: Vec<NetworkInfo>,
+}
+
+/// Биржи называют одни и те же сети по-разному (ERC20 / ETH, BEP20 / BSC).
+pub fn normalize_network(name: &str) -> String {
+    let upper = name.trim().to_uppercase();
+    let normalized = match upper.as_str() {
+        "ERC20" | "ETH" | "ETHEREUM" => "ETH",
+        "TRC20" | "TRX" | "TRON" => "TRX",
+        "BEP20" | "BSC" | "BEP20(BSC)" => "BSC",
+        "ARBITRUM" | "ARBITRUM ONE" | "ARBONE" | "ARBI" => "ARBITRUM",
+        "OPTIMISM" | "OP" => "OPTIMISM",
+        "POLYGON" | "MATIC" | "POL" => "POLYGON",
+        "SOLANA" | "SOL" => "SOL",
+        "AVAX C-CHAIN" | "AVAXC" | "CAVAX" => "AVAXC",
+        other => other,
+    };
+    normalized.to_string()
+}
+
+/// Маршрут перевода актива между двумя биржами.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    /// Нет данных о сетях хотя бы на одной из бирж
+    Unknown,
+    Available {
+        network: String,
+        fee: Decimal,
+        min: Decimal,
+    },
+    /// Вывод или ввод приостановлен, перевести актив нельзя
+    Suspended(String),
+}
+
+/// Стоимость перемещения инвентаря для одной сделки.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransferAssessment {
+    /// Амортизированная стоимость переводов в процентах от объёма сделки
+    pub cost_percent: Decimal,
+    /// Причина, по которой инвентарь нельзя переместить
+    pub warning: Option<String>,
+}
+
+/// Модель переводов: сети активов по биржам и параметры амортизации.
+/// Без данных о сетях или без курса котируемой валюты стоимость переводов считается нулевой.
+#[derive(Debug, Clone)]
+pub struct TransferModel {
+    assets: HashMap<String, HashMap<String, Vec<NetworkInfo>>>,
+    quote_tokens: Vec<String>,
+    amortize_over_trades: u32,
+    trade_amount: Decimal,
+    /// Курсы котируемых валют к опорной: объём сделки задан в опорной валюте
+    converter: QuoteConverter,
+}
+
+impl TransferModel {
+    /// `amortize_over_trades` — сколько сделок покрывает одна ребалансировка,
+    /// `trade_amount` — объём сделки в опорной валюте `reference`.
+    pub fn new(quote_tokens: Vec<String>, reference: &str, amortize_over_trades: u32, trade_amount: Decimal) -> Self {
+        Self {
+            assets: HashMap::new(),
+            quote_tokens,
+            amortize_over_trades,
+            trade_amount,
+            converter: QuoteConverter::new(reference, dec!(0)),
+        }
+    }
+
+    /// Курсы для пересчёта объёма сделки в котируемую валюту пары (`ETHBTC` → BTC).
+    pub fn with_converter(mut self, converter: QuoteConverter) -> Self {
+        self.converter = converter;
+        self
+    }
+
+    pub fn with_networks(mut self, networks: HashMap<String, Vec<AssetNetworks>>) -> Self {
+        for (exchange, assets) in networks {
+            self.set_networks(&exchange, assets);
+        }
+        self
+    }
+
+    pub fn set_networks(&mut self, exchange: &str, assets: Vec<AssetNetworks>) {
+        self.assets.insert(
+            exchange.to_lowercase(),
+            assets
+                .into_iter()
+                .map(|asset| (asset.asset.to_uppercase(), asset.networks))
+                .collect(),
+        );
+    }
+
+    pub fn networks(&self, exchange: &str, asset: &str) -> Option<&[NetworkInfo]> {
+        self.assets
+            .get(&exchange.to_lowercase())?
+            .get(&asset.to_uppercase())
+            .map(Vec::as_slice)
+    }
+
+    /// Самый дешёвый маршрут `asset` с биржи `from` на биржу `to` по общей сети.
+    pub fn route(&self, asset: &str, from: &str, to: &str) -> Route {
+        let (Some(source), Some(destination)) = (self.networks(from, asset), self.networks(to, asset)) else {
+            return Route::Unknown;
+        };
+
+        if !source.iter().any(|n| n.withdraw_enabled) {
+            return Route::Suspended(format!("{} withdrawals suspended on {}", asset, from));
+        }
+        if !destination.iter().any(|n| n.deposit_enabled) {
+            return Route::Suspended(format!("{} deposits suspended on {}", asset, to));
+        }
+
+        source
+            .iter()
+            .filter(|n| n.withdraw_enabled)
+            .filter(|n| {
+                let network = normalize_network(&n.network);
+                destination
+                    .iter()
+                    .any(|d| d.deposit_enabled && normalize_network(&d.network) == network)
+            })
+            .min_by_key(|n| n.withdraw_fee)
+            .map(|n| Route::Available {
+                network: normalize_network(&n.network),
+                fee: n.withdraw_fee,
+                min: n.withdraw_min,
+            })
+            .unwrap_or_else(|| {
+                Route::Suspended(format!("no open {} network from {} to {}", asset, from, to))
+            })
+    }
+
+    /// Оценка переводов для сделки «купить `pair` на `buy_exchange`, продать на `sell_exchange`»:
+    /// базовый актив возвращается на биржу продажи, котируемый — на биржу покупки.
+    pub fn assess(&self, pair: &str, buy_exchange: &str, sell_exchange: &str, price: Decimal) -> TransferAssessment {
+        let mut assessment = TransferAssessment::default();
+        let Some((base, quote)) = split_symbol(pair, &self.quote_tokens) else {
+            return assessment;
+        };
+
+        // Комиссии пересчитываются в котируемую валюту
+        let legs = [
+            (self.route(base, buy_exchange, sell_exchange), price),
+            (self.route(quote, sell_exchange, buy_exchange), dec!(1)),
+        ];
+
+        let mut cost = dec!(0);
+        for (route, unit_price) in legs {
+            match route {
+                Route::Available { fee, .. } => cost += fee * unit_price,
+                Route::Suspended(reason) => {
+                    assessment.warning.get_or_insert(reason);
+                }
+                Route::Unknown => {}
+            }
+        }
+
+        // Комиссии в котируемой валюте пары, объём сделки — в опорной
+        let rate = self
+            .converter
+            .rate(buy_exchange, quote)
+            .or_else(|| self.converter.rate(sell_exchange, quote));
+        if let Some(rate) = rate.filter(|_| self.amortize_over_trades > 0 && self.trade_amount > dec!(0)) {
+            let trade_amount = self.trade_amount / rate;
+            assessment.cost_percent = cost / Decimal::from(self.amortize_over_trades) / trade_amount * dec!(100);
+        }
+        assessment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn network(name: &str, withdraw_fee: Decimal, deposit_enabled: bool, withdraw_enabled: bool) -> NetworkInfo {
+        NetworkInfo {
+            network: name.to_string(),
+            withdraw_fee,
+            withdraw_min: dec!(0.01),
+            deposit_enabled,
+            withdraw_enabled,
+        }
+    }
+
+    fn asset(asset: &str, networks: Vec<NetworkInfo>) -> AssetNetworks {
+        AssetNetworks {
+            asset: asset.to_string(),
+            networks,
+        }
+    }
+
+    /// ETH и USDT на Binance и Bybit, 10 сделок на ребалансировку по 100 USDT.
+    fn model(binance_eth: Vec<NetworkInfo>, bybit_eth: Vec<NetworkInfo>) -> TransferModel {
+        let usdt = || asset("USDT", vec![network("TRC20", dec!(1), true, true)]);
+        let btc = || asset("BTC", vec![network("BTC", dec!(0.0001), true, true)]);
+        TransferModel::new(vec!["USDT".to_string(), "BTC".to_string()], "USDT", 10, dec!(100)).with_networks(
+            HashMap::from([
+                ("Binance".to_string(), vec![asset("eth", binance_eth), usdt(), btc()]),
+                ("bybit".to_string(), vec![asset("ETH", bybit_eth), usdt(), btc()]),
+            ]),
+        )
+    }
+
+    fn open_eth() -> TransferModel {
+        model(
+            vec![network("ERC20", dec!(0.005), true, true), network("ARBITRUM", dec!(0.001), true, true)],
+            vec![network("ETH", dec!(0.004), true, true), network("ARBONE", dec!(0.0008), true, true)],
+        )
+    }
+
+    #[test]
+    fn normalizes_network_aliases() {
+        assert_eq!(normalize_network(" bep20 "), "BSC");
+        assert_eq!(normalize_network("Arbitrum One"), "ARBITRUM");
+        assert_eq!(normalize_network("erc20"), "ETH");
+        // Неизвестные сети сравниваются по имени как есть
+        assert_eq!(normalize_network("ton"), "TON");
+    }
+
+    #[test]
+    fn route_takes_cheapest_common_network() {
+        let transfers = open_eth();
+        assert_eq!(
+            transfers.route("ETH", "binance", "bybit"),
+            Route::Available {
+                network: "ARBITRUM".to_string(),
+                fee: dec!(0.001),
+                min: dec!(0.01),
+            }
+        );
+        assert_eq!(
+            transfers.route("eth", "BYBIT", "binance"),
+            Route::Available {
+                network: "ARBITRUM".to_string(),
+                fee: dec!(0.0008),
+                min: dec!(0.01),
+            }
+        );
+    }
+
+    #[test]
+    fn route_is_unknown_without_network_data() {
+        let transfers = open_eth();
+        assert_eq!(transfers.route("SOL", "binance", "bybit"), Route::Unknown);
+        assert_eq!(transfers.route("ETH", "binance", "kucoin"), Route::Unknown);
+    }
+
+    #[test]
+    fn route_is_suspended_without_open_network() {
+        let transfers = model(
+            vec![network("ERC20", dec!(0.005), false, false)],
+            vec![network("ERC20", dec!(0.004), false, true)],
+        );
+        assert_eq!(
+            transfers.route("ETH", "binance", "bybit"),
+            Route::Suspended("ETH withdrawals suspended on binance".to_string())
+        );
+        assert_eq!(
+            transfers.route("ETH", "bybit", "binance"),
+            Route::Suspended("ETH deposits suspended on binance".to_string())
+        );
+
+        // Обе стороны открыты, но общей сети нет
+        let transfers = model(
+            vec![network("TON", dec!(0.001), true, true)],
+            vec![network("ERC20", dec!(0.004), true, true)],
+        );
+        assert_eq!(
+            transfers.route("ETH", "binance", "bybit"),
+            Route::Suspended("no open ETH network from binance to bybit".to_string())
+        );
+    }
+
+    #[test]
+    fn assess_amortizes_both_legs_over_trades() {
+        // ETH обратно на Bybit: 0.001 ETH × 2000 = 2 USDT, USDT обратно на Binance: 1 USDT.
+        // 3 USDT на 10 сделок по 100 USDT — 0.3% с каждой
+        let assessment = open_eth().assess("ETHUSDT", "binance", "bybit", dec!(2000));
+        assert_eq!(assessment.cost_percent, dec!(0.3));
+        assert_eq!(assessment.warning, None);
+    }
+
+    #[test]
+    fn assess_converts_trade_amount_into_pair_quote() {
+        // ETHBTC: 0.001 ETH × 0.05 + 0.0001 BTC = 0.00015 BTC; 100 USDT по 50000 — 0.002 BTC
+        let mut converter = QuoteConverter::new("USDT", dec!(0));
+        converter.set_rate("binance", "BTC", dec!(50000));
+        let transfers = open_eth().with_converter(converter);
+        let assessment = transfers.assess("ETHBTC", "binance", "bybit", dec!(0.05));
+        assert_eq!(assessment.cost_percent, dec!(0.75));
+
+        // Без курса BTC объём сделки в BTC неизвестен, стоимость не оценивается
+        let assessment = open_eth().assess("ETHBTC", "binance", "bybit", dec!(0.05));
+        assert_eq!(assessment.cost_percent, dec!(0));
+    }
+
+    #[test]
+    fn assess_warns_when_inventory_cannot_move() {
+        let transfers = model(
+            vec![network("ERC20", dec!(0.005), true, true)],
+            vec![network("ERC20", dec!(0.004), false, true)],
+        );
+        let assessment = transfers.assess("ETHUSDT", "binance", "bybit", dec!(2000));
+        assert_eq!(assessment.warning.as_deref(), Some("ETH deposits suspended on bybit"));
+        // Доступная нога USDT всё равно учитывается: 1 USDT на 10 сделок по 100 USDT
+        assert_eq!(assessment.cost_percent, dec!(0.1));
+    }
+
+    #[test]
+    fn assess_is_free_for_unknown_assets_and_pairs() {
+        let transfers = open_eth();
+        let assessment = transfers.assess("SOLUSDT", "binance", "bybit", dec!(150));
+        assert_eq!(assessment.warning, None);
+        // Остаётся только нога USDT
+        assert_eq!(assessment.cost_percent, dec!(0.1));
+
+        assert_eq!(transfers.assess("ETHEUR", "binance", "bybit", dec!(1800)), TransferAssessment::default());
+    }
+}
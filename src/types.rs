@@ -1,11 +1,17 @@
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct MarketPrice {
+    /// Цена последней сделки
     pub price: Decimal,
     pub volume_24h: Option<Decimal>,
+    /// Время события на бирже, приведённое к локальным часам, мс от эпохи
     pub timestamp: i64,
+    /// Лучшие цены стакана; `None`, если биржа их не отдала или сторона стакана пуста
+    #[serde(default)]
+    pub book: Option<TopOfBook>,
 }
 
 impl MarketPrice {
@@ -14,13 +20,69 @@ impl MarketPrice {
             price,
             volume_24h,
             timestamp,
+            book: None,
         }
     }
+
+    pub fn with_book(mut self, book: Option<TopOfBook>) -> Self {
+        self.book = book;
+        self
+    }
+
+    /// Цена покупки рыночным ордером: лучшая продажа стакана, без стакана — цена последней сделки.
+    pub fn ask(&self) -> Decimal {
+        self.book.map(|book| book.ask).filter(|ask| *ask > Decimal::ZERO).unwrap_or(self.price)
+    }
+
+    /// Цена продажи рыночным ордером: лучшая покупка стакана, без стакана — цена последней сделки.
+    pub fn bid(&self) -> Decimal {
+        self.book.map(|book| book.bid).filter(|bid| *bid > Decimal::ZERO).unwrap_or(self.price)
+    }
+}
+
+/// Вершина стакана: лучшие цены покупки и продажи и объёмы на них в базовом активе.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TopOfBook {
+    pub bid: Decimal,
+    pub bid_size: Decimal,
+    pub ask: Decimal,
+    pub ask_size: Decimal,
+}
+
+/// Цена с одной биржи в формате истории цен (`data/metrics/price_history.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricePoint {
+    pub exchange: String,
+    pub symbol: String,
+    pub price: Decimal,
+    pub timestamp: DateTime<Utc>,
+    pub volume: Option<Decimal>,
+}
+
+impl PricePoint {
+    pub fn from_market_price(exchange: &str, symbol: &str, price: &MarketPrice) -> Self {
+        Self {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            price: price.price,
+            timestamp: DateTime::from_timestamp_millis(price.timestamp).unwrap_or_else(Utc::now),
+            volume: price.volume_24h,
+        }
+    }
+
+    pub fn to_market_price(&self) -> MarketPrice {
+        MarketPrice::new(self.price, self.volume, self.timestamp.timestamp_millis())
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ArbitrageOpportunity {
+    /// Идентификатор из `OpportunityTracker`; одинаков для связки, пока она держится
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
     pub pair: String,
+    /// Символ на бирже продажи, если котировка отличается от `pair` (`BTCUSDC` → `BTCUSDT`)
+    pub sell_pair: Option<String>,
     pub buy_price: Decimal,
     pub buy_exchange: String,
     pub sell_price: Decimal,
@@ -28,4 +90,45 @@ pub struct ArbitrageOpportunity {
     pub spread: Decimal,
     pub volume: Option<Decimal>,
     pub potential_profit: Decimal,
+    /// Амортизированная стоимость переводов в процентах, уже вычтена из `spread`
+    pub transfer_cost: Decimal,
+    /// Почему инвентарь нельзя переместить между биржами (вывод/ввод приостановлен)
+    pub transfer_warning: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ArbitrageOpportunity {
+    /// Подпись связки для вывода: `BTCUSDT` или `BTCUSDC→BTCUSDT`.
+    pub fn symbols(&self) -> String {
+        match &self.sell_pair {
+            Some(sell_pair) => format!("{}→{}", self.pair, sell_pair),
+            None => self.pair.clone(),
+        }
+    }
+}
+
+/// Лучший исполнимый спред пары между биржами.
+#[derive(Debug, Clone, Serialize)]
+pub struct CrossableSpread {
+    pub symbol: String,
+    pub buy_exchange: String,
+    /// Лучшая продажа на бирже покупки
+    pub ask: Decimal,
+    pub sell_exchange: String,
+    /// Лучшая покупка на бирже продажи
+    pub bid: Decimal,
+    /// В процентах от `ask`, до комиссий
+    pub spread: Decimal,
+    /// Объём в базовом активе, доступный по обеим ценам
+    pub size: Decimal,
+}
+
+/// Делит символ на базовый и котируемый актив по списку котируемых токенов:
+/// `BTCUSDT` → `("BTC", "USDT")`. Выбирается самый длинный подходящий суффикс.
+pub fn split_symbol<'a>(symbol: &'a str, quote_tokens: &[String]) -> Option<(&'a str, &'a str)> {
+    quote_tokens
+        .iter()
+        .filter(|quote| symbol.len() > quote.len() && symbol.ends_with(quote.as_str()))
+        .max_by_key(|quote| quote.len())
+        .map(|quote| symbol.split_at(symbol.len() - quote.len()))
 }
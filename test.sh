@@ -29,6 +29,6 @@ fi
 
 # Проверяем конфигурацию
 echo -e "\nChecking configuration..."
-cargo run --quiet --bin check_config
+cargo run --quiet -- check-config
 
 echo -e "\nTest suite completed at $(date -u '+%Y-%m-%d %H:%M:%S UTC')"